@@ -4,10 +4,13 @@ case $ENV in
   "staging")
     ECDSA_KEY_NAME="test_key_1"
     WALLET="cvthj-wyaaa-aaaad-aaaaq-cai"
+    # The staging ckETH minter converts Sepolia ETH.
+    CKETH_CHAIN_ID="11155111"
     ;;
   "ic")
     ECDSA_KEY_NAME="key_1"
     WALLET="yit3i-lyaaa-aaaan-qeavq-cai"
+    CKETH_CHAIN_ID="1"
     ;;
   *)
     ECDSA_KEY_NAME="dfx_test_key"
@@ -21,6 +24,10 @@ if [ -n "${ENV+1}" ]; then
          allowed_callers = vec {};
      }
   })" --network "$ENV" --wallet "$WALLET"
+
+  # The ckETH minter of the network, the local one is set once deployed by deploy.cketh.sh.
+  CKETH_MINTER_ID="$(dfx canister id cketh_minter --network "$ENV")"
+  dfx canister call backend set_cketh_minter "(opt record { canister_id = principal \"$CKETH_MINTER_ID\"; chain_id = $CKETH_CHAIN_ID : nat64 })" --network "$ENV" --wallet "$WALLET"
 else
   dfx deploy backend --argument "(variant {
     Init = record {
@@ -60,5 +60,8 @@ dfx deploy cketh_index --network "$DFX_NETWORK" --argument "(opt variant {
    }
 })"
 
-echo "Step 5: transfer ckETH to principal..."
+echo "Step 5: let the backend track the conversions with the minter..."
+dfx canister call backend --network "$DFX_NETWORK" set_cketh_minter "(opt record { canister_id = principal \"$MINTERID\"; chain_id = 11155111 : nat64 })"
+
+echo "Step 6: transfer ckETH to principal..."
 dfx canister call cketh_ledger --network "$DFX_NETWORK" icrc1_transfer "(record {from=null; to=record { owner= principal \"x4w27-so7wg-cudsa-yy7fh-wcpy5-njul4-q54tv-euzzi-tdnzz-ill46-zqe\";}; amount=500_000_000_000_000_000; fee=null; memo=null; created_at_time=null;})"
@@ -1,6 +1,6 @@
 #!/bin/bash
 
-POCKET_IC_SERVER_VERSION=3.0.1
+POCKET_IC_SERVER_VERSION=5.0.0
 OISY_UPGRADE_VERSION=v0.0.13
 
 # If a backend wasm file exists at the root, it will be used for the tests.
@@ -15,6 +15,11 @@ else
     cargo build --locked --target wasm32-unknown-unknown --release -p backend
 fi
 
+# The mocks of the EVM RPC canister, of the ckETH minter and of an ICRC ledger the backend calls.
+
+echo "Building mock canisters."
+cargo build --locked --target wasm32-unknown-unknown --release -p mock_evm_rpc -p mock_cketh_minter -p mock_icrc_ledger
+
 # We use a previous version of the release to ensure upgradability
 
 OISY_UPGRADE_PATH="./backend-${OISY_UPGRADE_VERSION}.wasm.gz"
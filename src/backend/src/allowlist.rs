@@ -0,0 +1,186 @@
+use crate::denylist::APPROVAL_SELECTORS;
+use crate::{mutate_state, parse_eth_address, read_state, Candid, StoredPrincipal};
+use candid::{Nat, Principal};
+use shared::types::allowlist::{Allowlist, PendingAllowlistAddress};
+
+/// The time before an address added in allowlist mode is allowlisted, or before the mode turns off.
+/// It leaves the user time to react if their session is compromised.
+const CONFIRMATION_PERIOD_NANOS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+const MAX_ALLOWLIST_LENGTH: usize = 100;
+
+/// The ERC-20 `transfer(address,uint256)` and `transferFrom(address,address,uint256)` selectors.
+const TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+const TRANSFER_FROM_SELECTOR: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+
+fn normalize_address(address: &str) -> String {
+    format!("0x{}", hex::encode(parse_eth_address(address)))
+}
+
+/// Returns the allowlist of the principal, with the changes whose confirmation period has elapsed applied.
+///
+/// The changes are applied when the allowlist is read rather than by timers, so that nothing has to be rescheduled
+/// after an upgrade.
+pub fn get_allowlist(principal: &Principal, now: u64) -> Allowlist {
+    let mut allowlist = read_state(|s| {
+        s.allowlist
+            .get(&StoredPrincipal(*principal))
+            .unwrap_or_default()
+            .0
+    });
+    apply_due_changes(&mut allowlist, now);
+    allowlist
+}
+
+fn mutate_allowlist<R>(principal: &Principal, now: u64, f: impl FnOnce(&mut Allowlist) -> R) -> R {
+    let mut allowlist = get_allowlist(principal, now);
+    let result = f(&mut allowlist);
+    mutate_state(|s| {
+        s.allowlist
+            .insert(StoredPrincipal(*principal), Candid(allowlist))
+    });
+    result
+}
+
+/// Turns the allowlist mode on, cancelling a pending disabling.
+pub fn enable(principal: &Principal, now: u64) {
+    mutate_allowlist(principal, now, |allowlist| {
+        allowlist.enabled = true;
+        allowlist.pending_disable_timestamp = None;
+    });
+}
+
+/// Turns the allowlist mode off once the confirmation period has elapsed.
+pub fn request_disable(principal: &Principal, now: u64) {
+    mutate_allowlist(principal, now, |allowlist| {
+        if !allowlist.enabled {
+            ic_cdk::trap("Allowlist mode is not enabled");
+        }
+        allowlist
+            .pending_disable_timestamp
+            .get_or_insert(now + CONFIRMATION_PERIOD_NANOS);
+    });
+}
+
+/// Adds an address right away if the allowlist mode is off, otherwise once the confirmation period has elapsed.
+pub fn add_address(principal: &Principal, address: &str, now: u64) {
+    let address = normalize_address(address);
+
+    mutate_allowlist(principal, now, |allowlist| {
+        if allowlist.addresses.contains(&address)
+            || allowlist
+                .pending_addresses
+                .iter()
+                .any(|p| p.address == address)
+        {
+            return;
+        }
+        if allowlist.addresses.len() + allowlist.pending_addresses.len() >= MAX_ALLOWLIST_LENGTH {
+            ic_cdk::trap(&format!(
+                "Allowlist length should not exceed {MAX_ALLOWLIST_LENGTH}"
+            ));
+        }
+
+        if !allowlist.enabled {
+            allowlist.addresses.push(address);
+            return;
+        }
+
+        allowlist.pending_addresses.push(PendingAllowlistAddress {
+            address,
+            confirmation_timestamp: now + CONFIRMATION_PERIOD_NANOS,
+        });
+    });
+}
+
+/// Removes an address, whether it is allowlisted or pending.
+pub fn remove_address(principal: &Principal, address: &str, now: u64) {
+    let address = normalize_address(address);
+
+    mutate_allowlist(principal, now, |allowlist| {
+        allowlist.addresses.retain(|a| *a != address);
+        allowlist.pending_addresses.retain(|p| p.address != address);
+    });
+}
+
+/// Applies the additions and the disabling whose confirmation period has elapsed.
+fn apply_due_changes(allowlist: &mut Allowlist, now: u64) {
+    let (due, pending): (Vec<_>, Vec<_>) = allowlist
+        .pending_addresses
+        .drain(..)
+        .partition(|p| p.confirmation_timestamp <= now);
+    allowlist.pending_addresses = pending;
+    allowlist
+        .addresses
+        .extend(due.into_iter().map(|p| p.address));
+
+    if allowlist
+        .pending_disable_timestamp
+        .is_some_and(|timestamp| timestamp <= now)
+    {
+        allowlist.enabled = false;
+        allowlist.pending_disable_timestamp = None;
+    }
+}
+
+/// Traps if the allowlist mode is on and the transaction could move funds to an address that is not allowlisted.
+///
+/// The destination has to be allowlisted. A transaction with data has to be an ERC-20 transfer, whose recipient is
+/// decoded from the data, or an approval, whose spender is decoded from the data, and has to leave the value to the
+/// allowlisted counterparty: the destination, a contract whose code is unknown, would receive it otherwise. Any other
+/// call could hand the funds of the user to a third party, e.g. through a contract-specific approval.
+pub fn assert_recipient_allowed(principal: &Principal, to: &str, value: &Nat, data: Option<&[u8]>) {
+    let allowlist = get_allowlist(principal, ic_cdk::api::time());
+    if !allowlist.enabled {
+        return;
+    }
+
+    let assert_allowlisted = |role: &str, address: [u8; 20]| {
+        if !allowlist
+            .addresses
+            .iter()
+            .any(|allowed| parse_eth_address(allowed) == address)
+        {
+            ic_cdk::trap(&format!(
+                "{role} 0x{} is not in the allowlist",
+                hex::encode(address)
+            ));
+        }
+    };
+
+    assert_allowlisted("Recipient", parse_eth_address(to));
+
+    let data = match data {
+        Some(data) if !data.is_empty() => data,
+        _ => return,
+    };
+
+    // The arguments are left-padded to 32 bytes.
+    let (role, counterparty): (&str, [u8; 20]) = match data {
+        data if data.len() >= 36 && data[..4] == TRANSFER_SELECTOR => {
+            ("Recipient", data[16..36].try_into().unwrap())
+        }
+        data if data.len() >= 68 && data[..4] == TRANSFER_FROM_SELECTOR => {
+            ("Recipient", data[48..68].try_into().unwrap())
+        }
+        data if data.len() >= 36 && APPROVAL_SELECTORS.iter().any(|s| data[..4] == *s) => {
+            ("Spender", data[16..36].try_into().unwrap())
+        }
+        _ => ic_cdk::trap("Only token transfers and approvals can be signed in allowlist mode"),
+    };
+
+    if *value != 0u64 {
+        ic_cdk::trap("A token transfer should not carry a value in allowlist mode");
+    }
+    assert_allowlisted(role, counterparty);
+}
+
+/// Traps if the allowlist mode is on, since the allowlist only applies to the EVM transactions the backend decodes.
+///
+/// A precomputed hash could be the hash of any transaction, a message could authorize one off chain, and the Solana and
+/// Bitcoin transactions are not decoded.
+pub fn assert_not_in_allowlist_mode(principal: &Principal, operation: &str) {
+    if get_allowlist(principal, ic_cdk::api::time()).enabled {
+        ic_cdk::trap(&format!("{operation} is not allowed in allowlist mode"));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::evm_rpc::{
+    batch_request, check_chain_supported, consensus, parse_quantity, JsonRpcCall,
+};
+use crate::{read_state, u256_to_nat, StoredPrincipal};
+use candid::{Nat, Principal};
+use serde_json::json;
+use shared::types::balance::{EthBalances, TokenBalance};
+use shared::types::token::ChainId;
+
+/// The ERC-20 `balanceOf(address)` selector.
+const BALANCE_OF_SELECTOR: &str = "70a08231";
+
+/// The response size allowed per call of a batch, a call returns one 32-byte word.
+const MAX_RESPONSE_BYTES_PER_CALL: u64 = 256;
+
+/// Returns the native balance of the address and the balances of the ERC-20 tokens of the principal on the chain.
+///
+/// The balances are fetched in one batch per RPC provider, each balance is the one a majority of the providers agree on.
+/// Returns an error if the balances cannot be fetched on the chain at all.
+pub async fn eth_balances(
+    principal: &Principal,
+    address: String,
+    chain_id: ChainId,
+) -> Result<EthBalances, String> {
+    check_chain_supported(chain_id)?;
+
+    let contract_addresses: Vec<String> = read_state(|s| {
+        s.user_token
+            .get(&StoredPrincipal(*principal))
+            .unwrap_or_default()
+            .0
+    })
+    .into_iter()
+    .filter(|token| token.chain_id == chain_id)
+    .map(|token| token.contract_address)
+    .collect();
+
+    let balance_of_data = format!(
+        "0x{BALANCE_OF_SELECTOR}{:0>64}",
+        address.trim_start_matches("0x").to_lowercase()
+    );
+
+    let calls: Vec<JsonRpcCall> = std::iter::once(JsonRpcCall {
+        method: "eth_getBalance",
+        params: json!([address, "latest"]),
+        nullable: false,
+    })
+    .chain(
+        contract_addresses
+            .iter()
+            .map(|contract_address| JsonRpcCall {
+                method: "eth_call",
+                params: json!([{ "to": contract_address, "data": balance_of_data }, "latest"]),
+                nullable: false,
+            }),
+    )
+    .collect();
+
+    let max_response_bytes = MAX_RESPONSE_BYTES_PER_CALL * calls.len() as u64;
+    let provider_results = batch_request(chain_id, &calls, max_response_bytes).await;
+
+    let balances = std::iter::once(None)
+        .chain(contract_addresses.into_iter().map(Some))
+        .enumerate()
+        .map(|(i, contract_address)| {
+            let results: Vec<Result<Nat, String>> = provider_results
+                .iter()
+                .map(|results| {
+                    results[i]
+                        .as_ref()
+                        .map_err(Clone::clone)
+                        .and_then(|result| parse_quantity(result).map(u256_to_nat))
+                })
+                .collect();
+            TokenBalance {
+                contract_address,
+                balance: consensus(&results),
+            }
+        })
+        .collect();
+
+    Ok(EthBalances {
+        chain_id,
+        address,
+        balances,
+    })
+}
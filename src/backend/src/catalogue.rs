@@ -0,0 +1,229 @@
+use crate::chain::assert_chain_registered;
+use crate::denylist::{assert_not_denylisted, is_denylisted};
+use crate::token::{add_to_user_token, remove_from_user_token};
+use crate::{
+    mutate_state, parse_eth_address, read_state, Candid, StoredPrincipal, MAX_SYMBOL_LENGTH,
+};
+use sha2::{Digest, Sha256};
+use shared::types::catalogue::{
+    CatalogueToken, CatalogueTokenId, CatalogueTokenPage, ListedUserCatalogueToken, Network,
+    UserCatalogueToken,
+};
+use shared::types::custom_token::LedgerId;
+use shared::types::denylist::DenylistedId;
+use shared::types::TokenVersion;
+use std::ops::Bound;
+
+const MAX_NAME_LENGTH: usize = 64;
+const MAX_NETWORKS: usize = 16;
+const MAX_LOGO_SIZE: usize = 32 * 1024;
+const PAGE_SIZE: usize = 100;
+/// Bounds the instructions of a listing when few tokens match the network.
+const MAX_SCANNED_ENTRIES: usize = 1_000;
+
+/// The key of a catalogue entry, ERC-20 contract addresses are normalized so that an entry cannot be listed twice.
+fn catalogue_key(id: &CatalogueTokenId) -> String {
+    match id {
+        CatalogueTokenId::Erc20 {
+            chain_id,
+            contract_address,
+        } => format!(
+            "erc20:{chain_id}:{}",
+            hex::encode(parse_eth_address(contract_address))
+        ),
+        CatalogueTokenId::Icrc(ledger_id) => format!("icrc:{ledger_id}"),
+    }
+}
+
+fn denylisted_id(id: &CatalogueTokenId) -> DenylistedId {
+    match id {
+        CatalogueTokenId::Erc20 {
+            chain_id,
+            contract_address,
+        } => DenylistedId::Evm {
+            chain_id: *chain_id,
+            address: contract_address.clone(),
+        },
+        CatalogueTokenId::Icrc(ledger_id) => DenylistedId::Icrc(*ledger_id),
+    }
+}
+
+fn assert_valid_symbol(symbol: &str) {
+    if symbol.is_empty() || symbol.len() > MAX_SYMBOL_LENGTH {
+        ic_cdk::trap(&format!(
+            "Symbol should be between 1 and {MAX_SYMBOL_LENGTH} bytes"
+        ));
+    }
+}
+
+fn assert_valid_name(name: &str) {
+    if name.len() > MAX_NAME_LENGTH {
+        ic_cdk::trap(&format!("Name should not exceed {MAX_NAME_LENGTH} bytes"));
+    }
+}
+
+/// Checks the metadata of the token, that its chains are in the registry and that its logo was stored, otherwise traps.
+fn assert_valid_catalogue_token(token: &CatalogueToken) {
+    assert_valid_symbol(&token.symbol);
+    assert_valid_name(&token.name);
+    if let CatalogueTokenId::Erc20 { chain_id, .. } = token.id {
+        assert_chain_registered(chain_id);
+    }
+    if token.enabled_networks.len() > MAX_NETWORKS {
+        ic_cdk::trap(&format!(
+            "Enabled networks should not exceed {MAX_NETWORKS} entries"
+        ));
+    }
+    for network in &token.enabled_networks {
+        if let Network::Evm(chain_id) = network {
+            assert_chain_registered(*chain_id);
+        }
+    }
+    if token.index_id.is_some() && !matches!(token.id, CatalogueTokenId::Icrc(_)) {
+        ic_cdk::trap("Only ICRC tokens have an index canister");
+    }
+    if let Some(logo_hash) = token.logo_hash.as_ref() {
+        if !read_state(|s| s.catalogue_logo.contains_key(logo_hash)) {
+            ic_cdk::trap(&format!("Unknown logo {logo_hash}"));
+        }
+    }
+}
+
+/// Adds a token to the catalogue or updates it if the version matches the stored one.
+pub fn set_catalogue_token(token: CatalogueToken) {
+    assert_valid_catalogue_token(&token);
+
+    let key = catalogue_key(&token.id);
+
+    mutate_state(|s| {
+        if let Some(Candid(existing)) = s.catalogue.get(&key) {
+            if existing.get_version() != token.get_version() {
+                ic_cdk::trap("Version mismatch, catalogue token update not allowed");
+            }
+        }
+        s.catalogue
+            .insert(key, Candid(token.clone_with_incremented_version()));
+    });
+}
+
+pub fn remove_catalogue_token(id: &CatalogueTokenId) {
+    let key = catalogue_key(id);
+    mutate_state(|s| s.catalogue.remove(&key));
+}
+
+/// Returns the catalogue entry of a ledger the canister calls on behalf of the users, otherwise the error.
+///
+/// The calls of the canister cannot time out: a ledger that never replies would keep their call contexts open, which
+/// prevents the canister from being stopped to be upgraded. Only the ledgers the controllers listed are called.
+pub fn trusted_icrc_ledger(ledger_id: &LedgerId) -> Result<CatalogueToken, String> {
+    read_state(|s| {
+        s.catalogue
+            .get(&catalogue_key(&CatalogueTokenId::Icrc(*ledger_id)))
+    })
+    .map(|t| t.0)
+    .ok_or_else(|| format!("Ledger {ledger_id} is not in the catalogue"))
+}
+
+/// Stores a logo, e.g. an SVG data URL, and returns its hex-encoded SHA-256 hash.
+pub fn set_catalogue_logo(logo: String) -> String {
+    if logo.len() > MAX_LOGO_SIZE {
+        ic_cdk::trap(&format!("Logo should not exceed {MAX_LOGO_SIZE} bytes"));
+    }
+
+    let hash = hex::encode(Sha256::digest(logo.as_bytes()));
+    mutate_state(|s| s.catalogue_logo.insert(hash.clone(), logo));
+    hash
+}
+
+pub fn get_catalogue_logo(hash: String) -> Option<String> {
+    read_state(|s| s.catalogue_logo.get(&hash))
+}
+
+/// Lists the tokens enabled on the network in the order of their keys, starting after the cursor.
+pub fn list_catalogue_tokens(network: Network, cursor: Option<String>) -> CatalogueTokenPage {
+    let start = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+
+    read_state(|s| {
+        let mut tokens = vec![];
+        let mut last_key = None;
+        for (key, Candid(token)) in s
+            .catalogue
+            .range((start, Bound::Unbounded))
+            .take(MAX_SCANNED_ENTRIES)
+        {
+            if token.enabled_networks.contains(&network) {
+                tokens.push(token);
+            }
+            last_key = Some(key);
+            if tokens.len() == PAGE_SIZE {
+                break;
+            }
+        }
+
+        let next_cursor = last_key.filter(|key| {
+            s.catalogue
+                .range((Bound::Excluded(key.clone()), Bound::Unbounded))
+                .next()
+                .is_some()
+        });
+
+        CatalogueTokenPage {
+            tokens,
+            next_cursor,
+        }
+    })
+}
+
+/// Adds a catalogue token to the token list of the user or updates its overrides, the version should match the
+/// stored one.
+pub fn set_user_catalogue_token(principal: StoredPrincipal, token: UserCatalogueToken) {
+    let key = catalogue_key(&token.id);
+    if !read_state(|s| s.catalogue.contains_key(&key)) {
+        ic_cdk::trap("Unknown catalogue token");
+    }
+    assert_not_denylisted(&denylisted_id(&token.id));
+    if let Some(symbol) = token.symbol.as_ref() {
+        assert_valid_symbol(symbol);
+    }
+    if let Some(name) = token.name.as_ref() {
+        assert_valid_name(name);
+    }
+
+    let find = |t: &UserCatalogueToken| catalogue_key(&t.id) == key;
+
+    mutate_state(|s| add_to_user_token(principal, &mut s.user_catalogue_token, &token, &find));
+}
+
+pub fn remove_user_catalogue_token(principal: StoredPrincipal, id: &CatalogueTokenId) {
+    let key = catalogue_key(id);
+    let find = |t: &UserCatalogueToken| catalogue_key(&t.id) == key;
+
+    mutate_state(|s| remove_from_user_token(principal, &mut s.user_catalogue_token, &find));
+}
+
+/// Lists the catalogue tokens of the user, resolved against the current catalogue.
+pub fn list_user_catalogue_tokens(principal: StoredPrincipal) -> Vec<ListedUserCatalogueToken> {
+    read_state(|s| {
+        s.user_catalogue_token
+            .get(&principal)
+            .unwrap_or_default()
+            .0
+            .into_iter()
+            .map(|user_token| {
+                let token = s
+                    .catalogue
+                    .get(&catalogue_key(&user_token.id))
+                    .map(|Candid(token)| CatalogueToken {
+                        symbol: user_token.symbol.clone().unwrap_or(token.symbol),
+                        name: user_token.name.clone().unwrap_or(token.name),
+                        ..token
+                    });
+                ListedUserCatalogueToken {
+                    denylisted: is_denylisted(&denylisted_id(&user_token.id)).then_some(true),
+                    token,
+                    user_token,
+                }
+            })
+            .collect()
+    })
+}
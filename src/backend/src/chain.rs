@@ -0,0 +1,79 @@
+use crate::read_config;
+use candid::Nat;
+use shared::types::chain::{ChainConfig, TransactionType};
+use shared::types::token::ChainId;
+
+const ETHEREUM_CHAIN_ID: ChainId = 1;
+const SEPOLIA_CHAIN_ID: ChainId = 11155111;
+
+/// The block gas limit of Ethereum mainnet, used as the default cap of a single transaction.
+const DEFAULT_MAX_GAS_LIMIT: u64 = 30_000_000;
+
+/// The chains supported as long as the controllers have not configured the registry.
+pub fn default_chains() -> Vec<ChainConfig> {
+    vec![
+        ChainConfig {
+            chain_id: ETHEREUM_CHAIN_ID,
+            name: "Ethereum Mainnet".to_string(),
+            native_symbol: "ETH".to_string(),
+            supported_tx_types: vec![TransactionType::Eip1559],
+            max_gas_limit: DEFAULT_MAX_GAS_LIMIT,
+            enabled: true,
+        },
+        ChainConfig {
+            chain_id: SEPOLIA_CHAIN_ID,
+            name: "Sepolia".to_string(),
+            native_symbol: "SepoliaETH".to_string(),
+            supported_tx_types: vec![TransactionType::Eip1559],
+            max_gas_limit: DEFAULT_MAX_GAS_LIMIT,
+            enabled: true,
+        },
+    ]
+}
+
+/// Returns the chain registry, enabled and disabled chains alike.
+pub fn chain_registry() -> Vec<ChainConfig> {
+    read_config(|c| c.chains.clone()).unwrap_or_else(default_chains)
+}
+
+/// Traps if the chain is not in the registry, whether it is enabled or not.
+pub fn assert_chain_registered(chain_id: ChainId) {
+    if !chain_registry().iter().any(|c| c.chain_id == chain_id) {
+        ic_cdk::trap(&format!("Chain {chain_id} is not supported"));
+    }
+}
+
+/// Returns the configuration of the chain if it is known and enabled, otherwise traps.
+pub fn assert_chain_enabled(chain_id: ChainId) -> ChainConfig {
+    match chain_registry()
+        .into_iter()
+        .find(|c| c.chain_id == chain_id)
+    {
+        None => ic_cdk::trap(&format!("Chain {chain_id} is not supported")),
+        Some(chain) if !chain.enabled => ic_cdk::trap(&format!("Chain {chain_id} is disabled")),
+        Some(chain) => chain,
+    }
+}
+
+/// Checks that a transaction of the given type and gas limit can be signed for the chain, otherwise traps.
+pub fn assert_transaction_allowed(chain_id: &Nat, tx_type: TransactionType, gas: &Nat) {
+    let chain_id = nat_to_chain_id(chain_id);
+    let chain = assert_chain_enabled(chain_id);
+
+    if !chain.supported_tx_types.contains(&tx_type) {
+        ic_cdk::trap(&format!(
+            "Transaction type {tx_type:?} is not supported on chain {chain_id}"
+        ));
+    }
+
+    if u64::try_from(&gas.0).map_or(true, |gas| gas > chain.max_gas_limit) {
+        ic_cdk::trap(&format!(
+            "Gas limit should not exceed {} on chain {chain_id}",
+            chain.max_gas_limit
+        ));
+    }
+}
+
+pub fn nat_to_chain_id(n: &Nat) -> ChainId {
+    ChainId::try_from(&n.0).unwrap_or_else(|_| ic_cdk::trap(&format!("Chain {n} is not supported")))
+}
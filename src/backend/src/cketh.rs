@@ -0,0 +1,265 @@
+//! The conversions between ETH and [ckETH](https://github.com/dfinity/ic/tree/master/rs/ethereum/cketh).
+use crate::chain::assert_chain_enabled;
+use crate::evm_rpc::{batch_request, check_chain_supported, consensus, JsonRpcCall};
+use crate::{mutate_state, read_config, read_state, Candid, StoredPrincipal};
+use candid::{CandidType, Deserialize, Nat, Principal};
+use futures::future::join_all;
+use serde_json::{json, Value};
+use shared::types::cketh::{
+    CkEthConversion, CkEthDepositArgs, CkEthMinter, ConversionState, ConversionStatus,
+    TrackedConversion,
+};
+use shared::types::token::ChainId;
+use shared::types::transaction::SignRequest;
+
+/// The `deposit(bytes32)` selector of the helper contract.
+const DEPOSIT_SELECTOR: [u8; 4] = [0xb2, 0x14, 0xfa, 0xa5];
+
+/// The gas limit of a call of the `deposit(bytes32)` function of the helper contract.
+const DEPOSIT_GAS: u64 = 60_000;
+
+const MAX_TRACKED_CONVERSIONS: usize = 20;
+
+const MAX_RESPONSE_BYTES: u64 = 4_096;
+
+/// The subset of the `get_minter_info` response of the minter that is used.
+#[derive(CandidType, Deserialize)]
+struct MinterInfo {
+    eth_helper_contract_address: Option<String>,
+    /// The former name of the helper contract address.
+    smart_contract_address: Option<String>,
+}
+
+#[derive(CandidType, Deserialize)]
+struct EthTransaction {
+    transaction_hash: String,
+}
+
+#[derive(CandidType, Deserialize)]
+enum TxFinalizedStatus {
+    Success {
+        transaction_hash: String,
+        effective_transaction_fee: Option<Nat>,
+    },
+    Reimbursed {
+        transaction_hash: String,
+        reimbursed_amount: Nat,
+        reimbursed_in_block: Nat,
+    },
+    PendingReimbursement(EthTransaction),
+}
+
+/// The status of a withdrawal returned by `retrieve_eth_status`.
+#[derive(CandidType, Deserialize)]
+enum RetrieveEthStatus {
+    NotFound,
+    Pending,
+    TxCreated,
+    TxSent(EthTransaction),
+    TxFinalized(TxFinalizedStatus),
+}
+
+/// Returns the ckETH minter, traps if the controllers did not configure it.
+fn minter() -> CkEthMinter {
+    read_config(|c| c.cketh_minter)
+        .unwrap_or_else(|| ic_cdk::trap("The ckETH minter is not configured"))
+}
+
+/// Traps if the chain is not the one of the minter, whose helper contract is only deployed there.
+fn assert_minter_chain(minter: &CkEthMinter, chain_id: ChainId) {
+    if chain_id != minter.chain_id {
+        ic_cdk::trap(&format!(
+            "The ckETH minter converts the ETH of chain {}, not {chain_id}",
+            minter.chain_id
+        ));
+    }
+}
+
+/// Encodes a principal as the `bytes32` argument of the helper contract: its length, its bytes, then zeros.
+fn principal_to_bytes32(principal: &Principal) -> [u8; 32] {
+    let bytes = principal.as_slice();
+    let mut encoded = [0u8; 32];
+    encoded[0] = bytes.len() as u8;
+    encoded[1..=bytes.len()].copy_from_slice(bytes);
+    encoded
+}
+
+/// Returns the transaction depositing ETH to the helper contract, which mints ckETH to the principal.
+pub async fn deposit_request(principal: &Principal, args: CkEthDepositArgs) -> SignRequest {
+    assert_chain_enabled(args.chain_id);
+    let minter = minter();
+    assert_minter_chain(&minter, args.chain_id);
+
+    let (info,): (MinterInfo,) = ic_cdk::call(minter.canister_id, "get_minter_info", ())
+        .await
+        .unwrap_or_else(|(_, err)| {
+            ic_cdk::trap(&format!("failed to get the ckETH minter info: {err}"))
+        });
+    let helper_contract_address = info
+        .eth_helper_contract_address
+        .or(info.smart_contract_address)
+        .unwrap_or_else(|| ic_cdk::trap("The ckETH minter has no helper contract"));
+
+    SignRequest {
+        chain_id: Nat::from(args.chain_id),
+        to: helper_contract_address,
+        gas: Nat::from(DEPOSIT_GAS),
+        max_fee_per_gas: args.max_fee_per_gas,
+        max_priority_fee_per_gas: args.max_priority_fee_per_gas,
+        value: args.amount,
+        nonce: args.nonce,
+        data: Some(format!(
+            "0x{}{}",
+            hex::encode(DEPOSIT_SELECTOR),
+            hex::encode(principal_to_bytes32(principal))
+        )),
+    }
+}
+
+fn tracked_conversions(principal: &Principal) -> Vec<TrackedConversion> {
+    read_state(|s| {
+        s.cketh_conversion
+            .get(&StoredPrincipal(*principal))
+            .unwrap_or_default()
+            .0
+    })
+}
+
+/// Tracks a conversion of the principal, dropping the oldest one if too many are tracked.
+pub fn track(principal: &Principal, conversion: CkEthConversion, now: u64) {
+    let minter = minter();
+    match &conversion {
+        CkEthConversion::Deposit {
+            chain_id,
+            transaction_hash,
+        } => {
+            assert_chain_enabled(*chain_id);
+            assert_minter_chain(&minter, *chain_id);
+            let hash = transaction_hash.trim_start_matches("0x");
+            if hash.len() != 64 || hex::decode(hash).is_err() {
+                ic_cdk::trap(&format!("Invalid transaction hash: {transaction_hash}"));
+            }
+        }
+        CkEthConversion::Withdrawal { .. } => (),
+    }
+
+    let mut conversions = tracked_conversions(principal);
+    if conversions.iter().any(|c| c.conversion == conversion) {
+        return;
+    }
+    if conversions.len() == MAX_TRACKED_CONVERSIONS {
+        conversions.remove(0);
+    }
+    conversions.push(TrackedConversion {
+        conversion,
+        created_timestamp: now,
+    });
+
+    mutate_state(|s| {
+        s.cketh_conversion
+            .insert(StoredPrincipal(*principal), Candid(conversions))
+    });
+}
+
+/// Returns the state of a deposit from its receipt, as a majority of the providers see it.
+async fn deposit_state(chain_id: ChainId, transaction_hash: &str) -> ConversionState {
+    if let Err(err) = check_chain_supported(chain_id) {
+        return ConversionState::Error(err);
+    }
+
+    let call = JsonRpcCall {
+        method: "eth_getTransactionReceipt",
+        params: json!([transaction_hash]),
+        // The receipt of a pending transaction is `null`.
+        nullable: true,
+    };
+
+    // The receipts may differ in the fields the providers add, only their status is compared.
+    let results: Vec<Result<Option<String>, String>> =
+        batch_request(chain_id, &[call], MAX_RESPONSE_BYTES)
+            .await
+            .into_iter()
+            .map(|mut results| {
+                results.swap_remove(0).map(|receipt| match receipt {
+                    Value::Null => None,
+                    receipt => Some(receipt["status"].as_str().unwrap_or_default().to_string()),
+                })
+            })
+            .collect();
+
+    match consensus(&results) {
+        Ok(None) => ConversionState::Pending,
+        Ok(Some(status)) if status == "0x1" => ConversionState::Completed {
+            transaction_hash: transaction_hash.to_string(),
+        },
+        Ok(Some(_)) => ConversionState::Failed {
+            reason: "The deposit transaction reverted".to_string(),
+        },
+        Err(err) => ConversionState::Error(err),
+    }
+}
+
+async fn withdrawal_state(minter_id: Principal, ledger_block_index: u64) -> ConversionState {
+    let result: Result<(RetrieveEthStatus,), _> =
+        ic_cdk::call(minter_id, "retrieve_eth_status", (ledger_block_index,)).await;
+
+    match result {
+        Ok((RetrieveEthStatus::NotFound,)) => ConversionState::NotFound,
+        Ok((RetrieveEthStatus::Pending,)) | Ok((RetrieveEthStatus::TxCreated,)) => {
+            ConversionState::Pending
+        }
+        Ok((RetrieveEthStatus::TxSent(tx),)) => ConversionState::Sent {
+            transaction_hash: tx.transaction_hash,
+        },
+        Ok((RetrieveEthStatus::TxFinalized(TxFinalizedStatus::Success {
+            transaction_hash,
+            ..
+        }),)) => ConversionState::Completed { transaction_hash },
+        Ok((RetrieveEthStatus::TxFinalized(TxFinalizedStatus::Reimbursed { .. }),)) => {
+            ConversionState::Failed {
+                reason: "The withdrawal failed, the ckETH was reimbursed".to_string(),
+            }
+        }
+        Ok((RetrieveEthStatus::TxFinalized(TxFinalizedStatus::PendingReimbursement(_)),)) => {
+            ConversionState::Failed {
+                reason: "The withdrawal failed, the ckETH is being reimbursed".to_string(),
+            }
+        }
+        Err((_, err)) => {
+            ConversionState::Error(format!("failed to get the status of the withdrawal: {err}"))
+        }
+    }
+}
+
+/// Returns the state of the tracked conversions of the principal, most recent first.
+///
+/// A conversion whose state cannot be fetched, e.g. because the EVM RPC canister or the minter is no longer
+/// configured, is reported with the error.
+pub async fn conversion_status(principal: &Principal) -> Vec<ConversionStatus> {
+    let conversions: Vec<TrackedConversion> =
+        tracked_conversions(principal).into_iter().rev().collect();
+
+    let states = join_all(conversions.iter().map(|c| async {
+        match &c.conversion {
+            CkEthConversion::Deposit {
+                chain_id,
+                transaction_hash,
+            } => deposit_state(*chain_id, transaction_hash).await,
+            CkEthConversion::Withdrawal { ledger_block_index } => {
+                match read_config(|c| c.cketh_minter) {
+                    Some(minter) => withdrawal_state(minter.canister_id, *ledger_block_index).await,
+                    None => {
+                        ConversionState::Error("The ckETH minter is not configured".to_string())
+                    }
+                }
+            }
+        }
+    }))
+    .await;
+
+    conversions
+        .into_iter()
+        .zip(states)
+        .map(|(conversion, state)| ConversionStatus { conversion, state })
+        .collect()
+}
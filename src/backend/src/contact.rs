@@ -0,0 +1,93 @@
+use crate::{parse_eth_address, Candid, StoredPrincipal, VMem};
+use bech32::hrp::{BC, BCRT, TB};
+use ic_stable_structures::StableBTreeMap;
+use shared::types::contact::{Contact, ContactAddress};
+use shared::types::TokenVersion;
+
+const MAX_CONTACTS: usize = 100;
+const MAX_LABEL_LENGTH: usize = 64;
+const MAX_ADDRESSES_PER_CONTACT: usize = 10;
+const SUBACCOUNT_LENGTH: usize = 32;
+
+/// Version bytes of mainnet and testnet P2PKH and P2SH addresses.
+const BTC_BASE58_VERSIONS: [u8; 4] = [0x00, 0x05, 0x6f, 0xc4];
+
+/// Checks the label and the chain-specific addresses of a contact, otherwise traps.
+pub fn assert_valid_contact(contact: &Contact) {
+    if contact.label.is_empty() || contact.label.len() > MAX_LABEL_LENGTH {
+        ic_cdk::trap(&format!(
+            "Contact label should be between 1 and {MAX_LABEL_LENGTH} bytes"
+        ));
+    }
+
+    if contact.addresses.is_empty() || contact.addresses.len() > MAX_ADDRESSES_PER_CONTACT {
+        ic_cdk::trap(&format!(
+            "Contact should have between 1 and {MAX_ADDRESSES_PER_CONTACT} addresses"
+        ));
+    }
+
+    for address in &contact.addresses {
+        match address {
+            ContactAddress::Ethereum(address) => {
+                parse_eth_address(address);
+            }
+            ContactAddress::Icrc(account) => {
+                if let Some(subaccount) = account.subaccount.as_ref() {
+                    if subaccount.len() != SUBACCOUNT_LENGTH {
+                        ic_cdk::trap(&format!(
+                            "Subaccount should be {SUBACCOUNT_LENGTH} bytes long"
+                        ));
+                    }
+                }
+            }
+            ContactAddress::Bitcoin(address) => {
+                if !is_valid_btc_address(address) {
+                    ic_cdk::trap(&format!("failed to parse bitcoin address {address}"));
+                }
+            }
+        }
+    }
+}
+
+/// Accepts segwit addresses of the known networks and base58check encoded legacy addresses.
+fn is_valid_btc_address(address: &str) -> bool {
+    match bech32::segwit::decode(address) {
+        Ok((hrp, _, _)) => [BC, TB, BCRT].contains(&hrp),
+        Err(_) => bs58::decode(address)
+            .with_check(None)
+            .into_vec()
+            .map_or(false, |bytes| {
+                bytes.len() == 21 && BTC_BASE58_VERSIONS.contains(&bytes[0])
+            }),
+    }
+}
+
+/// Adds the contact or replaces the one with the same label, provided the version matches the stored contact.
+pub fn set_contact(
+    stored_principal: StoredPrincipal,
+    contacts: &mut StableBTreeMap<StoredPrincipal, Candid<Vec<Contact>>, VMem>,
+    contact: &Contact,
+) {
+    let Candid(mut user_contacts) = contacts.get(&stored_principal).unwrap_or_default();
+
+    match user_contacts.iter().position(|c| c.label == contact.label) {
+        Some(p) => {
+            if user_contacts[p].get_version().is_some()
+                && user_contacts[p].get_version() != contact.get_version()
+            {
+                ic_cdk::trap("Version mismatch, contact update not allowed");
+            }
+            user_contacts[p] = contact.clone_with_incremented_version();
+        }
+        None => {
+            if user_contacts.len() == MAX_CONTACTS {
+                ic_cdk::trap(&format!(
+                    "Contact list length should not exceed {MAX_CONTACTS}"
+                ));
+            }
+            user_contacts.push(contact.clone_with_incremented_version());
+        }
+    }
+
+    contacts.insert(stored_principal, Candid(user_contacts));
+}
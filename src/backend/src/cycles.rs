@@ -0,0 +1,86 @@
+use crate::{mutate_state, read_state, Candid, StoredPrincipal};
+use shared::metrics::MetricsEncoder;
+use shared::types::cycles::{CyclesConsumer, CyclesUsage};
+
+/// The fee attached by `ic_cdk` to every `sign_with_ecdsa` call.
+pub const SIGN_WITH_ECDSA_FEE: u128 = 26_153_846_153;
+
+const MAX_TOP_CYCLES_CONSUMERS: usize = 100;
+
+/// Charges the fee of a threshold signature to the principal and to the canister totals.
+pub fn record_signature(principal: StoredPrincipal, fee: u128) {
+    record(principal, fee, 1);
+}
+
+/// Charges the cycles an RPC call kept to the principal and to the canister totals.
+///
+/// The calls of the background jobs are charged to the canister itself.
+pub fn record_rpc_call(principal: StoredPrincipal, cycles: u128) {
+    if cycles > 0 {
+        record(principal, cycles, 0);
+    }
+}
+
+fn record(principal: StoredPrincipal, cycles: u128, signatures: u64) {
+    let charge = |usage: CyclesUsage| CyclesUsage {
+        cycles: usage.cycles.saturating_add(cycles),
+        signatures: usage.signatures.saturating_add(signatures),
+    };
+
+    mutate_state(|s| {
+        let usage = s.cycles_usage.get(&principal).unwrap_or_default().0;
+        let charged = charge(usage);
+        s.cycles_ranking.remove(&rank_key(usage.cycles, principal));
+        s.cycles_ranking
+            .insert(rank_key(charged.cycles, principal), ());
+        s.cycles_usage.insert(principal, Candid(charged));
+
+        let totals = s.cycles_totals.get().0;
+        s.cycles_totals
+            .set(Candid(charge(totals)))
+            .expect("setting cycles totals should succeed");
+    });
+}
+
+/// The key of a principal in the cycles ranking: the cycles are complemented so that iterating
+/// the ranking yields the largest consumers first.
+fn rank_key(cycles: u128, principal: StoredPrincipal) -> (u128, StoredPrincipal) {
+    (u128::MAX - cycles, principal)
+}
+
+/// Returns the principals that spent the most cycles, in descending order.
+pub fn top_consumers(limit: usize) -> Vec<CyclesConsumer> {
+    read_state(|s| {
+        s.cycles_ranking
+            .iter()
+            .take(limit.min(MAX_TOP_CYCLES_CONSUMERS))
+            .filter_map(|((_, principal), ())| {
+                s.cycles_usage.get(&principal).map(|usage| CyclesConsumer {
+                    principal: principal.0,
+                    usage: usage.0,
+                })
+            })
+            .collect()
+    })
+}
+
+pub fn encode_cycles_metrics(w: &mut MetricsEncoder<Vec<u8>>) -> std::io::Result<()> {
+    let (totals, consumers) = read_state(|s| (s.cycles_totals.get().0, s.cycles_usage.len()));
+
+    w.encode_counter(
+        "ic_eth_wallet_signing_cycles",
+        totals.cycles as f64,
+        "Cycles spent on threshold signatures and RPC calls",
+    )?;
+    w.encode_counter(
+        "ic_eth_wallet_signatures",
+        totals.signatures as f64,
+        "Number of threshold signatures",
+    )?;
+    w.encode_gauge(
+        "ic_eth_wallet_cycles_consumers",
+        consumers as f64,
+        "Number of principals that spent cycles",
+    )?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use crate::{mutate_state, parse_eth_address, read_state, Candid};
+use shared::types::custom_token::Token;
+use shared::types::denylist::{DenylistEntry, DenylistedId};
+use shared::types::token::ChainId;
+
+const MAX_REASON_LENGTH: usize = 256;
+
+/// The ERC-20 `approve(address,uint256)`, `increaseAllowance(address,uint256)` and ERC-721/1155 `setApprovalForAll(address,bool)` selectors.
+/// The spender is the first argument of all of them.
+pub const APPROVAL_SELECTORS: [[u8; 4]; 3] = [
+    [0x09, 0x5e, 0xa7, 0xb3],
+    [0x39, 0x50, 0x93, 0x51],
+    [0xa2, 0x2c, 0xb4, 0x65],
+];
+
+/// The key of a denylist entry, EVM addresses are normalized so that their case does not matter.
+fn denylist_key(id: &DenylistedId) -> String {
+    match id {
+        DenylistedId::Evm { chain_id, address } => {
+            format!("evm:{chain_id}:{}", hex::encode(parse_eth_address(address)))
+        }
+        DenylistedId::Icrc(ledger_id) => format!("icrc:{ledger_id}"),
+    }
+}
+
+pub fn custom_token_denylisted_id(token: &Token) -> DenylistedId {
+    match token {
+        Token::Icrc(token) => DenylistedId::Icrc(token.ledger_id),
+    }
+}
+
+pub fn set_denylist_entry(entry: DenylistEntry) {
+    if entry.reason.len() > MAX_REASON_LENGTH {
+        ic_cdk::trap(&format!(
+            "Reason should not exceed {MAX_REASON_LENGTH} bytes"
+        ));
+    }
+
+    let key = denylist_key(&entry.id);
+    mutate_state(|s| s.denylist.insert(key, Candid(entry)));
+}
+
+pub fn remove_denylist_entry(id: &DenylistedId) {
+    let key = denylist_key(id);
+    mutate_state(|s| s.denylist.remove(&key));
+}
+
+pub fn list_denylist() -> Vec<DenylistEntry> {
+    read_state(|s| s.denylist.iter().map(|(_, entry)| entry.0).collect())
+}
+
+pub fn is_denylisted(id: &DenylistedId) -> bool {
+    let key = denylist_key(id);
+    read_state(|s| s.denylist.contains_key(&key))
+}
+
+/// Traps if the token was denylisted by the controllers.
+pub fn assert_not_denylisted(id: &DenylistedId) {
+    let key = denylist_key(id);
+    if let Some(Candid(entry)) = read_state(|s| s.denylist.get(&key)) {
+        ic_cdk::trap(&format!("Token is denylisted: {}", entry.reason));
+    }
+}
+
+/// Traps if the transaction data approves a denylisted spender.
+pub fn assert_approval_allowed(chain_id: ChainId, data: &[u8]) {
+    // The selector followed by the spender, left-padded to 32 bytes.
+    if data.len() < 36 || !APPROVAL_SELECTORS.iter().any(|s| data[..4] == *s) {
+        return;
+    }
+
+    let spender = format!("0x{}", hex::encode(&data[16..36]));
+    let key = denylist_key(&DenylistedId::Evm {
+        chain_id,
+        address: spender.clone(),
+    });
+    if let Some(Candid(entry)) = read_state(|s| s.denylist.get(&key)) {
+        ic_cdk::trap(&format!(
+            "Approvals to {spender} are not allowed: {}",
+            entry.reason
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+//! The resolution of [ENS](https://docs.ens.domains) names on Ethereum mainnet.
+use crate::evm_rpc::{batch_request, consensus, JsonRpcCall};
+use crate::parse_eth_address;
+use ethers_core::abi::{decode, ParamType, Token};
+use ethers_core::types::Address;
+use ethers_core::utils::{keccak256, to_checksum};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::thread::LocalKey;
+
+const ETHEREUM_MAINNET_CHAIN_ID: u64 = 1;
+
+const ENS_REGISTRY_ADDRESS: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+
+/// The `resolver(bytes32)` selector of the registry.
+const RESOLVER_SELECTOR: &str = "0178b8bf";
+/// The `addr(bytes32)` selector of a resolver.
+const ADDR_SELECTOR: &str = "3b3b57de";
+/// The `name(bytes32)` selector of a reverse resolver.
+const NAME_SELECTOR: &str = "691f3431";
+
+const MAX_NAME_LENGTH: usize = 255;
+
+/// The time a resolution is served from the cache, the records of a name rarely change.
+const ENS_CACHE_TTL_NANOS: u64 = 5 * 60 * 1_000_000_000;
+const MAX_CACHE_ENTRIES: usize = 1_000;
+
+const MAX_RESPONSE_BYTES: u64 = 2_048;
+
+/// A cached resolution and the time it expires.
+type Cache = BTreeMap<String, (Option<String>, u64)>;
+
+thread_local! {
+    // The resolutions are kept on the heap: losing them on upgrade merely resolves the names again.
+    static FORWARD_CACHE: RefCell<Cache> = RefCell::default();
+    static REVERSE_CACHE: RefCell<Cache> = RefCell::default();
+}
+
+fn cached(cache: &'static LocalKey<RefCell<Cache>>, key: &str, now: u64) -> Option<Option<String>> {
+    cache.with(|c| {
+        c.borrow()
+            .get(key)
+            .filter(|(_, expiry)| now < *expiry)
+            .map(|(value, _)| value.clone())
+    })
+}
+
+fn cache_insert(
+    cache: &'static LocalKey<RefCell<Cache>>,
+    key: String,
+    value: Option<String>,
+    now: u64,
+) {
+    cache.with(|c| {
+        let mut c = c.borrow_mut();
+        if c.len() >= MAX_CACHE_ENTRIES {
+            c.retain(|_, (_, expiry)| now < *expiry);
+        }
+        if c.len() >= MAX_CACHE_ENTRIES {
+            c.pop_first();
+        }
+        c.insert(key, (value, now + ENS_CACHE_TTL_NANOS));
+    });
+}
+
+/// Lowercases the name, `None` if it is empty, too long or has an empty label.
+///
+/// The full [ENSIP-15](https://docs.ens.domains/ensip/15) normalization is left to the frontend.
+fn normalize_name(name: &str) -> Option<String> {
+    let name = name.trim().to_lowercase();
+    (name.len() <= MAX_NAME_LENGTH && !name.split('.').any(str::is_empty)).then_some(name)
+}
+
+/// Computes the [namehash](https://docs.ens.domains/ensip/1#namehash-algorithm) of a normalized name.
+fn namehash(name: &str) -> [u8; 32] {
+    name.rsplit('.').fold([0u8; 32], |node, label| {
+        let mut bytes = node.to_vec();
+        bytes.extend_from_slice(&keccak256(label.as_bytes()));
+        keccak256(bytes)
+    })
+}
+
+/// Calls a view function taking a node, returns the result the providers agree on.
+async fn call_with_node(to: &str, selector: &str, node: &[u8; 32]) -> Result<Vec<u8>, String> {
+    let call = JsonRpcCall {
+        method: "eth_call",
+        params: json!([
+            { "to": to, "data": format!("0x{selector}{}", hex::encode(node)) },
+            "latest"
+        ]),
+        nullable: false,
+    };
+
+    let results: Vec<Result<Value, String>> =
+        batch_request(ETHEREUM_MAINNET_CHAIN_ID, &[call], MAX_RESPONSE_BYTES)
+            .await
+            .into_iter()
+            .map(|mut results| results.swap_remove(0))
+            .collect();
+
+    let result = consensus(&results)?;
+    result
+        .as_str()
+        .and_then(|r| hex::decode(r.trim_start_matches("0x")).ok())
+        .ok_or_else(|| format!("invalid eth_call result: {result}"))
+}
+
+/// Decodes an address returned by a view function, `None` if it is the zero address.
+fn decode_address(result: &[u8]) -> Option<Address> {
+    match decode(&[ParamType::Address], result).ok()?.pop()? {
+        Token::Address(address) if !address.is_zero() => Some(address),
+        _ => None,
+    }
+}
+
+fn decode_string(result: &[u8]) -> Option<String> {
+    match decode(&[ParamType::String], result).ok()?.pop()? {
+        Token::String(name) if !name.is_empty() => Some(name),
+        _ => None,
+    }
+}
+
+async fn resolver(node: &[u8; 32]) -> Option<Address> {
+    let result = call_with_node(ENS_REGISTRY_ADDRESS, RESOLVER_SELECTOR, node)
+        .await
+        .unwrap_or_else(|err| ic_cdk::trap(&format!("failed to get the ENS resolver: {err}")));
+    decode_address(&result)
+}
+
+async fn fetch_address(name: &str) -> Option<String> {
+    let node = namehash(name);
+    let resolver = resolver(&node).await?;
+
+    let result = call_with_node(&format!("{resolver:#x}"), ADDR_SELECTOR, &node)
+        .await
+        .unwrap_or_else(|err| ic_cdk::trap(&format!("failed to resolve {name}: {err}")));
+    decode_address(&result).map(|address| to_checksum(&address, None))
+}
+
+/// Returns the address the name resolves to on Ethereum, if any.
+pub async fn resolve(name: &str, now: u64) -> Option<String> {
+    let name =
+        normalize_name(name).unwrap_or_else(|| ic_cdk::trap(&format!("Invalid ENS name: {name}")));
+    if let Some(address) = cached(&FORWARD_CACHE, &name, now) {
+        return address;
+    }
+
+    let address = fetch_address(&name).await;
+    cache_insert(&FORWARD_CACHE, name, address.clone(), now);
+    address
+}
+
+async fn fetch_primary_name(address: &Address, now: u64) -> Option<String> {
+    let node = namehash(&format!("{}.addr.reverse", hex::encode(address)));
+    let resolver = resolver(&node).await?;
+
+    let result = call_with_node(&format!("{resolver:#x}"), NAME_SELECTOR, &node)
+        .await
+        .unwrap_or_else(|err| {
+            ic_cdk::trap(&format!("failed to get the name of {address:#x}: {err}"))
+        });
+    let name = decode_string(&result)?;
+
+    // Anyone can claim any name in their reverse record, the name should resolve back to the address.
+    let forward = resolve(&normalize_name(&name)?, now).await?;
+    (parse_eth_address(&forward) == address.0).then_some(name)
+}
+
+/// Returns the primary name of the address, if it resolves back to the address.
+pub async fn reverse(address: &str, now: u64) -> Option<String> {
+    let address = Address::from(parse_eth_address(address));
+    let key = format!("{address:#x}");
+    if let Some(name) = cached(&REVERSE_CACHE, &key, now) {
+        return name;
+    }
+
+    let name = fetch_primary_name(&address, now).await;
+    cache_insert(&REVERSE_CACHE, key, name.clone(), now);
+    name
+}
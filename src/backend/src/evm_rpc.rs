@@ -0,0 +1,432 @@
+//! The client of the [EVM RPC canister](https://github.com/internet-computer-protocol/evm-rpc-canister),
+//! which relays the JSON-RPC requests to several providers through HTTPS outcalls.
+use crate::cycles::record_rpc_call;
+use crate::{decode_hex, read_config, StoredPrincipal};
+use candid::{CandidType, Deserialize, Nat, Principal};
+use ethers_core::abi::ethereum_types::U256;
+use ethers_core::utils::keccak256;
+use futures::future::join_all;
+use serde_json::{json, Value};
+use shared::types::token::ChainId;
+use shared::types::transaction::{
+    ProviderSendResult, SendTransactionResponse, SendTransactionStatus,
+};
+
+const ETHEREUM_MAINNET_CHAIN_ID: ChainId = 1;
+const SEPOLIA_CHAIN_ID: ChainId = 11155111;
+
+/// The cycles attached to a call, the EVM RPC canister refunds the cycles it does not use.
+const EVM_RPC_CYCLES: u128 = 10_000_000_000;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug)]
+pub enum EthMainnetService {
+    Alchemy,
+    Ankr,
+    BlockPi,
+    PublicNode,
+    Cloudflare,
+    Llama,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug)]
+pub enum EthSepoliaService {
+    Alchemy,
+    Ankr,
+    BlockPi,
+    PublicNode,
+    Sepolia,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug)]
+pub enum L2MainnetService {
+    Alchemy,
+    Ankr,
+    BlockPi,
+    PublicNode,
+    Llama,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RpcApi {
+    pub url: String,
+    pub headers: Option<Vec<HttpHeader>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum RpcService {
+    Chain(u64),
+    Provider(u64),
+    Custom(RpcApi),
+    EthMainnet(EthMainnetService),
+    EthSepolia(EthSepoliaService),
+    ArbitrumOne(L2MainnetService),
+    BaseMainnet(L2MainnetService),
+    OptimismMainnet(L2MainnetService),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum RpcServices {
+    EthMainnet(Option<Vec<EthMainnetService>>),
+    EthSepolia(Option<Vec<EthSepoliaService>>),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RpcConfig {
+    #[serde(rename = "responseSizeEstimate")]
+    pub response_size_estimate: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum RejectionCode {
+    NoError,
+    SysFatal,
+    SysTransient,
+    DestinationInvalid,
+    CanisterReject,
+    CanisterError,
+    Unknown,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum HttpOutcallError {
+    IcError {
+        code: RejectionCode,
+        message: String,
+    },
+    InvalidHttpJsonRpcResponse {
+        status: u16,
+        body: String,
+        #[serde(rename = "parsingError")]
+        parsing_error: Option<String>,
+    },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum ProviderError {
+    TooFewCycles { expected: Nat, received: Nat },
+    MissingRequiredProvider,
+    ProviderNotFound,
+    NoPermission,
+    InvalidRpcConfig(String),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum ValidationError {
+    Custom(String),
+    HostNotAllowed(String),
+    UrlParseError(String),
+    InvalidHex(String),
+    CredentialPathNotAllowed,
+    CredentialHeaderNotAllowed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+// The variant names mirror the interface of the EVM RPC canister.
+#[allow(clippy::enum_variant_names)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum RpcError {
+    ProviderError(ProviderError),
+    HttpOutcallError(HttpOutcallError),
+    JsonRpcError(JsonRpcError),
+    ValidationError(ValidationError),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum SendRawTransactionStatus {
+    /// The hash of the transaction, if the provider returned it.
+    Ok(Option<String>),
+    NonceTooLow,
+    NonceTooHigh,
+    InsufficientFunds,
+}
+
+pub type SendRawTransactionResult = Result<SendRawTransactionStatus, RpcError>;
+
+/// The results of the providers, collapsed into one if they all agree.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum MultiRpcResult<T> {
+    Consistent(T),
+    Inconsistent(Vec<(RpcService, T)>),
+}
+
+/// Returns the EVM RPC canister, traps if the controllers did not configure it.
+pub fn evm_rpc_canister_id() -> Principal {
+    read_config(|c| c.evm_rpc_canister_id)
+        .unwrap_or_else(|| ic_cdk::trap("The EVM RPC canister is not configured"))
+}
+
+/// Returns an error if the EVM RPC canister is not configured or has no providers for the chain.
+pub fn check_chain_supported(chain_id: ChainId) -> Result<(), String> {
+    if read_config(|c| c.evm_rpc_canister_id).is_none() {
+        return Err("The EVM RPC canister is not configured".to_string());
+    }
+    if !supports_chain(chain_id) {
+        return Err(format!(
+            "Chain {chain_id} is not supported by the EVM RPC canister"
+        ));
+    }
+    Ok(())
+}
+
+/// Whether the EVM RPC canister has providers for the chain.
+pub fn supports_chain(chain_id: ChainId) -> bool {
+    matches!(chain_id, ETHEREUM_MAINNET_CHAIN_ID | SEPOLIA_CHAIN_ID)
+}
+
+/// Returns the providers the requests are sent to on the chain, traps if the chain is not supported.
+pub fn rpc_services(chain_id: ChainId) -> (RpcServices, Vec<RpcService>) {
+    match chain_id {
+        ETHEREUM_MAINNET_CHAIN_ID => {
+            let services = vec![
+                EthMainnetService::Ankr,
+                EthMainnetService::BlockPi,
+                EthMainnetService::PublicNode,
+                EthMainnetService::Cloudflare,
+            ];
+            (
+                RpcServices::EthMainnet(Some(services.clone())),
+                services.into_iter().map(RpcService::EthMainnet).collect(),
+            )
+        }
+        SEPOLIA_CHAIN_ID => {
+            let services = vec![
+                EthSepoliaService::Ankr,
+                EthSepoliaService::BlockPi,
+                EthSepoliaService::PublicNode,
+                EthSepoliaService::Sepolia,
+            ];
+            (
+                RpcServices::EthSepolia(Some(services.clone())),
+                services.into_iter().map(RpcService::EthSepolia).collect(),
+            )
+        }
+        _ => ic_cdk::trap(&format!(
+            "Chain {chain_id} is not supported by the EVM RPC canister"
+        )),
+    }
+}
+
+/// Returns the name of a provider, e.g. `EthSepolia(Ankr)`.
+pub fn provider_name(service: &RpcService) -> String {
+    match service {
+        RpcService::Custom(api) => api.url.clone(),
+        service => format!("{service:?}"),
+    }
+}
+
+/// Pairs the result of each provider with its name.
+pub fn provider_results<T: Clone>(
+    providers: &[RpcService],
+    result: MultiRpcResult<T>,
+) -> Vec<(String, T)> {
+    match result {
+        MultiRpcResult::Consistent(result) => providers
+            .iter()
+            .map(|service| (provider_name(service), result.clone()))
+            .collect(),
+        MultiRpcResult::Inconsistent(results) => results
+            .into_iter()
+            .map(|(service, result)| (provider_name(&service), result))
+            .collect(),
+    }
+}
+
+fn send_status(result: SendRawTransactionResult) -> SendTransactionStatus {
+    match result {
+        Ok(SendRawTransactionStatus::Ok(_)) => SendTransactionStatus::Ok,
+        Ok(SendRawTransactionStatus::NonceTooLow) => SendTransactionStatus::NonceTooLow,
+        Ok(SendRawTransactionStatus::NonceTooHigh) => SendTransactionStatus::NonceTooHigh,
+        Ok(SendRawTransactionStatus::InsufficientFunds) => SendTransactionStatus::InsufficientFunds,
+        Err(err) => SendTransactionStatus::Error(format!("{err:?}")),
+    }
+}
+
+/// Broadcasts a signed raw transaction with `eth_sendRawTransaction` through the providers of the chain.
+///
+/// The transaction hash is computed from the raw transaction, so that it is returned even if no provider accepted it.
+pub async fn send_raw_transaction(
+    canister_id: Principal,
+    chain_id: ChainId,
+    raw_transaction: String,
+) -> SendTransactionResponse {
+    let (services, providers) = rpc_services(chain_id);
+
+    let transaction_hash = format!("0x{}", hex::encode(keccak256(decode_hex(&raw_transaction))));
+
+    let result: Result<(MultiRpcResult<SendRawTransactionResult>,), _> =
+        ic_cdk::api::call::call_with_payment128(
+            canister_id,
+            "eth_sendRawTransaction",
+            (services, None::<RpcConfig>, raw_transaction.clone()),
+            EVM_RPC_CYCLES,
+        )
+        .await;
+    record_cycles_kept();
+    let (result,) = result
+        .unwrap_or_else(|(_, err)| ic_cdk::trap(&format!("failed to send the transaction: {err}")));
+
+    SendTransactionResponse {
+        transaction_hash,
+        raw_transaction,
+        provider_results: provider_results(&providers, result)
+            .into_iter()
+            .map(|(provider, result)| ProviderSendResult {
+                provider,
+                status: send_status(result),
+            })
+            .collect(),
+    }
+}
+
+/// Charges the cycles the EVM RPC canister kept from the call that just returned to the caller.
+fn record_cycles_kept() {
+    let refunded = ic_cdk::api::call::msg_cycles_refunded128();
+    record_rpc_call(
+        StoredPrincipal(ic_cdk::caller()),
+        EVM_RPC_CYCLES.saturating_sub(refunded),
+    );
+}
+
+/// A call of a JSON-RPC batch, e.g. `eth_getBalance` with its parameters.
+pub struct JsonRpcCall {
+    pub method: &'static str,
+    pub params: Value,
+    /// Whether a `null` result is valid, e.g. the receipt of a pending transaction, rather than a missing result.
+    pub nullable: bool,
+}
+
+/// Sends a JSON-RPC request to one provider, returns the response body.
+async fn request(
+    canister_id: Principal,
+    service: RpcService,
+    json: String,
+    max_response_bytes: u64,
+) -> Result<String, String> {
+    let result: Result<(Result<String, RpcError>,), _> = ic_cdk::api::call::call_with_payment128(
+        canister_id,
+        "request",
+        (service, json, max_response_bytes),
+        EVM_RPC_CYCLES,
+    )
+    .await;
+    record_cycles_kept();
+    let (result,) =
+        result.map_err(|(_, err)| format!("failed to call the EVM RPC canister: {err}"))?;
+
+    result.map_err(|err| format!("{err:?}"))
+}
+
+/// Parses a hex-encoded quantity or 32-byte word of a JSON-RPC result.
+pub fn parse_quantity(result: &Value) -> Result<U256, String> {
+    let hex = result
+        .as_str()
+        .map(|r| r.trim_start_matches("0x"))
+        .filter(|hex| !hex.is_empty())
+        .ok_or_else(|| format!("invalid quantity: {result}"))?;
+
+    U256::from_str_radix(hex, 16).map_err(|err| format!("invalid quantity {result}: {err}"))
+}
+
+/// Returns the results of a batch response in the order of the calls.
+fn batch_results(
+    response: &str,
+    calls: &[JsonRpcCall],
+) -> Result<Vec<Result<Value, String>>, String> {
+    let responses: Vec<Value> = serde_json::from_str(response)
+        .map_err(|err| format!("failed to parse the JSON-RPC response: {err}"))?;
+
+    Ok((0..calls.len())
+        .map(|id| {
+            let response = responses
+                .iter()
+                .find(|r| r["id"].as_u64() == Some(id as u64))
+                .ok_or_else(|| format!("missing response to call {id}"))?;
+            match (response.get("result"), &response["error"]) {
+                (_, Value::Object(error)) => Err(format!(
+                    "JSON-RPC error {}: {}",
+                    error.get("code").unwrap_or(&Value::Null),
+                    error.get("message").unwrap_or(&Value::Null)
+                )),
+                (None, _) => Err(format!("missing result of call {id}")),
+                (Some(Value::Null), _) if !calls[id].nullable => {
+                    Err(format!("null result of call {id}"))
+                }
+                (Some(result), _) => Ok(result.clone()),
+            }
+        })
+        .collect())
+}
+
+/// Sends the calls in a single batch to each provider of the chain, in parallel.
+///
+/// Returns the results of each provider, in the order of the calls.
+pub async fn batch_request(
+    chain_id: ChainId,
+    calls: &[JsonRpcCall],
+    max_response_bytes: u64,
+) -> Vec<Vec<Result<Value, String>>> {
+    let canister_id = evm_rpc_canister_id();
+    let (_, providers) = rpc_services(chain_id);
+
+    let json = Value::Array(
+        calls
+            .iter()
+            .enumerate()
+            .map(|(id, call)| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": call.method,
+                    "params": call.params,
+                })
+            })
+            .collect(),
+    )
+    .to_string();
+
+    let responses = join_all(
+        providers
+            .into_iter()
+            .map(|service| request(canister_id, service, json.clone(), max_response_bytes)),
+    )
+    .await;
+
+    responses
+        .into_iter()
+        .map(
+            |response| match response.and_then(|r| batch_results(&r, calls)) {
+                Ok(results) => results,
+                Err(err) => vec![Err(err); calls.len()],
+            },
+        )
+        .collect()
+}
+
+/// Returns the result a majority of the providers agree on.
+pub fn consensus<T: PartialEq + Clone>(results: &[Result<T, String>]) -> Result<T, String> {
+    let threshold = results.len() / 2 + 1;
+
+    let ok: Vec<&T> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+    if let Some(value) = ok
+        .iter()
+        .find(|value| ok.iter().filter(|other| other == value).count() >= threshold)
+    {
+        return Ok((*value).clone());
+    }
+
+    match results.iter().find_map(|r| r.as_ref().err()) {
+        Some(err) if ok.is_empty() => Err(err.clone()),
+        _ => Err("The providers disagree".to_string()),
+    }
+}
@@ -0,0 +1,128 @@
+use crate::evm_rpc::{batch_request, parse_quantity, JsonRpcCall};
+use crate::u256_to_nat;
+use ethers_core::abi::ethereum_types::U256;
+use serde_json::{json, Value};
+use shared::types::fee::{FeeEstimate, FeeSuggestion};
+use shared::types::token::ChainId;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+/// The number of blocks the priority fees are taken from.
+const FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// The reward percentiles of the low, medium and high suggestions.
+const REWARD_PERCENTILES: [u8; 3] = [10, 50, 90];
+
+/// The time a suggestion is served from the cache, about two blocks on Ethereum.
+const FEE_CACHE_TTL_NANOS: u64 = 30 * 1_000_000_000;
+
+const MAX_RESPONSE_BYTES: u64 = 4_096;
+
+thread_local! {
+    // The suggestions are kept on the heap: losing them on upgrade merely fetches them again.
+    static FEE_SUGGESTIONS: RefCell<BTreeMap<ChainId, FeeSuggestion>> = RefCell::default();
+}
+
+/// The base fee of the next block and the median priority fee of each percentile, as seen by a provider.
+struct FeeHistory {
+    base_fee_per_gas: U256,
+    priority_fees: [U256; 3],
+}
+
+fn median(mut values: Vec<U256>) -> U256 {
+    values.sort();
+    values.get(values.len() / 2).copied().unwrap_or_default()
+}
+
+/// Parses an `eth_feeHistory` result, see the [JSON-RPC specification](https://ethereum.github.io/execution-apis/api-documentation/).
+fn parse_fee_history(result: &Value) -> Result<FeeHistory, String> {
+    // The last base fee is the one of the block following the newest one.
+    let base_fee_per_gas = result["baseFeePerGas"]
+        .as_array()
+        .and_then(|fees| fees.last())
+        .ok_or("missing base fee")
+        .and_then(|fee| parse_quantity(fee).map_err(|_| "invalid base fee"))?;
+
+    let rewards = result["reward"]
+        .as_array()
+        .filter(|rewards| !rewards.is_empty())
+        .ok_or("missing rewards")?;
+
+    let mut priority_fees = [U256::zero(); 3];
+    for (i, fee) in priority_fees.iter_mut().enumerate() {
+        *fee = median(
+            rewards
+                .iter()
+                .map(|block| parse_quantity(&block[i]))
+                .collect::<Result<_, _>>()?,
+        );
+    }
+
+    Ok(FeeHistory {
+        base_fee_per_gas,
+        priority_fees,
+    })
+}
+
+/// Allows the base fee to double, it rises by at most 12.5% per block.
+fn estimate(base_fee_per_gas: U256, priority_fee: U256) -> FeeEstimate {
+    FeeEstimate {
+        max_fee_per_gas: u256_to_nat(base_fee_per_gas * 2 + priority_fee),
+        max_priority_fee_per_gas: u256_to_nat(priority_fee),
+    }
+}
+
+async fn fetch_fee_suggestion(chain_id: ChainId, now: u64) -> FeeSuggestion {
+    let call = JsonRpcCall {
+        method: "eth_feeHistory",
+        params: json!([
+            format!("{FEE_HISTORY_BLOCKS:#x}"),
+            "latest",
+            REWARD_PERCENTILES
+        ]),
+        nullable: false,
+    };
+
+    let histories: Vec<FeeHistory> = batch_request(chain_id, &[call], MAX_RESPONSE_BYTES)
+        .await
+        .into_iter()
+        .filter_map(|results| {
+            results[0]
+                .as_ref()
+                .ok()
+                .and_then(|result| parse_fee_history(result).ok())
+        })
+        .collect();
+
+    if histories.is_empty() {
+        ic_cdk::trap(&format!(
+            "failed to get the fee history of chain {chain_id}"
+        ));
+    }
+
+    // The providers may not be at the same block, so the median of their values is used instead of a consensus.
+    let base_fee_per_gas = median(histories.iter().map(|h| h.base_fee_per_gas).collect());
+    let [low, medium, high] =
+        [0, 1, 2].map(|i| median(histories.iter().map(|h| h.priority_fees[i]).collect()));
+
+    FeeSuggestion {
+        chain_id,
+        base_fee_per_gas: u256_to_nat(base_fee_per_gas),
+        low: estimate(base_fee_per_gas, low),
+        medium: estimate(base_fee_per_gas, medium),
+        high: estimate(base_fee_per_gas, high),
+        timestamp: now,
+    }
+}
+
+/// Returns the fees suggested from the history of the last blocks, cached briefly per chain.
+pub async fn suggest_fees(chain_id: ChainId, now: u64) -> FeeSuggestion {
+    let cached = FEE_SUGGESTIONS.with(|s| s.borrow().get(&chain_id).cloned());
+    if let Some(suggestion) = cached.filter(|s| now < s.timestamp + FEE_CACHE_TTL_NANOS) {
+        return suggestion;
+    }
+
+    let suggestion = fetch_fee_suggestion(chain_id, now).await;
+    FEE_SUGGESTIONS.with(|s| s.borrow_mut().insert(chain_id, suggestion.clone()));
+    suggestion
+}
@@ -1,4 +1,5 @@
-use crate::read_config;
+use crate::rate_limit::{try_consume_external_call_token, try_consume_signing_token};
+use crate::{read_config, StoredPrincipal};
 use candid::Principal;
 use ic_cdk::caller;
 
@@ -10,6 +11,28 @@ pub fn caller_is_not_anonymous() -> Result<(), String> {
     }
 }
 
+/// Signing spends cycles on threshold ECDSA, hence every call consumes a token of the caller's rate limit bucket.
+pub fn caller_can_sign() -> Result<(), String> {
+    caller_is_not_anonymous()?;
+
+    if try_consume_signing_token(StoredPrincipal(caller()), ic_cdk::api::time()) {
+        Ok(())
+    } else {
+        Err("Signing rate limit exceeded.".to_string())
+    }
+}
+
+/// Limits the calls that make the canister call other canisters or the RPC providers, whose HTTPS outcalls it pays.
+pub fn caller_can_call_external() -> Result<(), String> {
+    caller_is_not_anonymous()?;
+
+    if try_consume_external_call_token(StoredPrincipal(caller()), ic_cdk::api::time()) {
+        Ok(())
+    } else {
+        Err("External call rate limit exceeded.".to_string())
+    }
+}
+
 pub fn caller_is_allowed() -> Result<(), String> {
     if read_config(|s| s.allowed_callers.contains(&caller())) {
         Ok(())
@@ -17,3 +40,20 @@ pub fn caller_is_allowed() -> Result<(), String> {
         Err("Caller is not allowed.".to_string())
     }
 }
+
+pub fn caller_is_controller() -> Result<(), String> {
+    if ic_cdk::api::is_controller(&caller()) {
+        Ok(())
+    } else {
+        Err("Caller is not a controller.".to_string())
+    }
+}
+
+/// The methods the canister calls on itself, e.g. to catch the errors of a task run by a timer.
+pub fn caller_is_self() -> Result<(), String> {
+    if caller() == ic_cdk::id() {
+        Ok(())
+    } else {
+        Err("Caller is not the canister itself.".to_string())
+    }
+}
@@ -0,0 +1,90 @@
+//! The transactions of an account, as served by the [index](https://github.com/dfinity/ic/tree/master/rs/rosetta-api/icrc1/index-ng) of an ICRC ledger.
+use candid::{CandidType, Deserialize, Nat, Principal};
+use shared::types::account::IcrcAccount;
+use shared::types::custom_token::IndexId;
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct Transfer {
+    pub from: IcrcAccount,
+    pub to: IcrcAccount,
+    pub amount: Nat,
+    pub fee: Option<Nat>,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct Mint {
+    pub to: IcrcAccount,
+    pub amount: Nat,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct Burn {
+    pub from: IcrcAccount,
+    pub amount: Nat,
+}
+
+/// The subset of a transaction of the index that is used, the approvals are left out.
+#[derive(CandidType, Deserialize, Clone)]
+pub struct Transaction {
+    pub kind: String,
+    pub mint: Option<Mint>,
+    pub burn: Option<Burn>,
+    pub transfer: Option<Transfer>,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct TransactionWithId {
+    pub id: Nat,
+    pub transaction: Transaction,
+}
+
+#[derive(CandidType, Deserialize)]
+struct GetAccountTransactionsArgs {
+    account: IcrcAccount,
+    start: Option<Nat>,
+    max_results: Nat,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct GetTransactions {
+    /// The transactions, most recent first.
+    pub transactions: Vec<TransactionWithId>,
+    pub oldest_tx_id: Option<Nat>,
+}
+
+#[derive(CandidType, Deserialize)]
+struct GetTransactionsErr {
+    message: String,
+}
+
+/// Whether the account is the default account of the principal.
+pub fn is_default_account(account: &IcrcAccount, principal: &Principal) -> bool {
+    account.owner == *principal && account.effective_subaccount() == Ok([0; 32])
+}
+
+/// Returns the transactions of the default account of the principal, most recent first, older than the `start` one.
+pub async fn get_account_transactions(
+    index_id: IndexId,
+    principal: &Principal,
+    start: Option<Nat>,
+    max_results: u64,
+) -> Result<GetTransactions, String> {
+    let args = GetAccountTransactionsArgs {
+        account: IcrcAccount {
+            owner: *principal,
+            subaccount: None,
+        },
+        start,
+        max_results: Nat::from(max_results),
+    };
+
+    let (result,): (Result<GetTransactions, GetTransactionsErr>,) =
+        ic_cdk::call(index_id, "get_account_transactions", (args,))
+            .await
+            .map_err(|(_, err)| {
+                format!("failed to get the transactions from index {index_id}: {err}")
+            })?;
+
+    result.map_err(|err| format!("Index {index_id} failed: {}", err.message))
+}
@@ -0,0 +1,168 @@
+use crate::{mutate_state, read_config, read_state, Candid, StoredPrincipal};
+use candid::{CandidType, Deserialize, Int, Nat, Principal};
+use serde_bytes::ByteBuf;
+use sha2::{Digest, Sha256};
+use shared::types::custom_token::{CustomToken, IcrcToken, IcrcTokenMetadata, Token};
+
+/// A value of the `icrc1_metadata` of a ledger, see [ICRC-1](https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-1#icrc1_metadata).
+#[derive(CandidType, Deserialize)]
+enum MetadataValue {
+    Nat(Nat),
+    Int(Int),
+    Text(String),
+    Blob(ByteBuf),
+}
+
+/// How long validated metadata is cached: a ledger may change its fee, symbol or logo.
+const METADATA_TTL_NANOS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// The error of [ICRC-106](https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-106/ICRC-106.md) `icrc106_get_index_principal`.
+#[derive(CandidType, Deserialize)]
+enum IndexPrincipalError {
+    IndexPrincipalNotSet,
+    GenericError {
+        error_code: Nat,
+        description: String,
+    },
+}
+
+/// Returns the cached metadata of a ledger, if it was validated less than a day ago.
+pub fn cached_metadata(ledger_id: &Principal) -> Option<IcrcTokenMetadata> {
+    read_state(|s| {
+        s.icrc_metadata
+            .get(&StoredPrincipal(*ledger_id))
+            .map(|m| m.0)
+    })
+    .filter(|m| ic_cdk::api::time().saturating_sub(m.validated_timestamp) < METADATA_TTL_NANOS)
+}
+
+/// Checks the ledger and index canisters of the custom tokens if the validation is enabled, otherwise does nothing.
+///
+/// Traps if a ledger does not expose its ICRC-1 metadata or if an index canister belongs to another ledger.
+pub async fn assert_valid_custom_tokens(tokens: &[CustomToken]) {
+    if !read_config(|c| c.validate_custom_tokens.unwrap_or(false)) {
+        return;
+    }
+
+    for CustomToken { token, .. } in tokens {
+        match token {
+            Token::Icrc(token) => assert_valid_icrc_token(token).await,
+        }
+    }
+}
+
+/// The cache is shared by all users: its index is the one the ledger itself designates, an index the ledger does not
+/// know of is only checked against the ledger it claims to belong to, and not cached.
+async fn assert_valid_icrc_token(token: &IcrcToken) {
+    let cached = cached_metadata(&token.ledger_id);
+    if let Some(cached) = cached.as_ref() {
+        match (token.index_id, cached.index_id) {
+            (None, _) => return,
+            (Some(index_id), Some(cached_index_id)) if index_id == cached_index_id => return,
+            (Some(index_id), Some(_)) => ic_cdk::trap(&format!(
+                "Index {index_id} does not belong to ledger {}",
+                token.ledger_id
+            )),
+            (Some(_), None) => {}
+        }
+    }
+
+    let mut metadata = match cached {
+        Some(cached) => cached,
+        None => fetch_metadata(&token.ledger_id).await,
+    };
+
+    if let Some(index_id) = token.index_id {
+        match ledger_index(&token.ledger_id).await {
+            Some(ledger_index_id) if ledger_index_id == index_id => {
+                metadata.index_id = Some(index_id);
+            }
+            Some(_) => ic_cdk::trap(&format!(
+                "Index {index_id} does not belong to ledger {}",
+                token.ledger_id
+            )),
+            None => assert_index_ledger(&index_id, &token.ledger_id).await,
+        }
+    }
+
+    metadata.validated_timestamp = ic_cdk::api::time();
+
+    mutate_state(|s| {
+        s.icrc_metadata
+            .insert(StoredPrincipal(token.ledger_id), Candid(metadata))
+    });
+}
+
+/// Returns the index the ledger designates, `None` if the ledger does not implement ICRC-106 or has no index set.
+async fn ledger_index(ledger_id: &Principal) -> Option<Principal> {
+    let result: Result<(Result<Principal, IndexPrincipalError>,), _> =
+        ic_cdk::call(*ledger_id, "icrc106_get_index_principal", ()).await;
+    result.ok().and_then(|(index,)| index.ok())
+}
+
+/// Traps if the index does not claim to belong to the ledger.
+async fn assert_index_ledger(index_id: &Principal, ledger_id: &Principal) {
+    let (index_ledger_id,): (Principal,) = ic_cdk::call(*index_id, "ledger_id", ())
+        .await
+        .unwrap_or_else(|(_, err)| {
+            ic_cdk::trap(&format!(
+                "failed to get the ledger of index {index_id}: {err}"
+            ))
+        });
+    if index_ledger_id != *ledger_id {
+        ic_cdk::trap(&format!(
+            "Index {index_id} does not belong to ledger {ledger_id}"
+        ));
+    }
+}
+
+async fn fetch_metadata(ledger_id: &Principal) -> IcrcTokenMetadata {
+    let (entries,): (Vec<(String, MetadataValue)>,) =
+        ic_cdk::call(*ledger_id, "icrc1_metadata", ())
+            .await
+            .unwrap_or_else(|(_, err)| {
+                ic_cdk::trap(&format!(
+                    "failed to get the metadata of ledger {ledger_id}: {err}"
+                ))
+            });
+
+    let find = |key: &str| {
+        entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    };
+    let missing = |key: &str| -> ! {
+        ic_cdk::trap(&format!(
+            "Ledger {ledger_id} is missing a valid {key} metadata"
+        ))
+    };
+
+    let symbol = match find("icrc1:symbol") {
+        Some(MetadataValue::Text(symbol)) => symbol.clone(),
+        _ => missing("icrc1:symbol"),
+    };
+    let decimals = match find("icrc1:decimals") {
+        Some(MetadataValue::Nat(decimals)) => {
+            u8::try_from(&decimals.0).unwrap_or_else(|_| missing("icrc1:decimals"))
+        }
+        _ => missing("icrc1:decimals"),
+    };
+    let fee = match find("icrc1:fee") {
+        Some(MetadataValue::Nat(fee)) => fee.clone(),
+        _ => missing("icrc1:fee"),
+    };
+    let logo_hash = match find("icrc1:logo") {
+        Some(MetadataValue::Text(logo)) => Some(hex::encode(Sha256::digest(logo.as_bytes()))),
+        _ => None,
+    };
+
+    IcrcTokenMetadata {
+        symbol,
+        decimals,
+        fee,
+        logo_hash,
+        index_id: None,
+        validated_timestamp: 0,
+    }
+}
@@ -1,10 +1,26 @@
-use crate::guards::{caller_is_allowed, caller_is_not_anonymous};
+use crate::allowlist::{assert_not_in_allowlist_mode, assert_recipient_allowed};
+use crate::chain::{assert_chain_enabled, assert_transaction_allowed, chain_registry};
+use crate::contact::assert_valid_contact;
+use crate::cycles::{encode_cycles_metrics, record_signature, top_consumers, SIGN_WITH_ECDSA_FEE};
+use crate::denylist::{
+    assert_approval_allowed, assert_not_denylisted, custom_token_denylisted_id, is_denylisted,
+};
+use crate::guards::{
+    caller_can_call_external, caller_can_sign, caller_is_allowed, caller_is_controller,
+    caller_is_not_anonymous, caller_is_self,
+};
+use crate::icrc_metadata::{assert_valid_custom_tokens, cached_metadata};
+use crate::nonce::SignedNonce;
+use crate::rate_limit::encode_rate_limit_metrics;
+use crate::recovery::{key_principal_of, try_key_principal_of, KeyControl};
 use crate::token::{add_to_user_token, remove_from_user_token};
+use crate::user_profile::{assert_valid_settings, set_user_settings};
 use candid::{CandidType, Deserialize, Nat, Principal};
 use core::ops::Deref;
 use ethers_core::abi::ethereum_types::{Address, H160, U256, U64};
 use ethers_core::types::Bytes;
 use ethers_core::utils::keccak256;
+use ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
 use ic_cdk::api::management_canister::ecdsa::{
     ecdsa_public_key, sign_with_ecdsa, EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyArgument,
     SignWithEcdsaArgument,
@@ -18,27 +34,147 @@ use ic_stable_structures::{
 use k256::PublicKey;
 use serde_bytes::ByteBuf;
 use shared::http::{HttpRequest, HttpResponse};
-use shared::metrics::get_metrics;
+use shared::metrics::{get_metrics, MetricsEncoder};
 use shared::std_canister_status;
-use shared::types::custom_token::{CustomToken, CustomTokenId};
-use shared::types::token::{UserToken, UserTokenId};
-use shared::types::transaction::SignRequest;
+use shared::types::account::IcrcAccount;
+use shared::types::allowlist::Allowlist;
+use shared::types::balance::EthBalances;
+use shared::types::catalogue::{
+    CatalogueToken, CatalogueTokenId, CatalogueTokenPage, ListedUserCatalogueToken, Network,
+    UserCatalogueToken,
+};
+use shared::types::chain::{ChainConfig, TransactionType};
+use shared::types::cketh::{
+    CkEthConversion, CkEthDepositArgs, CkEthMinter, ConversionStatus, TrackedConversion,
+};
+use shared::types::contact::Contact;
+use shared::types::custom_token::{
+    CustomToken, CustomTokenId, IcrcTokenMetadata, LedgerId, ListedCustomToken,
+};
+use shared::types::cycles::{CyclesConsumer, CyclesUsage};
+use shared::types::denylist::{DenylistEntry, DenylistedId};
+use shared::types::fee::FeeSuggestion;
+use shared::types::notification::{Notification, NotificationId, NotificationPage};
+use shared::types::portfolio::{Portfolio, PortfolioSnapshot};
+use shared::types::rate_limit::RateLimitConfig;
+use shared::types::recovery::{ApproveRecoveryRequest, GuardianConfig, RecoveryRequest};
+use shared::types::scheduled_transaction::{
+    ScheduleTransactionRequest, ScheduledTransaction, ScheduledTransactionId,
+};
+use shared::types::shared_account::{
+    CreateSharedAccountRequest, Proposal, ProposalId, ProposalPage, ProposalRef,
+    ProposeTransactionRequest, SharedAccount, SharedAccountId,
+};
+use shared::types::taproot::TaprootSignature;
+use shared::types::token::{ChainId, ListedUserToken, UserToken, UserTokenId};
+use shared::types::transaction::{SendTransactionResponse, SignRequest};
+use shared::types::transaction_history::{TransactionHistoryCursor, TransactionHistoryPage};
+use shared::types::user_profile::{UpdateUserSettingsRequest, UserProfile};
 use shared::types::{Arg, InitArg};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::str::FromStr;
 
+mod allowlist;
+mod balance;
+mod catalogue;
+mod chain;
+mod cketh;
+mod contact;
+mod cycles;
+mod denylist;
+mod ens;
+mod evm_rpc;
+mod fees;
 mod guards;
+mod icrc_index;
+mod icrc_metadata;
+mod nonce;
+mod notification;
+mod portfolio;
+mod portfolio_history;
+mod rate_limit;
+mod recovery;
+mod scheduled_transaction;
+mod schnorr;
+mod shared_account;
+mod solana;
+mod taproot;
 mod token;
+mod transaction_history;
+mod user_profile;
 
 type VMem = VirtualMemory<DefaultMemoryImpl>;
 type ConfigCell = StableCell<Option<Candid<Config>>, VMem>;
 type UserTokenMap = StableBTreeMap<StoredPrincipal, Candid<Vec<UserToken>>, VMem>;
 type CustomTokenMap = StableBTreeMap<StoredPrincipal, Candid<Vec<CustomToken>>, VMem>;
+type ContactMap = StableBTreeMap<StoredPrincipal, Candid<Vec<Contact>>, VMem>;
+type UserProfileMap = StableBTreeMap<StoredPrincipal, Candid<UserProfile>, VMem>;
+type CyclesUsageMap = StableBTreeMap<StoredPrincipal, Candid<CyclesUsage>, VMem>;
+type CyclesTotalsCell = StableCell<Candid<CyclesUsage>, VMem>;
+type CyclesRankingMap = StableBTreeMap<(u128, StoredPrincipal), (), VMem>;
+type IcrcMetadataMap = StableBTreeMap<StoredPrincipal, Candid<IcrcTokenMetadata>, VMem>;
+type CatalogueMap = StableBTreeMap<String, Candid<CatalogueToken>, VMem>;
+type CatalogueLogoMap = StableBTreeMap<String, String, VMem>;
+type UserCatalogueTokenMap = StableBTreeMap<StoredPrincipal, Candid<Vec<UserCatalogueToken>>, VMem>;
+type DenylistMap = StableBTreeMap<String, Candid<DenylistEntry>, VMem>;
+type AllowlistMap = StableBTreeMap<StoredPrincipal, Candid<Allowlist>, VMem>;
+type ScheduledTransactionMap =
+    StableBTreeMap<StoredPrincipal, Candid<Vec<ScheduledTransaction>>, VMem>;
+type ScheduledTransactionIdCell = StableCell<ScheduledTransactionId, VMem>;
+type ScheduledReleaseMap = StableBTreeMap<(u64, ScheduledTransactionId), StoredPrincipal, VMem>;
+type GuardianConfigMap = StableBTreeMap<StoredPrincipal, Candid<GuardianConfig>, VMem>;
+type RecoveryRequestMap = StableBTreeMap<StoredPrincipal, Candid<RecoveryRequest>, VMem>;
+type KeyControlMap = StableBTreeMap<StoredPrincipal, Candid<KeyControl>, VMem>;
+type SharedAccountMap = StableBTreeMap<SharedAccountId, Candid<SharedAccount>, VMem>;
+type SharedAccountIdCell = StableCell<SharedAccountId, VMem>;
+type ProposalMap = StableBTreeMap<(SharedAccountId, ProposalId), Candid<Proposal>, VMem>;
+type SharedAccountMemberMap = StableBTreeMap<(StoredPrincipal, SharedAccountId), (), VMem>;
+type ProposalIdCell = StableCell<ProposalId, VMem>;
+type OpenProposalMap = StableBTreeMap<(SharedAccountId, ProposalId), (), VMem>;
+type CkEthConversionMap = StableBTreeMap<StoredPrincipal, Candid<Vec<TrackedConversion>>, VMem>;
+type SignedNonceMap = StableBTreeMap<(StoredPrincipal, ChainId), Candid<Vec<SignedNonce>>, VMem>;
+type PortfolioHistoryUserMap = StableBTreeMap<StoredPrincipal, u64, VMem>;
+type PortfolioSnapshotMap = StableBTreeMap<(StoredPrincipal, u64), Candid<PortfolioSnapshot>, VMem>;
+type NotificationUserMap = StableBTreeMap<StoredPrincipal, u64, VMem>;
+type LastSeenTransactionMap =
+    StableBTreeMap<(StoredPrincipal, StoredPrincipal), Candid<Option<Nat>>, VMem>;
+type NotificationMap =
+    StableBTreeMap<(StoredPrincipal, NotificationId), Candid<Notification>, VMem>;
 
 const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(0);
 const USER_TOKEN_MEMORY_ID: MemoryId = MemoryId::new(1);
 const USER_CUSTOM_TOKEN_MEMORY_ID: MemoryId = MemoryId::new(2);
+const USER_CONTACT_MEMORY_ID: MemoryId = MemoryId::new(3);
+const USER_PROFILE_MEMORY_ID: MemoryId = MemoryId::new(4);
+const CYCLES_USAGE_MEMORY_ID: MemoryId = MemoryId::new(5);
+const CYCLES_TOTALS_MEMORY_ID: MemoryId = MemoryId::new(6);
+const CYCLES_RANKING_MEMORY_ID: MemoryId = MemoryId::new(7);
+const ICRC_METADATA_MEMORY_ID: MemoryId = MemoryId::new(8);
+const CATALOGUE_MEMORY_ID: MemoryId = MemoryId::new(9);
+const CATALOGUE_LOGO_MEMORY_ID: MemoryId = MemoryId::new(10);
+const USER_CATALOGUE_TOKEN_MEMORY_ID: MemoryId = MemoryId::new(11);
+const DENYLIST_MEMORY_ID: MemoryId = MemoryId::new(12);
+const ALLOWLIST_MEMORY_ID: MemoryId = MemoryId::new(13);
+const SCHEDULED_TRANSACTION_MEMORY_ID: MemoryId = MemoryId::new(14);
+const SCHEDULED_TRANSACTION_ID_MEMORY_ID: MemoryId = MemoryId::new(15);
+const SCHEDULED_RELEASE_MEMORY_ID: MemoryId = MemoryId::new(16);
+const GUARDIAN_CONFIG_MEMORY_ID: MemoryId = MemoryId::new(17);
+const RECOVERY_REQUEST_MEMORY_ID: MemoryId = MemoryId::new(18);
+const KEY_CONTROL_MEMORY_ID: MemoryId = MemoryId::new(19);
+const SHARED_ACCOUNT_MEMORY_ID: MemoryId = MemoryId::new(20);
+const SHARED_ACCOUNT_ID_MEMORY_ID: MemoryId = MemoryId::new(21);
+const PROPOSAL_MEMORY_ID: MemoryId = MemoryId::new(22);
+const SHARED_ACCOUNT_MEMBER_MEMORY_ID: MemoryId = MemoryId::new(23);
+const PROPOSAL_ID_MEMORY_ID: MemoryId = MemoryId::new(24);
+const OPEN_PROPOSAL_MEMORY_ID: MemoryId = MemoryId::new(25);
+const SIGNED_NONCE_MEMORY_ID: MemoryId = MemoryId::new(26);
+const CKETH_CONVERSION_MEMORY_ID: MemoryId = MemoryId::new(27);
+const PORTFOLIO_HISTORY_USER_MEMORY_ID: MemoryId = MemoryId::new(28);
+const PORTFOLIO_SNAPSHOT_MEMORY_ID: MemoryId = MemoryId::new(29);
+const NOTIFICATION_USER_MEMORY_ID: MemoryId = MemoryId::new(30);
+const LAST_SEEN_TRANSACTION_MEMORY_ID: MemoryId = MemoryId::new(31);
+const NOTIFICATION_MEMORY_ID: MemoryId = MemoryId::new(32);
 
 const MAX_SYMBOL_LENGTH: usize = 20;
 
@@ -52,6 +188,36 @@ thread_local! {
             config: ConfigCell::init(mm.borrow().get(CONFIG_MEMORY_ID), None).expect("config cell initialization should succeed"),
             user_token: UserTokenMap::init(mm.borrow().get(USER_TOKEN_MEMORY_ID)),
             custom_token: CustomTokenMap::init(mm.borrow().get(USER_CUSTOM_TOKEN_MEMORY_ID)),
+            contact: ContactMap::init(mm.borrow().get(USER_CONTACT_MEMORY_ID)),
+            user_profile: UserProfileMap::init(mm.borrow().get(USER_PROFILE_MEMORY_ID)),
+            cycles_usage: CyclesUsageMap::init(mm.borrow().get(CYCLES_USAGE_MEMORY_ID)),
+            cycles_totals: CyclesTotalsCell::init(mm.borrow().get(CYCLES_TOTALS_MEMORY_ID), Candid::default()).expect("cycles totals cell initialization should succeed"),
+            cycles_ranking: CyclesRankingMap::init(mm.borrow().get(CYCLES_RANKING_MEMORY_ID)),
+            icrc_metadata: IcrcMetadataMap::init(mm.borrow().get(ICRC_METADATA_MEMORY_ID)),
+            catalogue: CatalogueMap::init(mm.borrow().get(CATALOGUE_MEMORY_ID)),
+            catalogue_logo: CatalogueLogoMap::init(mm.borrow().get(CATALOGUE_LOGO_MEMORY_ID)),
+            user_catalogue_token: UserCatalogueTokenMap::init(mm.borrow().get(USER_CATALOGUE_TOKEN_MEMORY_ID)),
+            denylist: DenylistMap::init(mm.borrow().get(DENYLIST_MEMORY_ID)),
+            allowlist: AllowlistMap::init(mm.borrow().get(ALLOWLIST_MEMORY_ID)),
+            scheduled_transaction: ScheduledTransactionMap::init(mm.borrow().get(SCHEDULED_TRANSACTION_MEMORY_ID)),
+            guardian_config: GuardianConfigMap::init(mm.borrow().get(GUARDIAN_CONFIG_MEMORY_ID)),
+            recovery_request: RecoveryRequestMap::init(mm.borrow().get(RECOVERY_REQUEST_MEMORY_ID)),
+            key_control: KeyControlMap::init(mm.borrow().get(KEY_CONTROL_MEMORY_ID)),
+            shared_account: SharedAccountMap::init(mm.borrow().get(SHARED_ACCOUNT_MEMORY_ID)),
+            shared_account_id: SharedAccountIdCell::init(mm.borrow().get(SHARED_ACCOUNT_ID_MEMORY_ID), 0).expect("shared account id cell initialization should succeed"),
+            proposal: ProposalMap::init(mm.borrow().get(PROPOSAL_MEMORY_ID)),
+            shared_account_member: SharedAccountMemberMap::init(mm.borrow().get(SHARED_ACCOUNT_MEMBER_MEMORY_ID)),
+            proposal_id: ProposalIdCell::init(mm.borrow().get(PROPOSAL_ID_MEMORY_ID), 0).expect("proposal id cell initialization should succeed"),
+            open_proposal: OpenProposalMap::init(mm.borrow().get(OPEN_PROPOSAL_MEMORY_ID)),
+            signed_nonce: SignedNonceMap::init(mm.borrow().get(SIGNED_NONCE_MEMORY_ID)),
+            cketh_conversion: CkEthConversionMap::init(mm.borrow().get(CKETH_CONVERSION_MEMORY_ID)),
+            portfolio_history_user: PortfolioHistoryUserMap::init(mm.borrow().get(PORTFOLIO_HISTORY_USER_MEMORY_ID)),
+            portfolio_snapshot: PortfolioSnapshotMap::init(mm.borrow().get(PORTFOLIO_SNAPSHOT_MEMORY_ID)),
+            notification_user: NotificationUserMap::init(mm.borrow().get(NOTIFICATION_USER_MEMORY_ID)),
+            last_seen_transaction: LastSeenTransactionMap::init(mm.borrow().get(LAST_SEEN_TRANSACTION_MEMORY_ID)),
+            notification: NotificationMap::init(mm.borrow().get(NOTIFICATION_MEMORY_ID)),
+            scheduled_transaction_id: ScheduledTransactionIdCell::init(mm.borrow().get(SCHEDULED_TRANSACTION_ID_MEMORY_ID), 0).expect("scheduled transaction id cell initialization should succeed"),
+            scheduled_release: ScheduledReleaseMap::init(mm.borrow().get(SCHEDULED_RELEASE_MEMORY_ID)),
         })
     );
 }
@@ -74,6 +240,24 @@ pub fn read_config<R>(f: impl FnOnce(&Config) -> R) -> R {
     })
 }
 
+pub fn mutate_config<R>(f: impl FnOnce(&mut Config) -> R) -> R {
+    mutate_state(|state| {
+        let mut config = state
+            .config
+            .get()
+            .as_ref()
+            .expect("config is not initialized")
+            .0
+            .clone();
+        let result = f(&mut config);
+        state
+            .config
+            .set(Some(Candid(config)))
+            .expect("setting config should succeed");
+        result
+    })
+}
+
 #[derive(Default)]
 struct Candid<T>(T)
 where
@@ -112,6 +296,66 @@ pub struct State {
     /// Introduced to support a broader range of user-defined custom tokens, beyond just ERC20.
     /// Future updates may include migrating existing ERC20 tokens to this more flexible structure.
     custom_token: CustomTokenMap,
+    /// The address book of the users, labelled recipients with their chain-specific addresses.
+    contact: ContactMap,
+    /// Settings and preferences of the users, previously kept in the local storage of the browser.
+    user_profile: UserProfileMap,
+    /// The cycles spent on behalf of each user, e.g. the fees of the threshold ECDSA signatures.
+    cycles_usage: CyclesUsageMap,
+    /// The sum of the cycles usage of all the users.
+    cycles_totals: CyclesTotalsCell,
+    /// The principals ordered by the cycles they spent, see `cycles::rank_key`.
+    cycles_ranking: CyclesRankingMap,
+    /// The metadata of the validated ICRC ledgers, shared by all the users.
+    icrc_metadata: IcrcMetadataMap,
+    /// The tokens curated by the controllers, keyed by `catalogue_key`.
+    catalogue: CatalogueMap,
+    /// The logos of the catalogue tokens, keyed by their hash.
+    catalogue_logo: CatalogueLogoMap,
+    /// The catalogue tokens the users added to their token list.
+    user_catalogue_token: UserCatalogueTokenMap,
+    /// The scam and phishing contracts and ledgers blocked by the controllers.
+    denylist: DenylistMap,
+    /// The recipients the users in allowlist mode restricted their transactions to.
+    allowlist: AllowlistMap,
+    /// The transactions the users queued to be signed at a later time.
+    scheduled_transaction: ScheduledTransactionMap,
+    /// The last id assigned to a scheduled transaction.
+    scheduled_transaction_id: ScheduledTransactionIdCell,
+    /// The owners of the pending scheduled transactions, by release timestamp and id.
+    scheduled_release: ScheduledReleaseMap,
+    /// The guardians who can recover the keys of the users.
+    guardian_config: GuardianConfigMap,
+    /// The recoveries in progress, keyed by the user whose keys are recovered.
+    recovery_request: RecoveryRequestMap,
+    /// The principals controlling the keys of another principal after a recovery, and the recovered principals.
+    key_control: KeyControlMap,
+    /// The accounts controlled by several members, with keys of their own.
+    shared_account: SharedAccountMap,
+    /// The last id assigned to a shared account.
+    shared_account_id: SharedAccountIdCell,
+    /// The transactions proposed by the members of the shared accounts and their approvals.
+    proposal: ProposalMap,
+    /// The shared accounts of each member, to list them without going through all the accounts.
+    shared_account_member: SharedAccountMemberMap,
+    /// The last id assigned to a proposal.
+    proposal_id: ProposalIdCell,
+    /// The proposals that are not closed, to cap them per account without going through the closed ones.
+    open_proposal: OpenProposalMap,
+    /// The highest nonces signed per principal and chain, to detect conflicting transactions.
+    signed_nonce: SignedNonceMap,
+    /// The ckETH deposits and withdrawals the users track.
+    cketh_conversion: CkEthConversionMap,
+    /// The users who opted in to the portfolio history, with the time they did.
+    portfolio_history_user: PortfolioHistoryUserMap,
+    /// The daily portfolio snapshots per user, keyed by the day since the epoch.
+    portfolio_snapshot: PortfolioSnapshotMap,
+    /// The users who opted in to the notifications of incoming transfers, with the time they did.
+    notification_user: NotificationUserMap,
+    /// The id of the most recent transaction seen per user and ledger, `None` if the ledger was polled before any.
+    last_seen_transaction: LastSeenTransactionMap,
+    /// The inbox of each user.
+    notification: NotificationMap,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -136,11 +380,25 @@ impl Storable for StoredPrincipal {
     }
 }
 
-#[derive(CandidType, Deserialize)]
+#[derive(CandidType, Deserialize, Clone)]
 pub struct Config {
     pub ecdsa_key_name: String,
     // A list of allowed callers to restrict access to endpoints that do not particularly check or use the caller()
     pub allowed_callers: Vec<Principal>,
+    // The name of the threshold Schnorr key. The ECDSA key name is used until it is set, as the IC names both keys alike.
+    pub schnorr_key_name: Option<String>,
+    // The registry of chains managed by the controllers. The default chains are used until it is set.
+    pub chains: Option<Vec<ChainConfig>>,
+    // The token bucket limiting the signing calls of each principal. A default limit applies until it is set.
+    pub signing_rate_limit: Option<RateLimitConfig>,
+    // The token bucket limiting the calls of each principal that call other canisters or the RPC providers. A default limit applies until it is set.
+    pub external_call_rate_limit: Option<RateLimitConfig>,
+    // Whether the ledger and index canisters of the custom tokens are checked before they are added. Disabled until it is set.
+    pub validate_custom_tokens: Option<bool>,
+    // The EVM RPC canister broadcasting the transactions signed by `sign_and_send_transaction`. Broadcasting is disabled until it is set.
+    pub evm_rpc_canister_id: Option<Principal>,
+    // The ckETH minter, the `cketh_minter` canister of `dfx.json`, and its chain. The ckETH conversions are disabled until it is set.
+    pub cketh_minter: Option<CkEthMinter>,
 }
 
 #[init]
@@ -149,17 +407,28 @@ fn init(arg: Arg) {
         Arg::Init(InitArg {
             ecdsa_key_name,
             allowed_callers,
+            schnorr_key_name,
         }) => mutate_state(|state| {
             state
                 .config
                 .set(Some(Candid(Config {
                     ecdsa_key_name,
                     allowed_callers,
+                    schnorr_key_name,
+                    chains: None,
+                    signing_rate_limit: None,
+                    external_call_rate_limit: None,
+                    validate_custom_tokens: None,
+                    evm_rpc_canister_id: None,
+                    cketh_minter: None,
                 })))
                 .expect("setting config should succeed");
         }),
         Arg::Upgrade => ic_cdk::trap("upgrade args in init"),
     }
+
+    portfolio_history::schedule_snapshots();
+    notification::schedule_polls();
 }
 
 #[post_upgrade]
@@ -170,7 +439,11 @@ fn post_upgrade(_: Option<Arg>) {
             .get()
             .as_ref()
             .expect("config is not initialized: reinstall the canister instead of upgrading");
-    })
+    });
+
+    scheduled_transaction::arm_release_timer(ic_cdk::api::time());
+    portfolio_history::schedule_snapshots();
+    notification::schedule_polls();
 }
 
 /// Processes external HTTP requests.
@@ -178,7 +451,7 @@ fn post_upgrade(_: Option<Arg>) {
 pub fn http_request(request: HttpRequest) -> HttpResponse {
     let parts: Vec<&str> = request.url.split('?').collect();
     match parts[0] {
-        "/metrics" => get_metrics(),
+        "/metrics" => get_metrics(encode_canister_metrics),
         _ => HttpResponse {
             status_code: 404,
             headers: vec![],
@@ -187,10 +460,22 @@ pub fn http_request(request: HttpRequest) -> HttpResponse {
     }
 }
 
+fn encode_canister_metrics(w: &mut MetricsEncoder<Vec<u8>>) -> std::io::Result<()> {
+    encode_rate_limit_metrics(w)?;
+    encode_cycles_metrics(w)
+}
+
 fn principal_to_derivation_path(p: &Principal) -> Vec<Vec<u8>> {
+    try_principal_to_derivation_path(p).unwrap_or_else(|e| ic_cdk::trap(&e))
+}
+
+fn try_principal_to_derivation_path(p: &Principal) -> Result<Vec<Vec<u8>>, String> {
     const SCHEMA: u8 = 1;
 
-    vec![vec![SCHEMA], p.as_slice().to_vec()]
+    Ok(vec![
+        vec![SCHEMA],
+        try_key_principal_of(p)?.as_slice().to_vec(),
+    ])
 }
 
 /// Converts the public key bytes to an Ethereum address with a checksum.
@@ -211,18 +496,34 @@ fn pubkey_bytes_to_address(pubkey_bytes: &[u8]) -> String {
 
 /// Computes the public key of the specified principal.
 async fn ecdsa_pubkey_of(principal: &Principal) -> Vec<u8> {
+    ecdsa_pubkey_at(principal_to_derivation_path(principal)).await
+}
+
+/// Computes the public key of the specified principal, returns the error instead of trapping.
+async fn try_ecdsa_pubkey_of(principal: &Principal) -> Result<Vec<u8>, String> {
+    try_ecdsa_pubkey_at(try_principal_to_derivation_path(principal)?).await
+}
+
+/// Computes the public key at the specified derivation path.
+async fn ecdsa_pubkey_at(derivation_path: Vec<Vec<u8>>) -> Vec<u8> {
+    try_ecdsa_pubkey_at(derivation_path)
+        .await
+        .unwrap_or_else(|e| ic_cdk::trap(&e))
+}
+
+async fn try_ecdsa_pubkey_at(derivation_path: Vec<Vec<u8>>) -> Result<Vec<u8>, String> {
     let name = read_config(|s| s.ecdsa_key_name.clone());
     let (key,) = ecdsa_public_key(EcdsaPublicKeyArgument {
         canister_id: None,
-        derivation_path: principal_to_derivation_path(principal),
+        derivation_path,
         key_id: EcdsaKeyId {
             curve: EcdsaCurve::Secp256k1,
             name,
         },
     })
     .await
-    .expect("failed to get public key");
-    key.public_key
+    .map_err(|(code, msg)| format!("failed to get public key: {code:?} {msg}"))?;
+    Ok(key.public_key)
 }
 
 fn parse_eth_address(address: &str) -> [u8; 20] {
@@ -249,11 +550,220 @@ async fn eth_address_of(p: Principal) -> String {
     pubkey_bytes_to_address(&ecdsa_pubkey_of(&p).await)
 }
 
+/// Returns the native and ERC-20 balances of the caller on the chain, fetched through the EVM RPC canister.
+#[update(guard = "caller_can_call_external")]
+async fn eth_balances(chain_id: ChainId) -> EthBalances {
+    assert_chain_enabled(chain_id);
+
+    let caller = ic_cdk::caller();
+    let address = pubkey_bytes_to_address(&ecdsa_pubkey_of(&caller).await);
+
+    balance::eth_balances(&caller, address, chain_id)
+        .await
+        .unwrap_or_else(|e| ic_cdk::trap(&e))
+}
+
+/// Returns the balances of the caller's enabled ICRC custom tokens and EVM tokens, with their decimals applied.
+#[update(guard = "caller_can_call_external")]
+async fn get_portfolio() -> Portfolio {
+    portfolio::portfolio(&ic_cdk::caller()).await
+}
+
+/// Returns the portfolio of a user for their daily snapshot.
+///
+/// Only the canister calls it, a trap fails the snapshot of the user only.
+#[update(guard = "caller_is_self")]
+async fn portfolio_snapshot_of(principal: Principal) -> Portfolio {
+    portfolio::portfolio(&principal).await
+}
+
+/// Opts the caller in to a daily snapshot of their portfolio.
+#[update(guard = "caller_is_not_anonymous")]
+fn enable_portfolio_history() {
+    portfolio_history::enable(&ic_cdk::caller(), ic_cdk::api::time());
+}
+
+/// Opts the caller out of the daily snapshots and deletes their history.
+#[update(guard = "caller_is_not_anonymous")]
+fn disable_portfolio_history() {
+    portfolio_history::disable(&ic_cdk::caller());
+}
+
+#[query(guard = "caller_is_not_anonymous")]
+fn is_portfolio_history_enabled() -> bool {
+    portfolio_history::is_enabled(&ic_cdk::caller())
+}
+
+/// Returns up to `limit` snapshots of the caller's portfolio taken between the two timestamps, in nanoseconds, oldest
+/// first.
+#[query(guard = "caller_is_not_anonymous")]
+fn get_portfolio_history(from: u64, to: u64, limit: u64) -> Vec<PortfolioSnapshot> {
+    portfolio_history::history(&ic_cdk::caller(), from, to, limit)
+}
+
+/// Polls the indexes of a user for their notifications.
+///
+/// Only the canister calls it, a trap fails the poll of the user only.
+#[update(guard = "caller_is_self")]
+async fn poll_notifications_of(principal: Principal) {
+    notification::poll(principal).await;
+}
+
+/// Opts the caller in to the notifications of the transfers they receive on the indexed ICRC custom tokens.
+#[update(guard = "caller_is_not_anonymous")]
+fn enable_notifications() {
+    notification::enable(&ic_cdk::caller(), ic_cdk::api::time());
+}
+
+/// Opts the caller out of the notifications and deletes them.
+#[update(guard = "caller_is_not_anonymous")]
+fn disable_notifications() {
+    notification::disable(&ic_cdk::caller());
+}
+
+#[query(guard = "caller_is_not_anonymous")]
+fn is_notifications_enabled() -> bool {
+    notification::is_enabled(&ic_cdk::caller())
+}
+
+/// Returns a page of the notifications of the caller, most recent first.
+#[query(guard = "caller_is_not_anonymous")]
+fn list_notifications(cursor: Option<NotificationId>) -> NotificationPage {
+    notification::list(&ic_cdk::caller(), cursor)
+}
+
+#[update(guard = "caller_is_not_anonymous")]
+fn mark_notifications_read(ids: Vec<NotificationId>) {
+    notification::mark_read(&ic_cdk::caller(), ids);
+}
+
+/// Returns a page of the caller's transactions on the indexed ICRC custom tokens, most recent first.
+#[update(guard = "caller_can_call_external")]
+async fn get_transaction_history(
+    cursor: Option<TransactionHistoryCursor>,
+    limit: u64,
+) -> TransactionHistoryPage {
+    transaction_history::history(&ic_cdk::caller(), cursor, limit).await
+}
+
+/// Returns low, medium and high fees for the chain, computed from the priority fees of the last blocks.
+#[update(guard = "caller_can_call_external")]
+async fn suggest_fees(chain_id: ChainId) -> FeeSuggestion {
+    assert_chain_enabled(chain_id);
+
+    fees::suggest_fees(chain_id, ic_cdk::api::time()).await
+}
+
+/// Returns the Ethereum address an ENS name resolves to, if any.
+#[update(guard = "caller_can_call_external")]
+async fn resolve_ens(name: String) -> Option<String> {
+    ens::resolve(&name, ic_cdk::api::time()).await
+}
+
+/// Returns the primary ENS name of an Ethereum address, if it resolves back to the address.
+#[update(guard = "caller_can_call_external")]
+async fn reverse_ens(address: String) -> Option<String> {
+    ens::reverse(&address, ic_cdk::api::time()).await
+}
+
+/// Returns the transaction depositing ETH to the ckETH helper contract, minting ckETH to the caller.
+#[update(guard = "caller_can_call_external")]
+async fn cketh_deposit_request(args: CkEthDepositArgs) -> SignRequest {
+    cketh::deposit_request(&ic_cdk::caller(), args).await
+}
+
+/// Tracks a ckETH deposit or withdrawal of the caller, see `conversion_status`.
+#[update(guard = "caller_is_not_anonymous")]
+fn track_cketh_conversion(conversion: CkEthConversion) {
+    cketh::track(&ic_cdk::caller(), conversion, ic_cdk::api::time());
+}
+
+/// Returns the state of the ckETH deposits and withdrawals tracked by the caller, most recent first.
+#[update(guard = "caller_can_call_external")]
+async fn conversion_status() -> Vec<ConversionStatus> {
+    cketh::conversion_status(&ic_cdk::caller()).await
+}
+
+/// Encodes an account in the ICRC-1 textual format.
+#[query]
+fn encode_icrc_account(account: IcrcAccount) -> String {
+    shared::types::account::encode_icrc_account(&account)
+        .unwrap_or_else(|err| ic_cdk::trap(&format!("Invalid ICRC-1 account: {err}")))
+}
+
+/// Decodes an account in the ICRC-1 textual format, traps if it is invalid or not canonical.
+#[query]
+fn decode_icrc_account(text: String) -> IcrcAccount {
+    shared::types::account::decode_icrc_account(&text)
+        .unwrap_or_else(|err| ic_cdk::trap(&format!("Invalid ICRC-1 account {text}: {err}")))
+}
+
+/// Whether the text is a valid ICRC-1 textual account.
+#[query]
+fn is_valid_icrc_account(text: String) -> bool {
+    shared::types::account::decode_icrc_account(&text).is_ok()
+}
+
+/// Returns the account identifier of an account on the ICP ledger.
+#[query]
+fn icp_account_identifier(account: IcrcAccount) -> String {
+    shared::types::account::icp_account_identifier(&account)
+        .unwrap_or_else(|err| ic_cdk::trap(&format!("Invalid ICRC-1 account: {err}")))
+}
+
+/// Whether the text is a valid account identifier of the ICP ledger.
+#[query]
+fn is_valid_icp_account_identifier(text: String) -> bool {
+    shared::types::account::is_valid_icp_account_identifier(&text)
+}
+
+/// Returns the Solana address of the caller.
+#[update(guard = "caller_is_not_anonymous")]
+async fn caller_sol_address() -> String {
+    solana::pubkey_to_address(&solana::sol_pubkey_of(&ic_cdk::caller()).await)
+}
+
+/// Computes the Ed25519 signature of a base58-encoded, serialized legacy or v0 Solana message.
+///
+/// Returns the base58-encoded signature.
+#[update(guard = "caller_can_sign")]
+async fn sign_solana_message(message: String) -> String {
+    assert_not_in_allowlist_mode(&ic_cdk::caller(), "Signing a Solana message");
+
+    let message = bs58::decode(&message)
+        .into_vec()
+        .unwrap_or_else(|err| ic_cdk::trap(&format!("failed to decode base58: {err}")));
+
+    let signature = solana::sign_message(&ic_cdk::caller(), message).await;
+
+    bs58::encode(signature).into_string()
+}
+
+/// Returns the Taproot address of the caller, a key path only P2TR output.
+#[update(guard = "caller_is_not_anonymous")]
+async fn caller_btc_taproot_address(network: BitcoinNetwork) -> String {
+    taproot::taproot_address_of(&ic_cdk::caller(), network).await
+}
+
+/// Computes the BIP-341 key path signatures of the inputs of a hex-encoded PSBT that spend the caller's Taproot output.
+#[update(guard = "caller_can_sign")]
+async fn sign_btc_taproot_psbt(psbt: String) -> Vec<TaprootSignature> {
+    assert_not_in_allowlist_mode(&ic_cdk::caller(), "Signing a Bitcoin transaction");
+
+    taproot::sign_psbt(&ic_cdk::caller(), &decode_hex(&psbt)).await
+}
+
 fn nat_to_u256(n: &Nat) -> U256 {
     let be_bytes = n.0.to_bytes_be();
     U256::from_big_endian(&be_bytes)
 }
 
+fn u256_to_nat(n: U256) -> Nat {
+    n.to_string()
+        .parse()
+        .expect("a decimal number should be a valid Nat")
+}
+
 fn nat_to_u64(n: &Nat) -> U64 {
     let be_bytes = n.0.to_bytes_be();
     U64::from_big_endian(&be_bytes)
@@ -261,34 +771,194 @@ fn nat_to_u64(n: &Nat) -> U64 {
 
 /// Returns the public key and a message signature for the specified principal.
 async fn pubkey_and_signature(caller: &Principal, message_hash: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
+    pubkey_and_signature_at(caller, principal_to_derivation_path(caller), message_hash).await
+}
+
+/// Returns the public key and a message signature at the derivation path, the fee is charged to the caller.
+async fn pubkey_and_signature_at(
+    caller: &Principal,
+    derivation_path: Vec<Vec<u8>>,
+    message_hash: Vec<u8>,
+) -> (Vec<u8>, Vec<u8>) {
     // Fetch the pubkey and the signature concurrently to reduce latency.
     let (pubkey, response) = futures::join!(
-        ecdsa_pubkey_of(caller),
+        ecdsa_pubkey_at(derivation_path.clone()),
         sign_with_ecdsa(SignWithEcdsaArgument {
             message_hash,
-            derivation_path: principal_to_derivation_path(caller),
+            derivation_path,
             key_id: EcdsaKeyId {
                 curve: EcdsaCurve::Secp256k1,
                 name: read_config(|s| s.ecdsa_key_name.clone()),
             },
         })
     );
-    (
-        pubkey,
-        response.expect("failed to sign the message").0.signature,
-    )
+    let signature = response.expect("failed to sign the message").0.signature;
+
+    record_signature(StoredPrincipal(*caller), SIGN_WITH_ECDSA_FEE);
+
+    (pubkey, signature)
 }
 
 /// Computes a signature for an [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) transaction.
-#[update(guard = "caller_is_not_anonymous")]
+#[update(guard = "caller_can_sign")]
 async fn sign_transaction(req: SignRequest) -> String {
+    let caller = ic_cdk::caller();
+
+    assert_sign_request_allowed(&caller, &req);
+    nonce::assert_available(&key_principal_of(&caller), &req);
+
+    let raw_transaction =
+        sign_eip1559_transaction(&caller, principal_to_derivation_path(&caller), &req).await;
+    nonce::track(&key_principal_of(&caller), &req);
+
+    raw_transaction
+}
+
+/// Signs an [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) transaction and broadcasts it through the EVM RPC canister.
+///
+/// Returns the transaction hash and the result of each RPC provider.
+#[update(guard = "caller_can_sign")]
+async fn sign_and_send_transaction(req: SignRequest) -> SendTransactionResponse {
+    let caller = ic_cdk::caller();
+
+    let canister_id = evm_rpc::evm_rpc_canister_id();
+    let chain_id = nat_to_u64(&req.chain_id).as_u64();
+    // Checks the chain is supported before signing.
+    evm_rpc::rpc_services(chain_id);
+
+    assert_sign_request_allowed(&caller, &req);
+    nonce::assert_available(&key_principal_of(&caller), &req);
+
+    let raw_transaction =
+        sign_eip1559_transaction(&caller, principal_to_derivation_path(&caller), &req).await;
+    nonce::track(&key_principal_of(&caller), &req);
+
+    evm_rpc::send_raw_transaction(canister_id, chain_id, raw_transaction).await
+}
+
+/// Returns the nonce following the highest nonce the canister signed for the caller on the chain, if any.
+#[query(guard = "caller_is_not_anonymous")]
+fn next_nonce_hint(chain_id: ChainId) -> Option<u64> {
+    nonce::next_nonce_hint(&key_principal_of(&ic_cdk::caller()), chain_id)
+}
+
+/// Queues an [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) transaction that is signed once its release time has come.
+#[update(guard = "caller_can_sign")]
+fn schedule_transaction(request: ScheduleTransactionRequest) -> ScheduledTransactionId {
+    let caller = ic_cdk::caller();
+
+    assert_sign_request_allowed(&caller, &request.request);
+
+    scheduled_transaction::schedule(&caller, request, ic_cdk::api::time())
+}
+
+/// Signs a released scheduled transaction of the principal, checking it again since the settings of the principal
+/// may have changed in the meantime.
+///
+/// Only the canister calls it, the scheduled transaction fails with the error if it traps.
+#[update(guard = "caller_is_self")]
+async fn sign_scheduled_transaction(principal: Principal, request: SignRequest) -> String {
+    assert_sign_request_allowed(&principal, &request);
+    nonce::assert_available(&key_principal_of(&principal), &request);
+
+    let raw_transaction = sign_eip1559_transaction(
+        &principal,
+        principal_to_derivation_path(&principal),
+        &request,
+    )
+    .await;
+    nonce::track(&key_principal_of(&principal), &request);
+
+    raw_transaction
+}
+
+/// Cancels a scheduled transaction before its release.
+#[update(guard = "caller_is_not_anonymous")]
+fn cancel_scheduled_transaction(id: ScheduledTransactionId) {
+    scheduled_transaction::cancel(&ic_cdk::caller(), id);
+}
+
+#[query(guard = "caller_is_not_anonymous")]
+fn list_scheduled_transactions() -> Vec<ScheduledTransaction> {
+    scheduled_transaction::list(&ic_cdk::caller())
+}
+
+/// Returns the signed raw transaction of a scheduled transaction once it has been released.
+#[query(guard = "caller_is_not_anonymous")]
+fn get_signed_transaction(id: ScheduledTransactionId) -> Option<String> {
+    scheduled_transaction::signed_transaction(&ic_cdk::caller(), id)
+}
+
+/// Creates an account whose transactions are signed once enough of its members approved them.
+#[update(guard = "caller_is_not_anonymous")]
+fn create_shared_account(request: CreateSharedAccountRequest) -> SharedAccount {
+    shared_account::create(&ic_cdk::caller(), request, ic_cdk::api::time())
+}
+
+/// Returns the shared accounts the caller is a member of.
+#[query(guard = "caller_is_not_anonymous")]
+fn list_shared_accounts() -> Vec<SharedAccount> {
+    shared_account::list(&ic_cdk::caller())
+}
+
+/// Returns the Ethereum address of a shared account of the caller.
+#[update(guard = "caller_is_not_anonymous")]
+async fn shared_account_eth_address(account_id: SharedAccountId) -> String {
+    shared_account::address(&ic_cdk::caller(), account_id).await
+}
+
+/// Proposes a transaction of a shared account, approved by the caller.
+#[update(guard = "caller_can_sign")]
+async fn propose_transaction(request: ProposeTransactionRequest) -> ProposalId {
+    shared_account::propose(&ic_cdk::caller(), request, ic_cdk::api::time()).await
+}
+
+/// Approves a proposal, the transaction is signed once the threshold of the account is reached.
+#[update(guard = "caller_can_sign")]
+async fn approve_proposal(proposal: ProposalRef) -> Proposal {
+    shared_account::approve(&ic_cdk::caller(), proposal, ic_cdk::api::time()).await
+}
+
+#[update(guard = "caller_is_not_anonymous")]
+fn cancel_proposal(proposal: ProposalRef) {
+    shared_account::cancel(&ic_cdk::caller(), proposal);
+}
+
+/// Returns a page of the proposals of a shared account of the caller, with their approvals.
+#[query(guard = "caller_is_not_anonymous")]
+fn list_proposals(account_id: SharedAccountId, cursor: Option<ProposalId>) -> ProposalPage {
+    shared_account::list_proposals(&ic_cdk::caller(), account_id, cursor)
+}
+
+/// Removes the caller from the members of a shared account.
+#[update(guard = "caller_is_not_anonymous")]
+fn leave_shared_account(account_id: SharedAccountId) {
+    shared_account::leave(&ic_cdk::caller(), account_id);
+}
+
+/// Checks the chain, the approvals and the recipient of the transaction, otherwise traps.
+fn assert_sign_request_allowed(principal: &Principal, req: &SignRequest) {
+    assert_transaction_allowed(&req.chain_id, TransactionType::Eip1559, &req.gas);
+
+    let data = req.data.as_ref().map(|s| decode_hex(s));
+
+    if let Some(data) = data.as_ref() {
+        assert_approval_allowed(nat_to_u64(&req.chain_id).as_u64(), data);
+    }
+    assert_recipient_allowed(principal, &req.to, &req.value, data.as_deref());
+}
+
+/// Signs the transaction with the key at the derivation path and returns the hex-encoded raw transaction.
+async fn sign_eip1559_transaction(
+    caller: &Principal,
+    derivation_path: Vec<Vec<u8>>,
+    req: &SignRequest,
+) -> String {
     use ethers_core::types::transaction::eip1559::Eip1559TransactionRequest;
     use ethers_core::types::Signature;
 
     const EIP1559_TX_ID: u8 = 2;
 
-    let caller = ic_cdk::caller();
-
     let data = req.data.as_ref().map(|s| decode_hex(s));
 
     let tx = Eip1559TransactionRequest {
@@ -313,7 +983,8 @@ async fn sign_transaction(req: SignRequest) -> String {
 
     let txhash = keccak256(&unsigned_tx_bytes);
 
-    let (pubkey, signature) = pubkey_and_signature(&caller, txhash.to_vec()).await;
+    let (pubkey, signature) =
+        pubkey_and_signature_at(caller, derivation_path, txhash.to_vec()).await;
 
     let signature = Signature {
         v: y_parity(&txhash, &signature, &pubkey),
@@ -328,10 +999,12 @@ async fn sign_transaction(req: SignRequest) -> String {
 }
 
 /// Computes a signature for a hex-encoded message according to [EIP-191](https://eips.ethereum.org/EIPS/eip-191).
-#[update(guard = "caller_is_not_anonymous")]
+#[update(guard = "caller_can_sign")]
 async fn personal_sign(plaintext: String) -> String {
     let caller = ic_cdk::caller();
 
+    assert_not_in_allowlist_mode(&caller, "Signing a message");
+
     let bytes = decode_hex(&plaintext);
 
     let message = [
@@ -351,10 +1024,12 @@ async fn personal_sign(plaintext: String) -> String {
 }
 
 /// Computes a signature for a precomputed hash.
-#[update(guard = "caller_is_not_anonymous")]
+#[update(guard = "caller_can_sign")]
 async fn sign_prehash(prehash: String) -> String {
     let caller = ic_cdk::caller();
 
+    assert_not_in_allowlist_mode(&caller, "Signing a precomputed hash");
+
     let hash_bytes = decode_hex(&prehash);
 
     let (pubkey, mut signature) = pubkey_and_signature(&caller, hash_bytes.to_vec()).await;
@@ -367,8 +1042,15 @@ async fn sign_prehash(prehash: String) -> String {
 /// Adds a new token to the user.
 #[update(guard = "caller_is_not_anonymous")]
 fn add_user_token(token: UserToken) {
+    assert_chain_enabled(token.chain_id);
+
     let addr = parse_eth_address(&token.contract_address);
 
+    assert_not_denylisted(&DenylistedId::Evm {
+        chain_id: token.chain_id,
+        address: token.contract_address.clone(),
+    });
+
     if let Some(symbol) = token.symbol.as_ref() {
         if symbol.len() > MAX_SYMBOL_LENGTH {
             ic_cdk::trap(&format!(
@@ -398,14 +1080,35 @@ fn remove_user_token(token_id: UserTokenId) {
 }
 
 #[query(guard = "caller_is_not_anonymous")]
-fn list_user_tokens() -> Vec<UserToken> {
+fn list_user_tokens() -> Vec<ListedUserToken> {
     let stored_principal = StoredPrincipal(ic_cdk::caller());
-    read_state(|s| s.user_token.get(&stored_principal).unwrap_or_default().0)
+    let tokens = read_state(|s| s.user_token.get(&stored_principal).unwrap_or_default().0);
+
+    tokens
+        .into_iter()
+        .map(|token| {
+            let id = DenylistedId::Evm {
+                chain_id: token.chain_id,
+                address: token.contract_address.clone(),
+            };
+            ListedUserToken {
+                denylisted: is_denylisted(&id).then_some(true),
+                contract_address: token.contract_address,
+                chain_id: token.chain_id,
+                symbol: token.symbol,
+                decimals: token.decimals,
+                version: token.version,
+            }
+        })
+        .collect()
 }
 
 /// Add, remove or update custom token for the user.
-#[update(guard = "caller_is_not_anonymous")]
-fn set_custom_token(token: CustomToken) {
+#[update(guard = "caller_can_call_external")]
+async fn set_custom_token(token: CustomToken) {
+    assert_not_denylisted(&custom_token_denylisted_id(&token.token));
+    assert_valid_custom_tokens(std::slice::from_ref(&token)).await;
+
     let stored_principal = StoredPrincipal(ic_cdk::caller());
 
     let find = |t: &CustomToken| -> bool {
@@ -415,8 +1118,13 @@ fn set_custom_token(token: CustomToken) {
     mutate_state(|s| add_to_user_token(stored_principal, &mut s.custom_token, &token, &find));
 }
 
-#[update(guard = "caller_is_not_anonymous")]
-fn set_many_custom_tokens(tokens: Vec<CustomToken>) {
+#[update(guard = "caller_can_call_external")]
+async fn set_many_custom_tokens(tokens: Vec<CustomToken>) {
+    for token in tokens.iter() {
+        assert_not_denylisted(&custom_token_denylisted_id(&token.token));
+    }
+    assert_valid_custom_tokens(&tokens).await;
+
     let stored_principal = StoredPrincipal(ic_cdk::caller());
 
     mutate_state(|s| {
@@ -431,9 +1139,259 @@ fn set_many_custom_tokens(tokens: Vec<CustomToken>) {
 }
 
 #[query(guard = "caller_is_not_anonymous")]
-fn list_custom_tokens() -> Vec<CustomToken> {
+fn list_custom_tokens() -> Vec<ListedCustomToken> {
+    let stored_principal = StoredPrincipal(ic_cdk::caller());
+    let tokens = read_state(|s| s.custom_token.get(&stored_principal).unwrap_or_default().0);
+
+    tokens
+        .into_iter()
+        .map(|token| ListedCustomToken {
+            denylisted: is_denylisted(&custom_token_denylisted_id(&token.token)).then_some(true),
+            token: token.token,
+            enabled: token.enabled,
+            version: token.version,
+        })
+        .collect()
+}
+
+/// Returns the metadata of an ICRC ledger, if it was validated when a custom token was added.
+#[query(guard = "caller_is_not_anonymous")]
+fn get_icrc_token_metadata(ledger_id: LedgerId) -> Option<IcrcTokenMetadata> {
+    cached_metadata(&ledger_id)
+}
+
+/// Add or update a contact of the user's address book.
+#[update(guard = "caller_is_not_anonymous")]
+fn set_contact(contact: Contact) {
+    assert_valid_contact(&contact);
+
     let stored_principal = StoredPrincipal(ic_cdk::caller());
-    read_state(|s| s.custom_token.get(&stored_principal).unwrap_or_default().0)
+
+    mutate_state(|s| contact::set_contact(stored_principal, &mut s.contact, &contact));
+}
+
+#[update(guard = "caller_is_not_anonymous")]
+fn remove_contact(label: String) {
+    let stored_principal = StoredPrincipal(ic_cdk::caller());
+
+    let find = |c: &Contact| c.label == label;
+
+    mutate_state(|s| remove_from_user_token(stored_principal, &mut s.contact, &find));
+}
+
+#[query(guard = "caller_is_not_anonymous")]
+fn list_contacts() -> Vec<Contact> {
+    let stored_principal = StoredPrincipal(ic_cdk::caller());
+    read_state(|s| s.contact.get(&stored_principal).unwrap_or_default().0)
+}
+
+#[query(guard = "caller_is_not_anonymous")]
+fn get_user_profile() -> Option<UserProfile> {
+    let stored_principal = StoredPrincipal(ic_cdk::caller());
+    read_state(|s| s.user_profile.get(&stored_principal).map(|p| p.0))
+}
+
+/// Replaces the settings of the user, provided the version matches the stored profile.
+#[update(guard = "caller_is_not_anonymous")]
+fn update_user_settings(request: UpdateUserSettingsRequest) -> UserProfile {
+    assert_valid_settings(&request.settings);
+
+    let stored_principal = StoredPrincipal(ic_cdk::caller());
+    let now = ic_cdk::api::time();
+
+    mutate_state(|s| set_user_settings(stored_principal, &mut s.user_profile, request, now))
+}
+
+/// Returns the enabled chains the backend signs transactions for.
+#[query]
+fn list_supported_chains() -> Vec<ChainConfig> {
+    chain_registry().into_iter().filter(|c| c.enabled).collect()
+}
+
+/// Adds a chain to the registry or replaces the configuration of an existing one.
+#[update(guard = "caller_is_controller")]
+fn set_chain(chain: ChainConfig) {
+    if chain.native_symbol.len() > MAX_SYMBOL_LENGTH {
+        ic_cdk::trap(&format!(
+            "Native symbol should not exceed {MAX_SYMBOL_LENGTH} bytes",
+        ));
+    }
+
+    mutate_config(|c| {
+        let mut chains = c.chains.take().unwrap_or_else(chain::default_chains);
+        match chains.iter().position(|t| t.chain_id == chain.chain_id) {
+            Some(p) => chains[p] = chain,
+            None => chains.push(chain),
+        }
+        c.chains = Some(chains);
+    });
+}
+
+/// Sets the rate limit applied to the signing calls of each principal.
+#[update(guard = "caller_is_controller")]
+fn set_signing_rate_limit(rate_limit: RateLimitConfig) {
+    if rate_limit.capacity == 0 {
+        ic_cdk::trap("Rate limit capacity should be greater than 0");
+    }
+
+    mutate_config(|c| c.signing_rate_limit = Some(rate_limit));
+}
+
+/// Sets the rate limit applied to the calls of each principal that call other canisters or the RPC providers.
+#[update(guard = "caller_is_controller")]
+fn set_external_call_rate_limit(rate_limit: RateLimitConfig) {
+    if rate_limit.capacity == 0 {
+        ic_cdk::trap("Rate limit capacity should be greater than 0");
+    }
+
+    mutate_config(|c| c.external_call_rate_limit = Some(rate_limit));
+}
+
+/// Enables or disables the validation of the custom tokens against their ledger and index canisters.
+#[update(guard = "caller_is_controller")]
+fn set_custom_token_validation(enabled: bool) {
+    mutate_config(|c| c.validate_custom_tokens = Some(enabled));
+}
+
+/// Sets the EVM RPC canister broadcasting the transactions, or disables broadcasting.
+#[update(guard = "caller_is_controller")]
+fn set_evm_rpc_canister(canister_id: Option<Principal>) {
+    mutate_config(|c| c.evm_rpc_canister_id = canister_id);
+}
+
+/// Sets the ckETH minter and its chain, or disables the ckETH conversions.
+#[update(guard = "caller_is_controller")]
+fn set_cketh_minter(minter: Option<CkEthMinter>) {
+    mutate_config(|c| c.cketh_minter = minter);
+}
+
+/// Adds a token to the catalogue or updates it, the version should match the stored one.
+#[update(guard = "caller_is_controller")]
+fn set_catalogue_token(token: CatalogueToken) {
+    catalogue::set_catalogue_token(token);
+}
+
+#[update(guard = "caller_is_controller")]
+fn remove_catalogue_token(id: CatalogueTokenId) {
+    catalogue::remove_catalogue_token(&id);
+}
+
+/// Stores a logo of the catalogue and returns the hash the tokens refer to it by.
+#[update(guard = "caller_is_controller")]
+fn set_catalogue_logo(logo: String) -> String {
+    catalogue::set_catalogue_logo(logo)
+}
+
+#[query]
+fn get_catalogue_logo(hash: String) -> Option<String> {
+    catalogue::get_catalogue_logo(hash)
+}
+
+/// Returns a page of the catalogue tokens enabled on the network.
+#[query]
+fn list_catalogue_tokens(network: Network, cursor: Option<String>) -> CatalogueTokenPage {
+    catalogue::list_catalogue_tokens(network, cursor)
+}
+
+/// Adds a catalogue token to the token list of the caller, or updates the symbol and name overriding the catalogue ones.
+#[update(guard = "caller_is_not_anonymous")]
+fn set_user_catalogue_token(token: UserCatalogueToken) {
+    catalogue::set_user_catalogue_token(StoredPrincipal(ic_cdk::caller()), token);
+}
+
+#[update(guard = "caller_is_not_anonymous")]
+fn remove_user_catalogue_token(id: CatalogueTokenId) {
+    catalogue::remove_user_catalogue_token(StoredPrincipal(ic_cdk::caller()), &id);
+}
+
+/// Lists the catalogue tokens of the caller, the overrides applied to the current catalogue entries.
+#[query(guard = "caller_is_not_anonymous")]
+fn list_user_catalogue_tokens() -> Vec<ListedUserCatalogueToken> {
+    catalogue::list_user_catalogue_tokens(StoredPrincipal(ic_cdk::caller()))
+}
+
+/// Blocks a scam or phishing token, or the spender of malicious approvals.
+#[update(guard = "caller_is_controller")]
+fn set_denylist_entry(entry: DenylistEntry) {
+    denylist::set_denylist_entry(entry);
+}
+
+#[update(guard = "caller_is_controller")]
+fn remove_denylist_entry(id: DenylistedId) {
+    denylist::remove_denylist_entry(&id);
+}
+
+#[query]
+fn list_denylist() -> Vec<DenylistEntry> {
+    denylist::list_denylist()
+}
+
+#[query(guard = "caller_is_not_anonymous")]
+fn get_allowlist() -> Allowlist {
+    allowlist::get_allowlist(&ic_cdk::caller(), ic_cdk::api::time())
+}
+
+/// Restricts the transactions signed for the caller to the allowlisted recipients.
+#[update(guard = "caller_is_not_anonymous")]
+fn enable_allowlist_mode() {
+    allowlist::enable(&ic_cdk::caller(), ic_cdk::api::time());
+}
+
+/// Lifts the restriction of the allowlist mode after the confirmation period.
+#[update(guard = "caller_is_not_anonymous")]
+fn disable_allowlist_mode() {
+    allowlist::request_disable(&ic_cdk::caller(), ic_cdk::api::time());
+}
+
+/// Allowlists an Ethereum address, after the confirmation period if the allowlist mode is on.
+#[update(guard = "caller_is_not_anonymous")]
+fn add_allowlist_address(address: String) {
+    allowlist::add_address(&ic_cdk::caller(), &address, ic_cdk::api::time());
+}
+
+#[update(guard = "caller_is_not_anonymous")]
+fn remove_allowlist_address(address: String) {
+    allowlist::remove_address(&ic_cdk::caller(), &address, ic_cdk::api::time());
+}
+
+/// Sets the guardians who can recover the keys of the caller.
+#[update(guard = "caller_is_not_anonymous")]
+fn set_guardians(config: GuardianConfig) {
+    recovery::set_guardians(&ic_cdk::caller(), config);
+}
+
+#[query(guard = "caller_is_not_anonymous")]
+fn get_guardians() -> Option<GuardianConfig> {
+    recovery::get_guardians(&ic_cdk::caller())
+}
+
+/// Approves, as a guardian, the recovery of the keys of a user by a new principal.
+#[update(guard = "caller_is_not_anonymous")]
+fn approve_recovery(request: ApproveRecoveryRequest) {
+    recovery::approve(&ic_cdk::caller(), request, ic_cdk::api::time());
+}
+
+/// Cancels the recovery of the keys of the caller.
+#[update(guard = "caller_is_not_anonymous")]
+fn cancel_recovery() {
+    recovery::cancel(&ic_cdk::caller());
+}
+
+/// Takes control of the keys and the state of the user once the recovery time lock has elapsed.
+#[update(guard = "caller_is_not_anonymous")]
+fn complete_recovery(user: Principal) {
+    recovery::complete(&ic_cdk::caller(), &user, ic_cdk::api::time());
+}
+
+#[query(guard = "caller_is_not_anonymous")]
+fn get_recovery_request(user: Principal) -> Option<RecoveryRequest> {
+    recovery::get_recovery_request(&ic_cdk::caller(), &user)
+}
+
+/// Returns the principals that spent the most cycles, in descending order.
+#[query(guard = "caller_is_controller")]
+fn top_cycle_consumers(limit: u32) -> Vec<CyclesConsumer> {
+    top_consumers(limit as usize)
 }
 
 /// API method to get cycle balance and burn rate.
@@ -0,0 +1,111 @@
+use crate::{decode_hex, mutate_state, nat_to_u256, nat_to_u64, parse_eth_address, read_state};
+use crate::{Candid, StoredPrincipal};
+use candid::{CandidType, Deserialize, Principal};
+use ethers_core::utils::keccak256;
+use shared::types::token::ChainId;
+use shared::types::transaction::SignRequest;
+
+/// The number of nonces whose content is kept per chain, the highest ones.
+const MAX_TRACKED_NONCES: usize = 32;
+
+/// A nonce signed for a principal and the hash of the content of its transaction.
+#[derive(CandidType, Deserialize, Clone)]
+pub struct SignedNonce {
+    pub nonce: u64,
+    pub content_hash: [u8; 32],
+}
+
+/// Hashes the recipient, the value and the data of the transaction.
+///
+/// The fees are left out, so that a stuck transaction can be replaced by the same one with higher fees.
+fn content_hash(req: &SignRequest) -> [u8; 32] {
+    let mut value = [0u8; 32];
+    nat_to_u256(&req.value).to_big_endian(&mut value);
+
+    let mut content = parse_eth_address(&req.to).to_vec();
+    content.extend_from_slice(&value);
+    if let Some(data) = req.data.as_ref() {
+        content.extend_from_slice(&decode_hex(data));
+    }
+    keccak256(content)
+}
+
+fn signed_nonces(principal: &Principal, chain_id: ChainId) -> Vec<SignedNonce> {
+    read_state(|s| {
+        s.signed_nonce
+            .get(&(StoredPrincipal(*principal), chain_id))
+            .map(|n| n.0)
+            .unwrap_or_default()
+    })
+}
+
+/// Returns the nonce of the transaction, traps if it exceeds the nonces of Ethereum (EIP-2681).
+fn nonce_of(req: &SignRequest) -> u64 {
+    u64::try_from(&req.nonce.0)
+        .unwrap_or_else(|_| ic_cdk::trap(&format!("Nonce should not exceed {}", u64::MAX)))
+}
+
+/// Returns the nonces signed with the one of the transaction inserted, `None` if it is already in.
+///
+/// Traps if the nonce was already signed with a different recipient, value or data, e.g. in another tab, or if it is
+/// below the tracked nonces, whose content is no longer known.
+fn with_nonce_of(principal: &Principal, req: &SignRequest) -> Option<Vec<SignedNonce>> {
+    let chain_id = nat_to_u64(&req.chain_id).as_u64();
+    let nonce = nonce_of(req);
+    let content_hash = content_hash(req);
+
+    let mut signed = signed_nonces(principal, chain_id);
+    if signed.len() == MAX_TRACKED_NONCES && signed.first().is_some_and(|n| nonce < n.nonce) {
+        ic_cdk::trap(&format!(
+            "Nonce {nonce} is below the nonces tracked on chain {chain_id}"
+        ));
+    }
+    match signed.binary_search_by_key(&nonce, |n| n.nonce) {
+        Ok(i) if signed[i].content_hash == content_hash => return None,
+        Ok(_) => ic_cdk::trap(&format!(
+            "Nonce {nonce} was already signed for another transaction on chain {chain_id}"
+        )),
+        Err(i) => signed.insert(
+            i,
+            SignedNonce {
+                nonce,
+                content_hash,
+            },
+        ),
+    }
+    if signed.len() > MAX_TRACKED_NONCES {
+        signed.remove(0);
+    }
+    Some(signed)
+}
+
+/// Checks the nonce of the transaction before it is signed, otherwise traps.
+pub fn assert_available(principal: &Principal, req: &SignRequest) {
+    with_nonce_of(principal, req);
+}
+
+/// Records the nonce of the transaction once it is signed.
+///
+/// The nonce is checked again, another transaction may have been signed with it while this one was. A failed
+/// signature records nothing.
+pub fn track(principal: &Principal, req: &SignRequest) {
+    let Some(signed) = with_nonce_of(principal, req) else {
+        return;
+    };
+    let chain_id = nat_to_u64(&req.chain_id).as_u64();
+
+    mutate_state(|s| {
+        s.signed_nonce
+            .insert((StoredPrincipal(*principal), chain_id), Candid(signed))
+    });
+}
+
+/// Returns the nonce following the highest nonce signed for the principal, if any.
+///
+/// It is a hint: the transactions signed by the canister may not have been sent, and other wallets may use the same keys.
+/// There is none after the highest nonce of Ethereum.
+pub fn next_nonce_hint(principal: &Principal, chain_id: ChainId) -> Option<u64> {
+    signed_nonces(principal, chain_id)
+        .last()
+        .and_then(|n| n.nonce.checked_add(1))
+}
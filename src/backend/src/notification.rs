@@ -0,0 +1,335 @@
+//! Notifications of the incoming transfers of the ICRC tokens of the users who opted in.
+//!
+//! The index canisters of the enabled custom tokens are polled periodically for the transactions of the users.
+use crate::catalogue::trusted_icrc_ledger;
+use crate::icrc_index::{get_account_transactions, is_default_account, TransactionWithId};
+use crate::{mutate_state, read_state, Candid, StoredPrincipal};
+use candid::{Nat, Principal};
+use futures::future::join_all;
+use shared::types::account::IcrcAccount;
+use shared::types::custom_token::{IndexId, LedgerId, Token};
+use shared::types::notification::{Notification, NotificationId, NotificationPage};
+use std::cell::RefCell;
+use std::ops::Bound;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// The users polled in one slice of a run, so that a slice stays within the instruction limits.
+const POLL_BATCH_SIZE: usize = 10;
+
+/// The users whose indexes are polled, each poll calls the index canisters of their tokens.
+const MAX_USERS: u64 = 10_000;
+
+/// The most recent transactions fetched per poll, older new transactions are not notified.
+const MAX_POLLED_TRANSACTIONS: u64 = 20;
+
+/// The notifications kept per user, the oldest ones are dropped first.
+const MAX_NOTIFICATIONS: usize = 100;
+
+/// A run that has not finished after this long is given up, e.g. if one of its slices trapped.
+const MAX_RUN_DURATION: Duration = Duration::from_secs(60 * 60);
+
+thread_local! {
+    // The start of the run in progress, which identifies it. It is kept on the heap: an upgrade stops the run.
+    static CURRENT_RUN: RefCell<Option<u64>> = RefCell::default();
+}
+
+const PAGE_SIZE: usize = 20;
+
+pub fn enable(principal: &Principal, now: u64) {
+    let key = StoredPrincipal(*principal);
+    mutate_state(|s| {
+        if !s.notification_user.contains_key(&key) && s.notification_user.len() >= MAX_USERS {
+            ic_cdk::trap(&format!("Notification users should not exceed {MAX_USERS}"));
+        }
+        s.notification_user.insert(key, now)
+    });
+}
+
+/// Opts the user out and deletes their notifications and the transactions seen for them.
+pub fn disable(principal: &Principal) {
+    let key = StoredPrincipal(*principal);
+    mutate_state(|s| {
+        s.notification_user.remove(&key);
+
+        let ids: Vec<NotificationId> = s
+            .notification
+            .range((key, 0)..=(key, NotificationId::MAX))
+            .map(|((_, id), _)| id)
+            .collect();
+        for id in ids {
+            s.notification.remove(&(key, id));
+        }
+
+        // The management canister is the smallest principal.
+        let ledger_ids: Vec<StoredPrincipal> = s
+            .last_seen_transaction
+            .range((key, StoredPrincipal(Principal::management_canister()))..)
+            .take_while(|((principal, _), _)| *principal == key)
+            .map(|((_, ledger_id), _)| ledger_id)
+            .collect();
+        for ledger_id in ledger_ids {
+            s.last_seen_transaction.remove(&(key, ledger_id));
+        }
+    });
+}
+
+pub fn is_enabled(principal: &Principal) -> bool {
+    read_state(|s| {
+        s.notification_user
+            .contains_key(&StoredPrincipal(*principal))
+    })
+}
+
+/// Returns the notifications of the principal, oldest first.
+fn notifications(principal: &Principal) -> Vec<Notification> {
+    let key = StoredPrincipal(*principal);
+    read_state(|s| {
+        s.notification
+            .range((key, 0)..=(key, NotificationId::MAX))
+            .map(|(_, notification)| notification.0)
+            .collect()
+    })
+}
+
+/// Lists the notifications of the principal, most recent first, starting before the cursor.
+pub fn list(principal: &Principal, cursor: Option<NotificationId>) -> NotificationPage {
+    // The inbox is bounded, it is read at once to list it backwards.
+    let mut remaining: Vec<Notification> = notifications(principal)
+        .into_iter()
+        .rev()
+        .skip_while(|n| cursor.map_or(false, |cursor| n.id >= cursor))
+        .collect();
+    let rest = remaining.split_off(remaining.len().min(PAGE_SIZE));
+
+    NotificationPage {
+        next_cursor: remaining.last().map(|n| n.id).filter(|_| !rest.is_empty()),
+        notifications: remaining,
+    }
+}
+
+/// Marks the notifications as read, the unknown ids are ignored.
+pub fn mark_read(principal: &Principal, ids: Vec<NotificationId>) {
+    let key = StoredPrincipal(*principal);
+    mutate_state(|s| {
+        for id in ids {
+            if let Some(Candid(notification)) = s.notification.get(&(key, id)) {
+                s.notification.insert(
+                    (key, id),
+                    Candid(Notification {
+                        read: true,
+                        ..notification
+                    }),
+                );
+            }
+        }
+    });
+}
+
+/// Returns the amount and sender of the transaction if the principal received it, `None` as sender for a mint.
+fn incoming(
+    principal: &Principal,
+    TransactionWithId { transaction, .. }: &TransactionWithId,
+) -> Option<(Nat, Option<IcrcAccount>)> {
+    if let Some(transfer) = transaction.transfer.as_ref() {
+        if is_default_account(&transfer.to, principal)
+            && !is_default_account(&transfer.from, principal)
+        {
+            return Some((transfer.amount.clone(), Some(transfer.from.clone())));
+        }
+    }
+    if let Some(mint) = transaction.mint.as_ref() {
+        if is_default_account(&mint.to, principal) {
+            return Some((mint.amount.clone(), None));
+        }
+    }
+    None
+}
+
+/// Returns the transactions of the principal on the ledger that are more recent than the last one seen.
+///
+/// Nothing is new on the first poll of a ledger: the transactions before the user opted in or added the token are
+/// not notified.
+async fn poll_ledger(
+    principal: Principal,
+    ledger_id: LedgerId,
+    index_id: IndexId,
+) -> Result<Vec<TransactionWithId>, String> {
+    let key = (StoredPrincipal(principal), StoredPrincipal(ledger_id));
+    let polled = read_state(|s| s.last_seen_transaction.get(&key)).map(|id| id.0);
+
+    let transactions =
+        get_account_transactions(index_id, &principal, None, MAX_POLLED_TRANSACTIONS)
+            .await?
+            .transactions;
+
+    // The user may have opted out while the index was polled, which deleted the transactions seen for them.
+    if !is_enabled(&principal) {
+        return Ok(vec![]);
+    }
+
+    let last_seen = polled.clone().flatten();
+    let latest = transactions
+        .first()
+        .map(|t| t.id.clone())
+        .or(last_seen.clone());
+    mutate_state(|s| s.last_seen_transaction.insert(key, Candid(latest)));
+
+    Ok(match polled {
+        None => vec![],
+        Some(_) => transactions
+            .into_iter()
+            .filter(|t| {
+                last_seen
+                    .as_ref()
+                    .map_or(true, |last_seen| &t.id > last_seen)
+            })
+            .collect(),
+    })
+}
+
+/// Adds the notifications to the inbox of the principal, dropping the oldest ones if it is full.
+fn notify(principal: &Principal, ledger_id: LedgerId, transactions: Vec<TransactionWithId>) {
+    let key = StoredPrincipal(*principal);
+
+    mutate_state(|s| {
+        // The transactions are most recent first, they are notified oldest first.
+        for transaction in transactions.iter().rev() {
+            let Some((amount, from)) = incoming(principal, transaction) else {
+                continue;
+            };
+            let id = s
+                .notification
+                .range((key, 0)..=(key, NotificationId::MAX))
+                .last()
+                .map_or(0, |((_, id), _)| id + 1);
+            s.notification.insert(
+                (key, id),
+                Candid(Notification {
+                    id,
+                    ledger_id,
+                    transaction_id: transaction.id.clone(),
+                    from,
+                    amount,
+                    timestamp: transaction.transaction.timestamp,
+                    read: false,
+                }),
+            );
+        }
+
+        let ids: Vec<NotificationId> = s
+            .notification
+            .range((key, 0)..=(key, NotificationId::MAX))
+            .map(|((_, id), _)| id)
+            .collect();
+        for id in ids.iter().take(ids.len().saturating_sub(MAX_NOTIFICATIONS)) {
+            s.notification.remove(&(key, *id));
+        }
+    });
+}
+
+/// Polls the indexes of the enabled custom tokens of the principal, a failing index is polled again next time.
+///
+/// Only the tokens of the catalogue are polled, through the index of their catalogue entry, see `trusted_icrc_ledger`.
+pub async fn poll(principal: Principal) {
+    let tokens: Vec<(LedgerId, IndexId)> = read_state(|s| {
+        s.custom_token
+            .get(&StoredPrincipal(principal))
+            .unwrap_or_default()
+            .0
+    })
+    .into_iter()
+    .filter(|t| t.enabled)
+    .filter_map(|t| match t.token {
+        Token::Icrc(token) => trusted_icrc_ledger(&token.ledger_id)
+            .ok()
+            .and_then(|catalogue_token| catalogue_token.index_id)
+            .map(|index_id| (token.ledger_id, index_id)),
+    })
+    .collect();
+
+    let results = join_all(
+        tokens
+            .iter()
+            .map(|(ledger_id, index_id)| poll_ledger(principal, *ledger_id, *index_id)),
+    )
+    .await;
+
+    for ((ledger_id, _), result) in tokens.into_iter().zip(results) {
+        if let Ok(transactions) = result {
+            // The user may have opted out while the indexes were polled.
+            if is_enabled(&principal) {
+                notify(&principal, ledger_id, transactions);
+            }
+        }
+    }
+}
+
+/// Starts a run unless another one is in progress, returns its id.
+fn start_run(now: u64) -> Option<u64> {
+    CURRENT_RUN.with(|run| {
+        let mut run = run.borrow_mut();
+        if run.is_some_and(|started| {
+            Duration::from_nanos(now.saturating_sub(started)) < MAX_RUN_DURATION
+        }) {
+            return None;
+        }
+        *run = Some(now);
+        *run
+    })
+}
+
+fn is_current_run(run: u64) -> bool {
+    CURRENT_RUN.with(|current| *current.borrow() == Some(run))
+}
+
+/// Polls the next batch of users, then schedules the following batch.
+///
+/// The indexes of each user are polled by a call of the canister to itself, so that a trap skips the poll of that
+/// user only. A run given up for a newer one stops at its next batch.
+async fn poll_users(run: u64, after: Option<Principal>) {
+    if !is_current_run(run) {
+        return;
+    }
+
+    let start = after.map_or(Bound::Unbounded, |p| Bound::Excluded(StoredPrincipal(p)));
+    let batch: Vec<Principal> = read_state(|s| {
+        s.notification_user
+            .range((start, Bound::Unbounded))
+            .take(POLL_BATCH_SIZE)
+            .map(|(principal, _)| principal.0)
+            .collect()
+    });
+
+    join_all(batch.iter().map(|principal| {
+        ic_cdk::call::<_, ()>(ic_cdk::id(), "poll_notifications_of", (*principal,))
+    }))
+    .await;
+
+    // A full batch may be followed by more users.
+    match batch
+        .last()
+        .copied()
+        .filter(|_| batch.len() == POLL_BATCH_SIZE)
+    {
+        Some(last) => {
+            ic_cdk_timers::set_timer(Duration::ZERO, move || {
+                ic_cdk::spawn(poll_users(run, Some(last)))
+            });
+        }
+        None if is_current_run(run) => CURRENT_RUN.with(|current| *current.borrow_mut() = None),
+        None => (),
+    }
+}
+
+/// Sets the timer of the polls, the timers do not survive an upgrade.
+///
+/// A tick is skipped while the run of the previous one is in progress, so that the runs do not pile up.
+pub fn schedule_polls() {
+    ic_cdk_timers::set_timer_interval(POLL_INTERVAL, || {
+        if let Some(run) = start_run(ic_cdk::api::time()) {
+            ic_cdk::spawn(poll_users(run, None));
+        }
+    });
+}
@@ -0,0 +1,249 @@
+use crate::balance::eth_balances;
+use crate::catalogue::trusted_icrc_ledger;
+use crate::chain::chain_registry;
+use crate::evm_rpc::supports_chain;
+use crate::{
+    pubkey_bytes_to_address, read_config, read_state, try_ecdsa_pubkey_of, StoredPrincipal,
+};
+use candid::{Nat, Principal};
+use futures::future::join_all;
+use shared::types::account::IcrcAccount;
+use shared::types::balance::TokenBalance;
+use shared::types::chain::ChainConfig;
+use shared::types::custom_token::{LedgerId, Token};
+use shared::types::portfolio::{Portfolio, PortfolioBalance, PortfolioError, PortfolioToken};
+use shared::types::token::{ChainId, UserToken};
+
+/// The decimals of the native token of the EVM chains.
+const NATIVE_DECIMALS: u8 = 18;
+
+/// Formats a balance with its decimals applied, without trailing zeros, e.g. `150000000` with 8 decimals is `1.5`.
+pub fn format_amount(balance: &Nat, decimals: u8) -> String {
+    let decimals = usize::from(decimals);
+    let digits = format!(
+        "{:0>width$}",
+        balance.0.to_str_radix(10),
+        width = decimals + 1
+    );
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    match fraction.trim_end_matches('0') {
+        "" => integer.to_string(),
+        fraction => format!("{integer}.{fraction}"),
+    }
+}
+
+fn portfolio_balance(
+    token: PortfolioToken,
+    symbol: Option<String>,
+    decimals: u8,
+    balance: Nat,
+) -> PortfolioBalance {
+    PortfolioBalance {
+        token,
+        symbol,
+        decimals,
+        amount: format_amount(&balance, decimals),
+        balance,
+    }
+}
+
+/// Returns the balance of the default account of the principal on the ledger, with the symbol and decimals of its
+/// catalogue entry.
+///
+/// Only the ledgers of the catalogue are called, see `trusted_icrc_ledger`.
+async fn icrc_balance(
+    principal: Principal,
+    ledger_id: LedgerId,
+) -> Result<PortfolioBalance, String> {
+    let token = trusted_icrc_ledger(&ledger_id)?;
+    let account = IcrcAccount {
+        owner: principal,
+        subaccount: None,
+    };
+    let (balance,) = ic_cdk::call::<_, (Nat,)>(ledger_id, "icrc1_balance_of", (account,))
+        .await
+        .map_err(|(_, err)| format!("failed to get the balance on ledger {ledger_id}: {err}"))?;
+
+    Ok(portfolio_balance(
+        PortfolioToken::Icrc { ledger_id },
+        Some(token.symbol),
+        token.decimals,
+        balance,
+    ))
+}
+
+/// The native token of the chain followed by the ERC-20 tokens of the user on it.
+fn evm_tokens(chain_id: ChainId, user_tokens: &[UserToken]) -> Vec<PortfolioToken> {
+    std::iter::once(None)
+        .chain(
+            user_tokens
+                .iter()
+                .filter(|t| t.chain_id == chain_id)
+                .map(|t| Some(t.contract_address.clone())),
+        )
+        .map(|contract_address| PortfolioToken::Evm {
+            chain_id,
+            contract_address,
+        })
+        .collect()
+}
+
+fn evm_balance(
+    chain: &ChainConfig,
+    user_tokens: &[UserToken],
+    TokenBalance {
+        contract_address,
+        balance,
+    }: TokenBalance,
+) -> Result<PortfolioBalance, PortfolioError> {
+    let (symbol, decimals) = match &contract_address {
+        None => (Some(chain.native_symbol.clone()), Some(NATIVE_DECIMALS)),
+        Some(address) => user_tokens
+            .iter()
+            .find(|t| t.chain_id == chain.chain_id && &t.contract_address == address)
+            .map_or((None, None), |t| (t.symbol.clone(), t.decimals)),
+    };
+    let token = PortfolioToken::Evm {
+        chain_id: chain.chain_id,
+        contract_address,
+    };
+
+    match (balance, decimals) {
+        (Ok(balance), Some(decimals)) => Ok(portfolio_balance(token, symbol, decimals, balance)),
+        (Ok(_), None) => Err(PortfolioError {
+            token,
+            error: "The decimals of the token are unknown".to_string(),
+        }),
+        (Err(error), _) => Err(PortfolioError { token, error }),
+    }
+}
+
+/// Returns the native and ERC-20 balances of the principal on the enabled chains the EVM RPC canister supports.
+async fn evm_balances(
+    principal: &Principal,
+    user_tokens: &[UserToken],
+) -> Vec<Result<PortfolioBalance, PortfolioError>> {
+    let chains: Vec<ChainConfig> = chain_registry()
+        .into_iter()
+        .filter(|c| c.enabled && supports_chain(c.chain_id))
+        .collect();
+
+    let mut results: Vec<Result<PortfolioBalance, PortfolioError>> = user_tokens
+        .iter()
+        .filter(|t| !chains.iter().any(|c| c.chain_id == t.chain_id))
+        .map(|t| {
+            Err(PortfolioError {
+                token: PortfolioToken::Evm {
+                    chain_id: t.chain_id,
+                    contract_address: Some(t.contract_address.clone()),
+                },
+                error: format!("Chain {} is not supported", t.chain_id),
+            })
+        })
+        .collect();
+
+    // Every token of a chain whose balances cannot be fetched at all is reported with the error.
+    let chain_errors = |chain: &ChainConfig, error: &str| {
+        evm_tokens(chain.chain_id, user_tokens)
+            .into_iter()
+            .map(|token| {
+                Err(PortfolioError {
+                    token,
+                    error: error.to_string(),
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if chains.is_empty() {
+        return results;
+    }
+    if read_config(|c| c.evm_rpc_canister_id).is_none() {
+        for chain in &chains {
+            results.extend(chain_errors(
+                chain,
+                "The EVM RPC canister is not configured",
+            ));
+        }
+        return results;
+    }
+
+    let address = match try_ecdsa_pubkey_of(principal).await {
+        Ok(pubkey) => pubkey_bytes_to_address(&pubkey),
+        Err(error) => {
+            for chain in &chains {
+                results.extend(chain_errors(chain, &error));
+            }
+            return results;
+        }
+    };
+    let balances = join_all(
+        chains
+            .iter()
+            .map(|chain| eth_balances(principal, address.clone(), chain.chain_id)),
+    )
+    .await;
+
+    for (chain, balances) in chains.iter().zip(balances) {
+        match balances {
+            Ok(balances) => results.extend(
+                balances
+                    .balances
+                    .into_iter()
+                    .map(|balance| evm_balance(chain, user_tokens, balance)),
+            ),
+            Err(error) => results.extend(chain_errors(chain, &error)),
+        }
+    }
+    results
+}
+
+/// Returns the balances of the enabled ICRC custom tokens and of the EVM tokens of the principal.
+///
+/// The ledgers and the chains are queried in parallel. A balance that cannot be fetched, e.g. on a ledger that is not
+/// in the catalogue, is reported as an error instead of failing the others.
+pub async fn portfolio(principal: &Principal) -> Portfolio {
+    let stored_principal = StoredPrincipal(*principal);
+    let ledger_ids: Vec<LedgerId> =
+        read_state(|s| s.custom_token.get(&stored_principal).unwrap_or_default().0)
+            .into_iter()
+            .filter(|t| t.enabled)
+            .map(|t| match t.token {
+                Token::Icrc(token) => token.ledger_id,
+            })
+            .collect();
+    let user_tokens = read_state(|s| s.user_token.get(&stored_principal).unwrap_or_default().0);
+
+    let (icrc_results, evm_results) = futures::join!(
+        join_all(
+            ledger_ids
+                .iter()
+                .map(|ledger_id| icrc_balance(*principal, *ledger_id))
+        ),
+        evm_balances(principal, &user_tokens),
+    );
+
+    let icrc_results = ledger_ids
+        .into_iter()
+        .zip(icrc_results)
+        .map(|(ledger_id, result)| {
+            result.map_err(|error| PortfolioError {
+                token: PortfolioToken::Icrc { ledger_id },
+                error,
+            })
+        });
+
+    let (mut balances, mut errors) = (vec![], vec![]);
+    for result in icrc_results.chain(evm_results) {
+        match result {
+            Ok(balance) => balances.push(balance),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    Portfolio {
+        balances,
+        errors,
+        timestamp: ic_cdk::api::time(),
+    }
+}
@@ -0,0 +1,159 @@
+//! Daily snapshots of the portfolio of the users who opted in.
+use crate::{mutate_state, read_state, Candid, StoredPrincipal};
+use candid::Principal;
+use futures::future::join_all;
+use shared::types::portfolio::{Portfolio, PortfolioSnapshot, SnapshotBalance};
+use std::ops::Bound;
+use std::time::Duration;
+
+const DAY_NANOS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// The days of history kept per user, older snapshots are dropped when a new one is taken.
+const RETENTION_DAYS: u64 = 365;
+
+/// The users whose portfolio is fetched in one slice of a run, so that a slice stays within the instruction limits.
+const SNAPSHOT_BATCH_SIZE: usize = 10;
+
+/// The users whose portfolio is snapshotted daily, each snapshot calls the ledgers and the RPC providers.
+const MAX_USERS: u64 = 10_000;
+
+/// The snapshots returned per call, so that a response stays within the message size limit.
+const MAX_LIMIT: u64 = 100;
+
+pub fn enable(principal: &Principal, now: u64) {
+    let key = StoredPrincipal(*principal);
+    mutate_state(|s| {
+        if !s.portfolio_history_user.contains_key(&key)
+            && s.portfolio_history_user.len() >= MAX_USERS
+        {
+            ic_cdk::trap(&format!(
+                "Portfolio history users should not exceed {MAX_USERS}"
+            ));
+        }
+        s.portfolio_history_user.insert(key, now)
+    });
+}
+
+/// Removes the snapshots of the principal taken before the day.
+fn remove_snapshots_before(principal: &Principal, day: u64) {
+    let key = StoredPrincipal(*principal);
+    mutate_state(|s| {
+        let days: Vec<u64> = s
+            .portfolio_snapshot
+            .range((key, 0)..(key, day))
+            .map(|((_, day), _)| day)
+            .collect();
+        for day in days {
+            s.portfolio_snapshot.remove(&(key, day));
+        }
+    });
+}
+
+/// Opts the user out and deletes their history.
+pub fn disable(principal: &Principal) {
+    mutate_state(|s| {
+        s.portfolio_history_user
+            .remove(&StoredPrincipal(*principal))
+    });
+    remove_snapshots_before(principal, u64::MAX);
+}
+
+pub fn is_enabled(principal: &Principal) -> bool {
+    read_state(|s| {
+        s.portfolio_history_user
+            .contains_key(&StoredPrincipal(*principal))
+    })
+}
+
+/// Returns up to `limit` snapshots of the principal taken between the two timestamps, oldest first.
+///
+/// The following ones are listed by starting the period right after the timestamp of the last one returned.
+pub fn history(principal: &Principal, from: u64, to: u64, limit: u64) -> Vec<PortfolioSnapshot> {
+    if from > to {
+        ic_cdk::trap("The start of the period should not be after its end");
+    }
+    if limit == 0 || limit > MAX_LIMIT {
+        ic_cdk::trap(&format!("The limit should be between 1 and {MAX_LIMIT}"));
+    }
+
+    let key = StoredPrincipal(*principal);
+    read_state(|s| {
+        s.portfolio_snapshot
+            .range((key, from / DAY_NANOS)..=(key, to / DAY_NANOS))
+            .map(|(_, snapshot)| snapshot.0)
+            .filter(|snapshot| (from..=to).contains(&snapshot.timestamp))
+            .take(limit as usize)
+            .collect()
+    })
+}
+
+fn snapshot(portfolio: Portfolio) -> PortfolioSnapshot {
+    PortfolioSnapshot {
+        timestamp: portfolio.timestamp,
+        balances: portfolio
+            .balances
+            .into_iter()
+            .map(|b| SnapshotBalance {
+                token: b.token,
+                decimals: b.decimals,
+                balance: b.balance,
+            })
+            .collect(),
+        failed_tokens: portfolio.errors.into_iter().map(|e| e.token).collect(),
+    }
+}
+
+/// Records the snapshot of the day, replacing an earlier one of the same day, and drops the expired ones.
+fn record(principal: &Principal, snapshot: PortfolioSnapshot) {
+    let day = snapshot.timestamp / DAY_NANOS;
+
+    remove_snapshots_before(principal, day.saturating_sub(RETENTION_DAYS));
+    mutate_state(|s| {
+        s.portfolio_snapshot
+            .insert((StoredPrincipal(*principal), day), Candid(snapshot))
+    });
+}
+
+/// Takes the snapshots of the next batch of users, then schedules the following batch.
+///
+/// The portfolio of each user is fetched by a call of the canister to itself, so that a trap skips the snapshot of
+/// that user only.
+async fn take_snapshots(after: Option<Principal>) {
+    let start = after.map_or(Bound::Unbounded, |p| Bound::Excluded(StoredPrincipal(p)));
+    let batch: Vec<Principal> = read_state(|s| {
+        s.portfolio_history_user
+            .range((start, Bound::Unbounded))
+            .take(SNAPSHOT_BATCH_SIZE)
+            .map(|(principal, _)| principal.0)
+            .collect()
+    });
+
+    let portfolios = join_all(batch.iter().map(|principal| {
+        ic_cdk::call::<_, (Portfolio,)>(ic_cdk::id(), "portfolio_snapshot_of", (*principal,))
+    }))
+    .await;
+    for (principal, portfolio) in batch.iter().zip(portfolios) {
+        // The user may have opted out while the balances were fetched.
+        if let (Ok((portfolio,)), true) = (portfolio, is_enabled(principal)) {
+            record(principal, snapshot(portfolio));
+        }
+    }
+
+    // A full batch may be followed by more users.
+    if let Some(last) = batch
+        .last()
+        .copied()
+        .filter(|_| batch.len() == SNAPSHOT_BATCH_SIZE)
+    {
+        ic_cdk_timers::set_timer(Duration::ZERO, move || {
+            ic_cdk::spawn(take_snapshots(Some(last)))
+        });
+    }
+}
+
+/// Sets the daily timer of the snapshots, the timers do not survive an upgrade.
+pub fn schedule_snapshots() {
+    ic_cdk_timers::set_timer_interval(Duration::from_nanos(DAY_NANOS), || {
+        ic_cdk::spawn(take_snapshots(None))
+    });
+}
@@ -0,0 +1,136 @@
+use crate::{read_config, StoredPrincipal};
+use shared::metrics::MetricsEncoder;
+use shared::types::rate_limit::RateLimitConfig;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::thread::LocalKey;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Allows a burst of signatures, e.g. an approval followed by a transfer, and a sustained rate of six per minute.
+const DEFAULT_SIGNING_RATE_LIMIT: RateLimitConfig = RateLimitConfig {
+    capacity: 20,
+    refill_interval_secs: 10,
+};
+
+/// Allows loading the balances of every chain at once and a sustained rate of thirty calls per minute.
+const DEFAULT_EXTERNAL_CALL_RATE_LIMIT: RateLimitConfig = RateLimitConfig {
+    capacity: 30,
+    refill_interval_secs: 2,
+};
+
+struct TokenBucket {
+    tokens: u32,
+    last_refill: u64,
+}
+
+thread_local! {
+    // The buckets are kept on the heap: losing them on upgrade merely refills them.
+    static SIGNING_BUCKETS: RefCell<BTreeMap<StoredPrincipal, TokenBucket>> = RefCell::default();
+    static SIGNING_RATE_LIMIT_HITS: RefCell<u64> = RefCell::default();
+    static EXTERNAL_CALL_BUCKETS: RefCell<BTreeMap<StoredPrincipal, TokenBucket>> = RefCell::default();
+    static EXTERNAL_CALL_RATE_LIMIT_HITS: RefCell<u64> = RefCell::default();
+}
+
+type Buckets = RefCell<BTreeMap<StoredPrincipal, TokenBucket>>;
+
+pub fn signing_rate_limit() -> RateLimitConfig {
+    read_config(|c| c.signing_rate_limit).unwrap_or(DEFAULT_SIGNING_RATE_LIMIT)
+}
+
+pub fn external_call_rate_limit() -> RateLimitConfig {
+    read_config(|c| c.external_call_rate_limit).unwrap_or(DEFAULT_EXTERNAL_CALL_RATE_LIMIT)
+}
+
+/// Takes a token from the bucket of the principal, returns false if the bucket is empty.
+fn try_consume(
+    buckets: &'static LocalKey<Buckets>,
+    hits: &'static LocalKey<RefCell<u64>>,
+    rate_limit: RateLimitConfig,
+    principal: StoredPrincipal,
+    now: u64,
+) -> bool {
+    let RateLimitConfig {
+        capacity,
+        refill_interval_secs,
+    } = rate_limit;
+    let refill_interval = refill_interval_secs.saturating_mul(NANOS_PER_SEC).max(1);
+
+    let consumed = buckets.with(|buckets| {
+        let mut buckets = buckets.borrow_mut();
+        let bucket = buckets.entry(principal).or_insert(TokenBucket {
+            tokens: capacity,
+            last_refill: now,
+        });
+
+        let refills = now.saturating_sub(bucket.last_refill) / refill_interval;
+        if refills > 0 {
+            bucket.tokens = u64::from(bucket.tokens)
+                .saturating_add(refills)
+                .min(u64::from(capacity)) as u32;
+            bucket.last_refill += refills * refill_interval;
+        }
+        // The capacity may have been lowered since the bucket was filled.
+        bucket.tokens = bucket.tokens.min(capacity);
+
+        if bucket.tokens == 0 {
+            false
+        } else {
+            bucket.tokens -= 1;
+            true
+        }
+    });
+
+    if !consumed {
+        hits.with(|hits| *hits.borrow_mut() += 1);
+    }
+
+    consumed
+}
+
+/// Takes a signing token from the bucket of the principal, returns false if the bucket is empty.
+pub fn try_consume_signing_token(principal: StoredPrincipal, now: u64) -> bool {
+    try_consume(
+        &SIGNING_BUCKETS,
+        &SIGNING_RATE_LIMIT_HITS,
+        signing_rate_limit(),
+        principal,
+        now,
+    )
+}
+
+/// Takes a token of the calls to other canisters and RPC providers from the bucket of the principal, returns false
+/// if the bucket is empty.
+pub fn try_consume_external_call_token(principal: StoredPrincipal, now: u64) -> bool {
+    try_consume(
+        &EXTERNAL_CALL_BUCKETS,
+        &EXTERNAL_CALL_RATE_LIMIT_HITS,
+        external_call_rate_limit(),
+        principal,
+        now,
+    )
+}
+
+pub fn encode_rate_limit_metrics(w: &mut MetricsEncoder<Vec<u8>>) -> std::io::Result<()> {
+    w.encode_counter(
+        "ic_eth_wallet_signing_rate_limit_hits",
+        SIGNING_RATE_LIMIT_HITS.with(|hits| *hits.borrow()) as f64,
+        "Number of signing calls rejected because the caller exceeded the rate limit",
+    )?;
+    w.encode_gauge(
+        "ic_eth_wallet_signing_rate_limit_buckets",
+        SIGNING_BUCKETS.with(|buckets| buckets.borrow().len()) as f64,
+        "Number of principals with a signing rate limit bucket",
+    )?;
+    w.encode_counter(
+        "ic_eth_wallet_external_call_rate_limit_hits",
+        EXTERNAL_CALL_RATE_LIMIT_HITS.with(|hits| *hits.borrow()) as f64,
+        "Number of external calls rejected because the caller exceeded the rate limit",
+    )?;
+    w.encode_gauge(
+        "ic_eth_wallet_external_call_rate_limit_buckets",
+        EXTERNAL_CALL_BUCKETS.with(|buckets| buckets.borrow().len()) as f64,
+        "Number of principals with an external call rate limit bucket",
+    )?;
+    Ok(())
+}
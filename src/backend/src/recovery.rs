@@ -0,0 +1,277 @@
+use crate::scheduled_transaction::move_releases;
+use crate::shared_account::move_memberships;
+use crate::{mutate_state, read_state, Candid, State, StoredPrincipal, VMem};
+use candid::{CandidType, Deserialize, Principal};
+use ic_stable_structures::{StableBTreeMap, Storable};
+use shared::types::notification::NotificationId;
+use shared::types::recovery::{ApproveRecoveryRequest, GuardianConfig, RecoveryRequest};
+
+const MAX_GUARDIANS: usize = 10;
+
+/// The time the user has to cancel a recovery approved by their guardians.
+const RECOVERY_TIME_LOCK_NANOS: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+
+/// Who controls the keys derived for a principal, once a recovery has been completed.
+#[derive(CandidType, Deserialize)]
+pub enum KeyControl {
+    /// The principal controls the keys derived for another principal.
+    KeysOf(Principal),
+    /// The keys of the principal were recovered by another principal.
+    RecoveredBy(Principal),
+}
+
+/// Returns the principal the keys of the specified principal are derived for.
+///
+/// Traps if the keys of the principal were recovered by another principal.
+pub fn key_principal_of(principal: &Principal) -> Principal {
+    try_key_principal_of(principal).unwrap_or_else(|e| ic_cdk::trap(&e))
+}
+
+/// Returns the principal the keys of the specified principal are derived for, or the error if they were recovered
+/// by another principal.
+pub fn try_key_principal_of(principal: &Principal) -> Result<Principal, String> {
+    match read_state(|s| s.key_control.get(&StoredPrincipal(*principal))) {
+        None => Ok(*principal),
+        Some(Candid(KeyControl::KeysOf(key_principal))) => Ok(key_principal),
+        Some(Candid(KeyControl::RecoveredBy(new_principal))) => Err(format!(
+            "The keys of the caller were recovered by {new_principal}"
+        )),
+    }
+}
+
+fn assert_valid_guardian_config(principal: &Principal, config: &GuardianConfig) {
+    if config.guardians.is_empty() || config.guardians.len() > MAX_GUARDIANS {
+        ic_cdk::trap(&format!(
+            "Guardians should be between 1 and {MAX_GUARDIANS}"
+        ));
+    }
+    if config.threshold == 0 || config.threshold as usize > config.guardians.len() {
+        ic_cdk::trap("Threshold should be between 1 and the number of guardians");
+    }
+    for (i, guardian) in config.guardians.iter().enumerate() {
+        if guardian == principal || *guardian == Principal::anonymous() {
+            ic_cdk::trap(&format!("{guardian} cannot be a guardian"));
+        }
+        if config.guardians[..i].contains(guardian) {
+            ic_cdk::trap(&format!("Guardian {guardian} is listed twice"));
+        }
+    }
+}
+
+/// Sets the guardians of the user, cancelling a recovery in progress.
+pub fn set_guardians(principal: &Principal, config: GuardianConfig) {
+    // The recovered principals no longer control their keys.
+    key_principal_of(principal);
+    assert_valid_guardian_config(principal, &config);
+
+    let stored_principal = StoredPrincipal(*principal);
+    mutate_state(|s| {
+        s.guardian_config.insert(stored_principal, Candid(config));
+        s.recovery_request.remove(&stored_principal);
+    });
+}
+
+pub fn get_guardians(principal: &Principal) -> Option<GuardianConfig> {
+    read_state(|s| {
+        s.guardian_config
+            .get(&StoredPrincipal(*principal))
+            .map(|c| c.0)
+    })
+}
+
+/// Returns the recovery of the user, if the caller is the user, one of their guardians or the new principal.
+pub fn get_recovery_request(caller: &Principal, user: &Principal) -> Option<RecoveryRequest> {
+    let config = get_guardians(user)?;
+    let request = read_state(|s| s.recovery_request.get(&StoredPrincipal(*user)).map(|r| r.0))?;
+
+    (caller == user || config.guardians.contains(caller) || request.new_principal == *caller)
+        .then_some(request)
+}
+
+/// Records the approval of a guardian, the time lock starts once the threshold is reached.
+pub fn approve(guardian: &Principal, request: ApproveRecoveryRequest, now: u64) {
+    let ApproveRecoveryRequest {
+        user,
+        new_principal,
+    } = request;
+
+    let config = get_guardians(&user)
+        .filter(|config| config.guardians.contains(guardian))
+        .unwrap_or_else(|| ic_cdk::trap(&format!("Caller is not a guardian of {user}")));
+
+    if new_principal == user || new_principal == Principal::anonymous() {
+        ic_cdk::trap(&format!(
+            "{new_principal} cannot recover the keys of {user}"
+        ));
+    }
+
+    let stored_user = StoredPrincipal(user);
+    let mut recovery = read_state(|s| s.recovery_request.get(&stored_user))
+        .map(|r| r.0)
+        .unwrap_or(RecoveryRequest {
+            new_principal,
+            approvals: vec![],
+            created_timestamp: now,
+            unlock_timestamp: None,
+        });
+
+    if recovery.new_principal != new_principal {
+        ic_cdk::trap(&format!(
+            "A recovery to {} is already in progress",
+            recovery.new_principal
+        ));
+    }
+    if !recovery.approvals.contains(guardian) {
+        recovery.approvals.push(*guardian);
+    }
+    if recovery.unlock_timestamp.is_none() && recovery.approvals.len() >= config.threshold as usize
+    {
+        recovery.unlock_timestamp = Some(now + RECOVERY_TIME_LOCK_NANOS);
+    }
+
+    mutate_state(|s| s.recovery_request.insert(stored_user, Candid(recovery)));
+}
+
+/// Cancels the recovery of the user, e.g. if their guardians were compromised.
+pub fn cancel(user: &Principal) {
+    if mutate_state(|s| s.recovery_request.remove(&StoredPrincipal(*user))).is_none() {
+        ic_cdk::trap("No recovery in progress");
+    }
+}
+
+/// Returns whether the principal holds keys or any state of its own.
+///
+/// The profile is left out, it is created as soon as a principal signs in.
+fn has_state(s: &State, principal: &Principal) -> bool {
+    let key = StoredPrincipal(*principal);
+    s.key_control.contains_key(&key)
+        || s.signed_nonce
+            .range((key, 0)..)
+            .next()
+            .is_some_and(|((p, _), _)| p == key)
+        || s.user_token.contains_key(&key)
+        || s.custom_token.contains_key(&key)
+        || s.user_catalogue_token.contains_key(&key)
+        || s.contact.contains_key(&key)
+        || s.allowlist.contains_key(&key)
+        || s.scheduled_transaction.contains_key(&key)
+        || s.cketh_conversion.contains_key(&key)
+        || s.guardian_config.contains_key(&key)
+        || s.portfolio_history_user.contains_key(&key)
+        || s.portfolio_snapshot
+            .range((key, 0)..)
+            .next()
+            .is_some_and(|((p, _), _)| p == key)
+        || s.shared_account_member
+            .range((key, 0)..)
+            .next()
+            .is_some_and(|((p, _), _)| p == key)
+        || s.notification_user.contains_key(&key)
+        || s.notification
+            .range((key, 0)..)
+            .next()
+            .is_some_and(|((p, _), _)| p == key)
+}
+
+fn move_entry<V: Storable>(
+    map: &mut StableBTreeMap<StoredPrincipal, V, VMem>,
+    from: StoredPrincipal,
+    to: StoredPrincipal,
+) {
+    if let Some(value) = map.remove(&from) {
+        map.insert(to, value);
+    }
+}
+
+/// Moves the state of the user to the new principal, the opt-ins included so that the background jobs no longer
+/// run for the user, whose keys are no longer reachable.
+///
+/// The nonces signed are kept by key principal, which the new principal now shares with the user.
+fn move_state(s: &mut State, from: StoredPrincipal, to: StoredPrincipal) {
+    move_entry(&mut s.user_token, from, to);
+    move_entry(&mut s.custom_token, from, to);
+    move_entry(&mut s.user_catalogue_token, from, to);
+    move_entry(&mut s.contact, from, to);
+    // The profile of the user replaces the one created when the new principal signed in.
+    move_entry(&mut s.user_profile, from, to);
+    move_entry(&mut s.allowlist, from, to);
+    move_entry(&mut s.scheduled_transaction, from, to);
+    move_releases(s, from, to);
+    move_entry(&mut s.cketh_conversion, from, to);
+    // The guardians keep protecting the keys.
+    move_entry(&mut s.guardian_config, from, to);
+    move_entry(&mut s.portfolio_history_user, from, to);
+    move_entry(&mut s.notification_user, from, to);
+    move_memberships(s, from, to);
+
+    let days: Vec<u64> = s
+        .portfolio_snapshot
+        .range((from, 0)..=(from, u64::MAX))
+        .map(|((_, day), _)| day)
+        .collect();
+    for day in days {
+        if let Some(snapshot) = s.portfolio_snapshot.remove(&(from, day)) {
+            s.portfolio_snapshot.insert((to, day), snapshot);
+        }
+    }
+
+    let ids: Vec<NotificationId> = s
+        .notification
+        .range((from, 0)..=(from, NotificationId::MAX))
+        .map(|((_, id), _)| id)
+        .collect();
+    for id in ids {
+        if let Some(notification) = s.notification.remove(&(from, id)) {
+            s.notification.insert((to, id), notification);
+        }
+    }
+
+    // The management canister is the smallest principal.
+    let ledger_ids: Vec<StoredPrincipal> = s
+        .last_seen_transaction
+        .range((from, StoredPrincipal(Principal::management_canister()))..)
+        .take_while(|((principal, _), _)| *principal == from)
+        .map(|((_, ledger_id), _)| ledger_id)
+        .collect();
+    for ledger_id in ledger_ids {
+        if let Some(last_seen) = s.last_seen_transaction.remove(&(from, ledger_id)) {
+            s.last_seen_transaction.insert((to, ledger_id), last_seen);
+        }
+    }
+}
+
+/// Moves the control of the keys and the state of the user to the new principal once the time lock has elapsed.
+///
+/// The new principal must not hold keys or state of its own, so that nothing of it is lost or mixed with the
+/// state of the user.
+pub fn complete(new_principal: &Principal, user: &Principal, now: u64) {
+    let stored_user = StoredPrincipal(*user);
+    let recovery = read_state(|s| s.recovery_request.get(&stored_user))
+        .map(|r| r.0)
+        .filter(|r| r.new_principal == *new_principal)
+        .unwrap_or_else(|| ic_cdk::trap(&format!("No recovery of {user} to the caller")));
+
+    match recovery.unlock_timestamp {
+        Some(unlock_timestamp) if unlock_timestamp <= now => (),
+        Some(_) => ic_cdk::trap("The recovery is still time-locked"),
+        None => ic_cdk::trap("The recovery has not been approved by enough guardians"),
+    }
+
+    if read_state(|s| has_state(s, new_principal)) {
+        ic_cdk::trap("The caller already holds keys or state");
+    }
+
+    let key_principal = key_principal_of(user);
+    let stored_new_principal = StoredPrincipal(*new_principal);
+
+    mutate_state(|s| {
+        s.key_control.insert(
+            stored_new_principal,
+            Candid(KeyControl::KeysOf(key_principal)),
+        );
+        s.key_control
+            .insert(stored_user, Candid(KeyControl::RecoveredBy(*new_principal)));
+        s.recovery_request.remove(&stored_user);
+        move_state(s, stored_user, stored_new_principal);
+    });
+}
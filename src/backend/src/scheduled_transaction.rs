@@ -0,0 +1,231 @@
+use crate::{mutate_state, read_state, Candid, State, StoredPrincipal};
+use candid::Principal;
+use ic_cdk_timers::TimerId;
+use shared::types::scheduled_transaction::{
+    ScheduleTransactionRequest, ScheduledTransaction, ScheduledTransactionId,
+    ScheduledTransactionStatus,
+};
+use std::cell::Cell;
+use std::time::Duration;
+
+const MAX_SCHEDULED_TRANSACTIONS: usize = 20;
+const MAX_RELEASE_DELAY_NANOS: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+
+/// The transactions released per timer, the next ones are released by a timer set right after.
+const MAX_RELEASES_PER_TIMER: usize = 20;
+
+thread_local! {
+    // The release timestamp the timer is set for, and the timer. There is one timer at most, for the earliest
+    // pending transaction; it is kept on the heap and set again after an upgrade.
+    static RELEASE_TIMER: Cell<Option<(u64, TimerId)>> = const { Cell::new(None) };
+}
+
+fn scheduled_transactions(principal: &Principal) -> Vec<ScheduledTransaction> {
+    read_state(|s| {
+        s.scheduled_transaction
+            .get(&StoredPrincipal(*principal))
+            .unwrap_or_default()
+            .0
+    })
+}
+
+fn mutate_scheduled_transactions<R>(
+    principal: &Principal,
+    f: impl FnOnce(&mut Vec<ScheduledTransaction>) -> R,
+) -> R {
+    let mut transactions = scheduled_transactions(principal);
+    let result = f(&mut transactions);
+    mutate_state(|s| {
+        s.scheduled_transaction
+            .insert(StoredPrincipal(*principal), Candid(transactions))
+    });
+    result
+}
+
+fn next_id() -> ScheduledTransactionId {
+    mutate_state(|s| {
+        let id = *s.scheduled_transaction_id.get() + 1;
+        s.scheduled_transaction_id
+            .set(id)
+            .expect("setting the scheduled transaction id should succeed");
+        id
+    })
+}
+
+/// Queues a transaction to be signed at its release time.
+///
+/// The signed, failed and cancelled transactions are dropped, oldest first, to make room in a full queue.
+pub fn schedule(
+    principal: &Principal,
+    request: ScheduleTransactionRequest,
+    now: u64,
+) -> ScheduledTransactionId {
+    if request.release_timestamp <= now {
+        ic_cdk::trap("Release timestamp should be in the future");
+    }
+    if request.release_timestamp - now > MAX_RELEASE_DELAY_NANOS {
+        ic_cdk::trap("Release timestamp should be within 30 days");
+    }
+
+    let id = next_id();
+
+    mutate_scheduled_transactions(principal, |transactions| {
+        if transactions.len() == MAX_SCHEDULED_TRANSACTIONS {
+            match transactions
+                .iter()
+                .position(|t| t.status != ScheduledTransactionStatus::Pending)
+            {
+                Some(p) => {
+                    transactions.remove(p);
+                }
+                None => ic_cdk::trap(&format!(
+                    "Pending transactions should not exceed {MAX_SCHEDULED_TRANSACTIONS}"
+                )),
+            }
+        }
+
+        transactions.push(ScheduledTransaction {
+            id,
+            request: request.request,
+            created_timestamp: now,
+            release_timestamp: request.release_timestamp,
+            status: ScheduledTransactionStatus::Pending,
+        });
+    });
+
+    mutate_state(|s| {
+        s.scheduled_release
+            .insert((request.release_timestamp, id), StoredPrincipal(*principal))
+    });
+    arm_release_timer(now);
+
+    id
+}
+
+/// Cancels a transaction that has not been signed yet.
+pub fn cancel(principal: &Principal, id: ScheduledTransactionId) {
+    let release_timestamp = mutate_scheduled_transactions(principal, |transactions| {
+        match transactions.iter_mut().find(|t| t.id == id) {
+            Some(t) if t.status == ScheduledTransactionStatus::Pending => {
+                t.status = ScheduledTransactionStatus::Cancelled;
+                t.release_timestamp
+            }
+            Some(_) => ic_cdk::trap(&format!("Transaction {id} is not pending")),
+            None => ic_cdk::trap(&format!("Transaction {id} not found")),
+        }
+    });
+    mutate_state(|s| s.scheduled_release.remove(&(release_timestamp, id)));
+}
+
+pub fn list(principal: &Principal) -> Vec<ScheduledTransaction> {
+    scheduled_transactions(principal)
+}
+
+/// Returns the signed raw transaction once it has been released.
+pub fn signed_transaction(principal: &Principal, id: ScheduledTransactionId) -> Option<String> {
+    scheduled_transactions(principal)
+        .into_iter()
+        .find(|t| t.id == id)
+        .and_then(|t| match t.status {
+            ScheduledTransactionStatus::Signed(raw_transaction) => Some(raw_transaction),
+            _ => None,
+        })
+}
+
+/// Sets the timer of the earliest pending transaction, unless it is set already.
+///
+/// The timers do not survive an upgrade, this is called again then.
+pub fn arm_release_timer(now: u64) {
+    let Some((timestamp, _)) =
+        read_state(|s| s.scheduled_release.iter().next().map(|(key, _)| key))
+    else {
+        return;
+    };
+
+    RELEASE_TIMER.with(|timer| {
+        if let Some((armed_timestamp, timer_id)) = timer.get() {
+            if armed_timestamp <= timestamp {
+                return;
+            }
+            ic_cdk_timers::clear_timer(timer_id);
+        }
+        let delay = Duration::from_nanos(timestamp.saturating_sub(now));
+        let timer_id = ic_cdk_timers::set_timer(delay, release_due);
+        timer.set(Some((timestamp, timer_id)));
+    });
+}
+
+/// Releases the transactions whose release time has come, then sets the timer of the next one.
+fn release_due() {
+    RELEASE_TIMER.with(|timer| timer.set(None));
+
+    let now = ic_cdk::api::time();
+    let due: Vec<(ScheduledTransactionId, Principal)> = mutate_state(|s| {
+        let due: Vec<_> = s
+            .scheduled_release
+            .range(..=(now, ScheduledTransactionId::MAX))
+            .take(MAX_RELEASES_PER_TIMER)
+            .collect();
+        for (key, _) in &due {
+            s.scheduled_release.remove(key);
+        }
+        due.into_iter()
+            .map(|((_, id), principal)| (id, principal.0))
+            .collect()
+    });
+
+    for (id, principal) in due {
+        ic_cdk::spawn(release(principal, id));
+    }
+
+    arm_release_timer(now);
+}
+
+/// Signs the transaction unless it was cancelled in the meantime.
+///
+/// The signing is a call of the canister to itself, so that a transaction that is no longer allowed or that cannot
+/// be signed fails instead of staying pending.
+async fn release(principal: Principal, id: ScheduledTransactionId) {
+    let pending = |principal: &Principal| {
+        scheduled_transactions(principal)
+            .into_iter()
+            .find(|t| t.id == id && t.status == ScheduledTransactionStatus::Pending)
+    };
+
+    let Some(transaction) = pending(&principal) else {
+        return;
+    };
+
+    let result: Result<(String,), _> = ic_cdk::call(
+        ic_cdk::id(),
+        "sign_scheduled_transaction",
+        (principal, transaction.request),
+    )
+    .await;
+    let status = match result {
+        Ok((raw_transaction,)) => ScheduledTransactionStatus::Signed(raw_transaction),
+        Err((_, reason)) => ScheduledTransactionStatus::Failed { reason },
+    };
+
+    // The transaction may have been cancelled while it was being signed.
+    if pending(&principal).is_some() {
+        mutate_scheduled_transactions(&principal, |transactions| {
+            if let Some(t) = transactions.iter_mut().find(|t| t.id == id) {
+                t.status = status;
+            }
+        });
+    }
+}
+
+/// Points the releases of the pending transactions moved from a principal to another one, e.g. by a recovery.
+pub fn move_releases(s: &mut State, from: StoredPrincipal, to: StoredPrincipal) {
+    let Some(transactions) = s.scheduled_transaction.get(&to) else {
+        return;
+    };
+    for t in transactions.0 {
+        let key = (t.release_timestamp, t.id);
+        if s.scheduled_release.get(&key) == Some(from) {
+            s.scheduled_release.insert(key, to);
+        }
+    }
+}
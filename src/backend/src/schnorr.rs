@@ -0,0 +1,132 @@
+//! Threshold Schnorr signatures of the management canister.
+//!
+//! Note: `ic_cdk` does not expose this API yet, hence the types below mirror the interface specification.
+
+use crate::cycles::record_signature;
+use crate::recovery::key_principal_of;
+use crate::{read_config, StoredPrincipal};
+use candid::{CandidType, Deserialize, Principal};
+use serde_bytes::ByteBuf;
+
+/// The fee of a `sign_with_schnorr` call, the same as the one of `sign_with_ecdsa`.
+const SIGN_WITH_SCHNORR_FEE: u128 = 26_153_846_153;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug)]
+pub enum SchnorrAlgorithm {
+    #[serde(rename = "bip340secp256k1")]
+    Bip340Secp256k1,
+    #[serde(rename = "ed25519")]
+    Ed25519,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+struct SchnorrKeyId {
+    algorithm: SchnorrAlgorithm,
+    name: String,
+}
+
+#[derive(CandidType, Deserialize)]
+struct SchnorrPublicKeyArgument {
+    canister_id: Option<Principal>,
+    derivation_path: Vec<ByteBuf>,
+    key_id: SchnorrKeyId,
+}
+
+#[derive(CandidType, Deserialize)]
+struct SchnorrPublicKeyResponse {
+    public_key: ByteBuf,
+    chain_code: ByteBuf,
+}
+
+/// The tweak of a [BIP-341](https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki) output key.
+///
+/// An empty merkle root commits to no script path, as specified by [BIP-86](https://github.com/bitcoin/bips/blob/master/bip-0086.mediawiki).
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Bip341Aux {
+    pub merkle_root_hash: ByteBuf,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum SchnorrAux {
+    #[serde(rename = "bip341")]
+    Bip341(Bip341Aux),
+}
+
+#[derive(CandidType, Deserialize)]
+struct SignWithSchnorrArgument {
+    message: ByteBuf,
+    derivation_path: Vec<ByteBuf>,
+    key_id: SchnorrKeyId,
+    aux: Option<SchnorrAux>,
+}
+
+#[derive(CandidType, Deserialize)]
+struct SignWithSchnorrResponse {
+    signature: ByteBuf,
+}
+
+/// Derives the keys of a principal, the schema separates the keys of the different chains.
+fn derivation_path(schema: u8, p: &Principal) -> Vec<ByteBuf> {
+    vec![
+        ByteBuf::from(vec![schema]),
+        ByteBuf::from(key_principal_of(p).as_slice().to_vec()),
+    ]
+}
+
+fn key_id(algorithm: SchnorrAlgorithm) -> SchnorrKeyId {
+    SchnorrKeyId {
+        algorithm,
+        name: read_config(|c| {
+            c.schnorr_key_name
+                .clone()
+                .unwrap_or_else(|| c.ecdsa_key_name.clone())
+        }),
+    }
+}
+
+/// Computes the Schnorr public key of the specified principal.
+pub async fn schnorr_pubkey_of(
+    principal: &Principal,
+    algorithm: SchnorrAlgorithm,
+    schema: u8,
+) -> Vec<u8> {
+    let (key,): (SchnorrPublicKeyResponse,) = ic_cdk::call(
+        Principal::management_canister(),
+        "schnorr_public_key",
+        (SchnorrPublicKeyArgument {
+            canister_id: None,
+            derivation_path: derivation_path(schema, principal),
+            key_id: key_id(algorithm),
+        },),
+    )
+    .await
+    .expect("failed to get schnorr public key");
+    key.public_key.into_vec()
+}
+
+/// Signs the message with the Schnorr key of the specified principal.
+pub async fn sign_with_schnorr(
+    principal: &Principal,
+    algorithm: SchnorrAlgorithm,
+    schema: u8,
+    message: Vec<u8>,
+    aux: Option<SchnorrAux>,
+) -> Vec<u8> {
+    let (response,): (SignWithSchnorrResponse,) = ic_cdk::api::call::call_with_payment128(
+        Principal::management_canister(),
+        "sign_with_schnorr",
+        (SignWithSchnorrArgument {
+            message: ByteBuf::from(message),
+            derivation_path: derivation_path(schema, principal),
+            key_id: key_id(algorithm),
+            aux,
+        },),
+        SIGN_WITH_SCHNORR_FEE,
+    )
+    .await
+    .expect("failed to sign the message with schnorr");
+
+    record_signature(StoredPrincipal(*principal), SIGN_WITH_SCHNORR_FEE);
+
+    response.signature.into_vec()
+}
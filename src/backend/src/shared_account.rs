@@ -0,0 +1,413 @@
+use crate::chain::assert_transaction_allowed;
+use crate::denylist::assert_approval_allowed;
+use crate::recovery::try_key_principal_of;
+use crate::{
+    decode_hex, ecdsa_pubkey_at, mutate_state, nat_to_u64, pubkey_bytes_to_address, read_state,
+    sign_eip1559_transaction, Candid, State, StoredPrincipal,
+};
+use candid::Principal;
+use shared::types::chain::TransactionType;
+use shared::types::shared_account::{
+    Approval, CreateSharedAccountRequest, Proposal, ProposalId, ProposalPage, ProposalRef,
+    ProposalStatus, ProposeTransactionRequest, SharedAccount, SharedAccountId,
+};
+use shared::types::transaction::SignRequest;
+use std::ops::Bound;
+
+/// Separates the keys of the shared accounts from the keys of the principals.
+const SHARED_ACCOUNT_SCHEMA: u8 = 4;
+
+const MAX_NAME_LENGTH: usize = 64;
+const MAX_MEMBERS: usize = 10;
+const MAX_OPEN_PROPOSALS: usize = 20;
+/// The proposals kept per account, the oldest closed ones are dropped first.
+const MAX_PROPOSALS: usize = 100;
+const PAGE_SIZE: usize = 20;
+const MAX_CREATED_ACCOUNTS: usize = 10;
+
+fn derivation_path(account_id: SharedAccountId) -> Vec<Vec<u8>> {
+    vec![
+        vec![SHARED_ACCOUNT_SCHEMA],
+        account_id.to_be_bytes().to_vec(),
+    ]
+}
+
+/// Returns the account if the principal is one of its members, otherwise traps.
+///
+/// A principal whose keys were recovered no longer acts for the accounts, its memberships were moved.
+fn member_account(principal: &Principal, account_id: SharedAccountId) -> SharedAccount {
+    if let Err(e) = try_key_principal_of(principal) {
+        ic_cdk::trap(&e);
+    }
+    read_state(|s| s.shared_account.get(&account_id))
+        .map(|a| a.0)
+        .filter(|a| a.members.contains(principal))
+        .unwrap_or_else(|| {
+            ic_cdk::trap(&format!(
+                "Caller is not a member of shared account {account_id}"
+            ))
+        })
+}
+
+/// Returns the accounts the principal is a member of.
+fn member_accounts(principal: &Principal) -> Vec<SharedAccount> {
+    let key = StoredPrincipal(*principal);
+    read_state(|s| {
+        s.shared_account_member
+            .range((key, 0)..=(key, SharedAccountId::MAX))
+            .filter_map(|((_, id), _)| s.shared_account.get(&id))
+            .map(|a| a.0)
+            .collect()
+    })
+}
+
+fn proposal(account_id: SharedAccountId, proposal_id: ProposalId) -> Proposal {
+    read_state(|s| s.proposal.get(&(account_id, proposal_id)))
+        .map(|p| p.0)
+        .unwrap_or_else(|| ic_cdk::trap(&format!("Proposal {proposal_id} not found")))
+}
+
+fn is_open(status: &ProposalStatus) -> bool {
+    matches!(status, ProposalStatus::Open | ProposalStatus::Approved)
+}
+
+/// Saves the proposal and keeps the index of the proposals that are not closed up to date.
+fn save_proposal(account_id: SharedAccountId, proposal: Proposal) {
+    mutate_state(|s| {
+        if is_open(&proposal.status) {
+            s.open_proposal.insert((account_id, proposal.id), ());
+        } else {
+            s.open_proposal.remove(&(account_id, proposal.id));
+        }
+        s.proposal
+            .insert((account_id, proposal.id), Candid(proposal))
+    });
+}
+
+/// Returns the proposals of the account that are not closed.
+fn open_proposals(s: &State, account_id: SharedAccountId) -> Vec<Proposal> {
+    s.open_proposal
+        .range((account_id, 0)..=(account_id, ProposalId::MAX))
+        .filter_map(|(key, _)| s.proposal.get(&key))
+        .map(|p| p.0)
+        .collect()
+}
+
+fn next_proposal_id() -> ProposalId {
+    mutate_state(|s| {
+        let id = *s.proposal_id.get() + 1;
+        s.proposal_id
+            .set(id)
+            .expect("setting the proposal id should succeed");
+        id
+    })
+}
+
+/// Drops the oldest closed proposals of the account beyond `MAX_PROPOSALS`.
+///
+/// The proposals are pruned each time one is added, so there are at most `MAX_PROPOSALS + 1` of them to go through.
+fn prune_proposals(account_id: SharedAccountId) {
+    mutate_state(|s| {
+        let ids: Vec<ProposalId> = s
+            .proposal
+            .range((account_id, 0)..=(account_id, ProposalId::MAX))
+            .map(|((_, id), _)| id)
+            .collect();
+        let excess = ids.len().saturating_sub(MAX_PROPOSALS);
+        let closed: Vec<ProposalId> = ids
+            .into_iter()
+            .filter(|id| !s.open_proposal.contains_key(&(account_id, *id)))
+            .take(excess)
+            .collect();
+        for id in closed {
+            s.proposal.remove(&(account_id, id));
+        }
+    });
+}
+
+pub fn create(caller: &Principal, request: CreateSharedAccountRequest, now: u64) -> SharedAccount {
+    if request.name.len() > MAX_NAME_LENGTH {
+        ic_cdk::trap(&format!("Name should not exceed {MAX_NAME_LENGTH} bytes"));
+    }
+    if !request.members.contains(caller) {
+        ic_cdk::trap("The caller should be a member of the shared account");
+    }
+    if request.members.len() > MAX_MEMBERS {
+        ic_cdk::trap(&format!("Members should not exceed {MAX_MEMBERS}"));
+    }
+    if request.threshold == 0 || request.threshold as usize > request.members.len() {
+        ic_cdk::trap("Threshold should be between 1 and the number of members");
+    }
+    if member_accounts(caller)
+        .iter()
+        .filter(|a| a.created_by == *caller)
+        .count()
+        >= MAX_CREATED_ACCOUNTS
+    {
+        ic_cdk::trap(&format!(
+            "Shared accounts created by the caller should not exceed {MAX_CREATED_ACCOUNTS}"
+        ));
+    }
+    for (i, member) in request.members.iter().enumerate() {
+        if *member == Principal::anonymous() {
+            ic_cdk::trap("Anonymous principal cannot be a member");
+        }
+        if request.members[..i].contains(member) {
+            ic_cdk::trap(&format!("Member {member} is listed twice"));
+        }
+    }
+
+    mutate_state(|s| {
+        let id = *s.shared_account_id.get() + 1;
+        s.shared_account_id
+            .set(id)
+            .expect("setting the shared account id should succeed");
+
+        let account = SharedAccount {
+            id,
+            name: request.name,
+            members: request.members,
+            threshold: request.threshold,
+            created_by: *caller,
+            created_timestamp: now,
+        };
+        for member in &account.members {
+            s.shared_account_member
+                .insert((StoredPrincipal(*member), id), ());
+        }
+        s.shared_account.insert(id, Candid(account.clone()));
+        account
+    })
+}
+
+/// Returns the accounts the principal is a member of.
+pub fn list(principal: &Principal) -> Vec<SharedAccount> {
+    member_accounts(principal)
+}
+
+pub async fn address(caller: &Principal, account_id: SharedAccountId) -> String {
+    member_account(caller, account_id);
+
+    pubkey_bytes_to_address(&ecdsa_pubkey_at(derivation_path(account_id)).await)
+}
+
+/// Checks the chain and the approvals of the transaction, otherwise traps.
+fn assert_proposal_allowed(req: &SignRequest) {
+    assert_transaction_allowed(&req.chain_id, TransactionType::Eip1559, &req.gas);
+
+    if let Some(data) = req.data.as_ref() {
+        assert_approval_allowed(nat_to_u64(&req.chain_id).as_u64(), &decode_hex(data));
+    }
+}
+
+/// Records a proposal approved by its proposer, signs it right away if the threshold is 1.
+pub async fn propose(
+    caller: &Principal,
+    request: ProposeTransactionRequest,
+    now: u64,
+) -> ProposalId {
+    let ProposeTransactionRequest {
+        account_id,
+        request,
+    } = request;
+    member_account(caller, account_id);
+    assert_proposal_allowed(&request);
+
+    let open_count = read_state(|s| {
+        s.open_proposal
+            .range((account_id, 0)..=(account_id, ProposalId::MAX))
+            .take(MAX_OPEN_PROPOSALS)
+            .count()
+    });
+    if open_count >= MAX_OPEN_PROPOSALS {
+        ic_cdk::trap(&format!(
+            "Open proposals should not exceed {MAX_OPEN_PROPOSALS}"
+        ));
+    }
+
+    let id = next_proposal_id();
+
+    save_proposal(
+        account_id,
+        Proposal {
+            id,
+            request,
+            proposer: *caller,
+            created_timestamp: now,
+            approvals: vec![],
+            status: ProposalStatus::Open,
+        },
+    );
+    prune_proposals(account_id);
+
+    approve(
+        caller,
+        ProposalRef {
+            account_id,
+            proposal_id: id,
+        },
+        now,
+    )
+    .await;
+
+    id
+}
+
+/// Records the approval of a member of an open proposal and signs the transaction once the threshold is reached.
+///
+/// A member who already approved can only approve again to sign a proposal that reached a threshold lowered by a
+/// member leaving. An approved proposal whose signing failed can be cancelled by its proposer and proposed again.
+pub async fn approve(caller: &Principal, proposal_ref: ProposalRef, now: u64) -> Proposal {
+    let ProposalRef {
+        account_id,
+        proposal_id,
+    } = proposal_ref;
+    let account = member_account(caller, account_id);
+    let mut proposal = proposal(account_id, proposal_id);
+
+    match proposal.status {
+        ProposalStatus::Open => {
+            if !proposal.approvals.iter().any(|a| a.member == *caller) {
+                proposal.approvals.push(Approval {
+                    member: *caller,
+                    timestamp: now,
+                });
+            } else if proposal.approvals.len() < account.threshold as usize {
+                ic_cdk::trap(&format!("Caller already approved proposal {proposal_id}"));
+            }
+            if proposal.approvals.len() >= account.threshold as usize {
+                proposal.status = ProposalStatus::Approved;
+            }
+            save_proposal(account_id, proposal.clone());
+        }
+        _ => ic_cdk::trap(&format!("Proposal {proposal_id} is not open")),
+    }
+
+    if proposal.status != ProposalStatus::Approved {
+        return proposal;
+    }
+
+    let raw_transaction =
+        sign_eip1559_transaction(caller, derivation_path(account_id), &proposal.request).await;
+
+    // Another approval may have signed the proposal in the meantime.
+    let mut proposal = self::proposal(account_id, proposal_id);
+    if proposal.status == ProposalStatus::Approved {
+        proposal.status = ProposalStatus::Signed(raw_transaction);
+        save_proposal(account_id, proposal.clone());
+    }
+    proposal
+}
+
+/// Cancels a proposal of the caller that is not signed yet.
+///
+/// A proposal cancelled while it is being signed stays cancelled, its signature is dropped.
+pub fn cancel(caller: &Principal, proposal_ref: ProposalRef) {
+    let ProposalRef {
+        account_id,
+        proposal_id,
+    } = proposal_ref;
+    member_account(caller, account_id);
+    let mut proposal = proposal(account_id, proposal_id);
+
+    if proposal.proposer != *caller {
+        ic_cdk::trap("Only the proposer can cancel a proposal");
+    }
+    if !is_open(&proposal.status) {
+        ic_cdk::trap(&format!("Proposal {proposal_id} is closed"));
+    }
+
+    proposal.status = ProposalStatus::Cancelled;
+    save_proposal(account_id, proposal);
+}
+
+/// Returns a page of the proposals of the account, the most recent closed ones included for auditing.
+pub fn list_proposals(
+    caller: &Principal,
+    account_id: SharedAccountId,
+    cursor: Option<ProposalId>,
+) -> ProposalPage {
+    member_account(caller, account_id);
+
+    let start = match cursor {
+        Some(cursor) => Bound::Excluded((account_id, cursor)),
+        None => Bound::Included((account_id, 0)),
+    };
+    let mut proposals: Vec<Proposal> = read_state(|s| {
+        s.proposal
+            .range((start, Bound::Included((account_id, ProposalId::MAX))))
+            .take(PAGE_SIZE + 1)
+            .map(|(_, p)| p.0)
+            .collect()
+    });
+    let rest = proposals.split_off(proposals.len().min(PAGE_SIZE));
+
+    ProposalPage {
+        next_cursor: proposals.last().map(|p| p.id).filter(|_| !rest.is_empty()),
+        proposals,
+    }
+}
+
+/// Removes the caller from the members of the account and withdraws their approvals of the open proposals.
+///
+/// The threshold is lowered to the number of remaining members if it exceeds it. The last member cannot leave, the
+/// funds of the account would no longer be reachable.
+pub fn leave(caller: &Principal, account_id: SharedAccountId) {
+    let mut account = member_account(caller, account_id);
+    if account.members.len() == 1 {
+        ic_cdk::trap("The last member cannot leave the shared account");
+    }
+
+    account.members.retain(|m| m != caller);
+    account.threshold = account.threshold.min(account.members.len() as u8);
+
+    mutate_state(|s| {
+        s.shared_account_member
+            .remove(&(StoredPrincipal(*caller), account_id));
+        s.shared_account.insert(account_id, Candid(account));
+    });
+
+    for mut proposal in read_state(|s| open_proposals(s, account_id)) {
+        if proposal.status == ProposalStatus::Open {
+            proposal.approvals.retain(|a| a.member != *caller);
+            save_proposal(account_id, proposal);
+        }
+    }
+}
+
+/// Moves the memberships of a principal to another one, e.g. by a recovery, with its proposals and approvals that are
+/// not closed, so that they are not approved twice and can still be cancelled.
+pub fn move_memberships(s: &mut State, from: StoredPrincipal, to: StoredPrincipal) {
+    let account_ids: Vec<SharedAccountId> = s
+        .shared_account_member
+        .range((from, 0)..=(from, SharedAccountId::MAX))
+        .map(|((_, id), _)| id)
+        .collect();
+
+    let replace = |principal: &mut Principal| {
+        if *principal == from.0 {
+            *principal = to.0;
+        }
+    };
+
+    for account_id in account_ids {
+        s.shared_account_member.remove(&(from, account_id));
+        s.shared_account_member.insert((to, account_id), ());
+
+        if let Some(Candid(mut account)) = s.shared_account.get(&account_id) {
+            account.members.iter_mut().for_each(replace);
+            replace(&mut account.created_by);
+            s.shared_account.insert(account_id, Candid(account));
+        }
+
+        for mut proposal in open_proposals(s, account_id) {
+            replace(&mut proposal.proposer);
+            proposal
+                .approvals
+                .iter_mut()
+                .for_each(|a| replace(&mut a.member));
+            s.proposal
+                .insert((account_id, proposal.id), Candid(proposal));
+        }
+    }
+}
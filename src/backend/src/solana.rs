@@ -0,0 +1,90 @@
+use crate::schnorr::{schnorr_pubkey_of, sign_with_schnorr, SchnorrAlgorithm};
+use candid::Principal;
+
+/// Separates the Ed25519 keys of Solana from the keys of the other chains.
+const SOLANA_SCHEMA: u8 = 2;
+
+const PUBKEY_LENGTH: usize = 32;
+
+/// The high bit of the first byte flags a versioned message, the others carry the version.
+const VERSION_PREFIX_MASK: u8 = 0x80;
+
+/// Computes the Ed25519 public key of the specified principal, which is also its Solana address.
+pub async fn sol_pubkey_of(principal: &Principal) -> Vec<u8> {
+    schnorr_pubkey_of(principal, SchnorrAlgorithm::Ed25519, SOLANA_SCHEMA).await
+}
+
+pub fn pubkey_to_address(pubkey: &[u8]) -> String {
+    bs58::encode(pubkey).into_string()
+}
+
+/// Returns the accounts that have to sign a serialized legacy or v0 message, otherwise traps.
+fn required_signers(message: &[u8]) -> Vec<&[u8]> {
+    let header = match message.first() {
+        Some(prefix) if prefix & VERSION_PREFIX_MASK != 0 => {
+            let version = prefix & !VERSION_PREFIX_MASK;
+            if version != 0 {
+                ic_cdk::trap(&format!("Unsupported message version {version}"));
+            }
+            &message[1..]
+        }
+        Some(_) => message,
+        None => ic_cdk::trap("failed to parse the message: empty message"),
+    };
+
+    // The header holds the number of required signatures followed by the counts of read-only accounts.
+    let (num_required_signatures, keys) = match header {
+        [num_required_signatures, _, _, keys @ ..] => (*num_required_signatures as usize, keys),
+        _ => ic_cdk::trap("failed to parse the message: truncated header"),
+    };
+    let (num_keys, keys) = decode_compact_u16(keys);
+
+    if num_required_signatures == 0 || num_required_signatures > num_keys {
+        ic_cdk::trap("failed to parse the message: invalid number of signatures");
+    }
+    if keys.len() < num_keys * PUBKEY_LENGTH {
+        ic_cdk::trap("failed to parse the message: truncated account keys");
+    }
+
+    keys.chunks_exact(PUBKEY_LENGTH)
+        .take(num_required_signatures)
+        .collect()
+}
+
+/// Decodes the variable-length encoding of `u16` used by Solana for the lengths of arrays.
+fn decode_compact_u16(bytes: &[u8]) -> (usize, &[u8]) {
+    let mut value = 0usize;
+    for (i, byte) in bytes.iter().take(3).enumerate() {
+        value |= ((byte & 0x7f) as usize) << (7 * i);
+        if byte & 0x80 == 0 {
+            return (value, &bytes[i + 1..]);
+        }
+    }
+    ic_cdk::trap("failed to parse the message: invalid compact-u16")
+}
+
+/// Signs a serialized message of which the principal is one of the required signers.
+pub async fn sign_message(principal: &Principal, message: Vec<u8>) -> Vec<u8> {
+    let signers: Vec<Vec<u8>> = required_signers(&message)
+        .into_iter()
+        .map(|key| key.to_vec())
+        .collect();
+
+    let pubkey = sol_pubkey_of(principal).await;
+
+    if !signers.contains(&pubkey) {
+        ic_cdk::trap(&format!(
+            "{} is not a required signer of the message",
+            pubkey_to_address(&pubkey)
+        ));
+    }
+
+    sign_with_schnorr(
+        principal,
+        SchnorrAlgorithm::Ed25519,
+        SOLANA_SCHEMA,
+        message,
+        None,
+    )
+    .await
+}
@@ -0,0 +1,340 @@
+//! Key-path only Taproot outputs, see [BIP-86](https://github.com/bitcoin/bips/blob/master/bip-0086.mediawiki).
+
+use crate::schnorr::{
+    schnorr_pubkey_of, sign_with_schnorr, Bip341Aux, SchnorrAlgorithm, SchnorrAux,
+};
+use bech32::hrp::{BC, BCRT, TB};
+use candid::Principal;
+use futures::future::join_all;
+use ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::PrimeField;
+use k256::schnorr::signature::hazmat::PrehashVerifier;
+use k256::schnorr::{Signature, VerifyingKey};
+use k256::{ProjectivePoint, PublicKey, Scalar};
+use serde_bytes::ByteBuf;
+use sha2::{Digest, Sha256};
+use shared::types::taproot::TaprootSignature;
+
+/// Separates the BIP-340 keys of Bitcoin from the keys of the other chains.
+const TAPROOT_SCHEMA: u8 = 3;
+
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+const PSBT_IN_SIGHASH_TYPE: u8 = 0x03;
+
+const SIGHASH_DEFAULT: u32 = 0x00;
+
+/// Bounds the cycles a single call can spend on signatures.
+const MAX_SIGNED_INPUTS: usize = 16;
+
+/// Computes the x-only internal key of the specified principal.
+async fn internal_key_of(principal: &Principal) -> [u8; 32] {
+    let pubkey =
+        schnorr_pubkey_of(principal, SchnorrAlgorithm::Bip340Secp256k1, TAPROOT_SCHEMA).await;
+    // The management canister returns the key in the compressed SEC1 encoding.
+    pubkey[1..]
+        .try_into()
+        .expect("failed to parse the public key as SEC1")
+}
+
+/// Computes a hash as specified by [BIP-340](https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#design).
+fn tagged_hash(tag: &str, data: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.update(tag_hash);
+    hasher.update(tag_hash);
+    for chunk in data {
+        hasher.update(chunk);
+    }
+    hasher.finalize().into()
+}
+
+/// Tweaks the x-only internal key without script path, returns the x-only output key.
+fn output_key(internal_key: &[u8; 32]) -> [u8; 32] {
+    let point = PublicKey::from_sec1_bytes(&[&[0x02], internal_key.as_slice()].concat())
+        .expect("failed to lift the internal key");
+    let tweak = Option::<Scalar>::from(Scalar::from_repr(
+        tagged_hash("TapTweak", &[internal_key]).into(),
+    ))
+    .expect("the tweak should be lower than the curve order");
+
+    let output = (point.to_projective() + ProjectivePoint::GENERATOR * tweak).to_affine();
+    output.to_encoded_point(true).as_bytes()[1..]
+        .try_into()
+        .expect("the x coordinate should be 32 bytes")
+}
+
+/// The script of a segwit v1 output paying to the output key.
+fn p2tr_script(output_key: &[u8; 32]) -> Vec<u8> {
+    [&[0x51, 0x20], output_key.as_slice()].concat()
+}
+
+/// Returns the P2TR address of the specified principal.
+pub async fn taproot_address_of(principal: &Principal, network: BitcoinNetwork) -> String {
+    let output_key = output_key(&internal_key_of(principal).await);
+    let hrp = match network {
+        BitcoinNetwork::Mainnet => BC,
+        BitcoinNetwork::Testnet => TB,
+        BitcoinNetwork::Regtest => BCRT,
+    };
+    bech32::segwit::encode_v1(hrp, &output_key).expect("failed to encode the address")
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read(&mut self, len: usize) -> &'a [u8] {
+        if self.bytes.len() < len {
+            ic_cdk::trap("failed to parse the PSBT: unexpected end of data");
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        head
+    }
+
+    fn read_u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.read(4).try_into().unwrap())
+    }
+
+    fn read_u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.read(8).try_into().unwrap())
+    }
+
+    fn read_compact_size(&mut self) -> usize {
+        let size = match self.read(1)[0] {
+            0xfd => u16::from_le_bytes(self.read(2).try_into().unwrap()) as u64,
+            0xfe => self.read_u32() as u64,
+            0xff => self.read_u64(),
+            n => n as u64,
+        };
+        usize::try_from(size)
+            .ok()
+            .filter(|size| *size <= self.bytes.len())
+            .unwrap_or_else(|| ic_cdk::trap("failed to parse the PSBT: invalid length"))
+    }
+
+    fn read_var_bytes(&mut self) -> &'a [u8] {
+        let len = self.read_compact_size();
+        self.read(len)
+    }
+
+    /// Reads the key-value pairs of a PSBT map up to its separator.
+    fn read_map(&mut self) -> Vec<(&'a [u8], &'a [u8])> {
+        let mut pairs = vec![];
+        loop {
+            let key = self.read_var_bytes();
+            if key.is_empty() {
+                return pairs;
+            }
+            pairs.push((key, self.read_var_bytes()));
+        }
+    }
+}
+
+struct TxIn<'a> {
+    outpoint: &'a [u8],
+    sequence: u32,
+}
+
+/// The fields of an unsigned transaction committed to by the signature hash.
+struct UnsignedTx<'a> {
+    version: u32,
+    inputs: Vec<TxIn<'a>>,
+    outputs: Vec<&'a [u8]>,
+    lock_time: u32,
+}
+
+fn parse_unsigned_tx(bytes: &[u8]) -> UnsignedTx {
+    let mut reader = Reader { bytes };
+    let version = reader.read_u32();
+    let inputs = (0..reader.read_compact_size())
+        .map(|_| {
+            let outpoint = reader.read(36);
+            if !reader.read_var_bytes().is_empty() {
+                ic_cdk::trap("failed to parse the PSBT: the transaction should be unsigned");
+            }
+            TxIn {
+                outpoint,
+                sequence: reader.read_u32(),
+            }
+        })
+        .collect();
+    // The outputs are kept serialized, the way the signature hash commits to them.
+    let outputs = (0..reader.read_compact_size())
+        .map(|_| {
+            let output = reader.bytes;
+            reader.read(8);
+            reader.read_var_bytes();
+            &output[..output.len() - reader.bytes.len()]
+        })
+        .collect();
+    UnsignedTx {
+        version,
+        inputs,
+        outputs,
+        lock_time: reader.read_u32(),
+    }
+}
+
+/// The amount and script of the output spent by an input.
+struct Prevout<'a> {
+    amount: u64,
+    script: &'a [u8],
+}
+
+fn parse_prevout(bytes: &[u8]) -> Prevout {
+    let mut reader = Reader { bytes };
+    Prevout {
+        amount: reader.read_u64(),
+        script: reader.read_var_bytes(),
+    }
+}
+
+/// Computes the signature hash of a key path spend as specified by [BIP-341](https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#common-signature-message).
+fn key_spend_sighash(tx: &UnsignedTx, prevouts: &[Prevout], input_index: u32) -> [u8; 32] {
+    let sha256 = |chunks: &mut dyn Iterator<Item = Vec<u8>>| -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        chunks.for_each(|chunk| hasher.update(chunk));
+        hasher.finalize().into()
+    };
+
+    let sha_prevouts = sha256(&mut tx.inputs.iter().map(|i| i.outpoint.to_vec()));
+    let sha_amounts = sha256(&mut prevouts.iter().map(|p| p.amount.to_le_bytes().to_vec()));
+    let sha_scriptpubkeys = sha256(&mut prevouts.iter().map(|p| {
+        let mut script = encode_compact_size(p.script.len());
+        script.extend_from_slice(p.script);
+        script
+    }));
+    let sha_sequences = sha256(&mut tx.inputs.iter().map(|i| i.sequence.to_le_bytes().to_vec()));
+    let sha_outputs = sha256(&mut tx.outputs.iter().map(|o| o.to_vec()));
+
+    tagged_hash(
+        "TapSighash",
+        &[
+            // The epoch, followed by the sighash type
+            &[0x00, SIGHASH_DEFAULT as u8],
+            &tx.version.to_le_bytes(),
+            &tx.lock_time.to_le_bytes(),
+            &sha_prevouts,
+            &sha_amounts,
+            &sha_scriptpubkeys,
+            &sha_sequences,
+            &sha_outputs,
+            // The spend type: key path without annex
+            &[0x00],
+            &input_index.to_le_bytes(),
+        ],
+    )
+}
+
+fn encode_compact_size(size: usize) -> Vec<u8> {
+    match size {
+        0..=0xfc => vec![size as u8],
+        0xfd..=0xffff => [&[0xfd], (size as u16).to_le_bytes().as_slice()].concat(),
+        0x10000..=0xffff_ffff => [&[0xfe], (size as u32).to_le_bytes().as_slice()].concat(),
+        _ => [&[0xff], (size as u64).to_le_bytes().as_slice()].concat(),
+    }
+}
+
+/// Signs the inputs of a [BIP-174](https://github.com/bitcoin/bips/blob/master/bip-0174.mediawiki) PSBT that spend the P2TR output of the principal.
+pub async fn sign_psbt(principal: &Principal, psbt: &[u8]) -> Vec<TaprootSignature> {
+    let mut reader = Reader { bytes: psbt };
+    if reader.read(PSBT_MAGIC.len()) != PSBT_MAGIC {
+        ic_cdk::trap("failed to parse the PSBT: invalid magic bytes");
+    }
+
+    let unsigned_tx = reader
+        .read_map()
+        .into_iter()
+        .find(|(key, _)| *key == [PSBT_GLOBAL_UNSIGNED_TX])
+        .map(|(_, value)| parse_unsigned_tx(value))
+        .unwrap_or_else(|| ic_cdk::trap("failed to parse the PSBT: missing unsigned transaction"));
+
+    let prevouts: Vec<Prevout> = unsigned_tx
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(index, _)| {
+            let input = reader.read_map();
+            if let Some((_, sighash_type)) =
+                input.iter().find(|(key, _)| *key == [PSBT_IN_SIGHASH_TYPE])
+            {
+                if *sighash_type != SIGHASH_DEFAULT.to_le_bytes() {
+                    ic_cdk::trap(&format!(
+                        "Input {index} should use the default sighash type"
+                    ));
+                }
+            }
+            input
+                .iter()
+                .find(|(key, _)| *key == [PSBT_IN_WITNESS_UTXO])
+                .map(|(_, value)| parse_prevout(value))
+                .unwrap_or_else(|| {
+                    ic_cdk::trap(&format!("Input {index} is missing its witness UTXO"))
+                })
+        })
+        .collect();
+
+    let output_key = output_key(&internal_key_of(principal).await);
+    let script = p2tr_script(&output_key);
+
+    let sighashes: Vec<(u32, [u8; 32])> = prevouts
+        .iter()
+        .enumerate()
+        .filter(|(_, prevout)| prevout.script == script.as_slice())
+        .map(|(index, _)| {
+            let index = index as u32;
+            (index, key_spend_sighash(&unsigned_tx, &prevouts, index))
+        })
+        .collect();
+
+    if sighashes.is_empty() {
+        ic_cdk::trap("No input of the PSBT spends the Taproot output of the caller");
+    }
+    if sighashes.len() > MAX_SIGNED_INPUTS {
+        ic_cdk::trap(&format!(
+            "The number of signed inputs should not exceed {MAX_SIGNED_INPUTS}"
+        ));
+    }
+
+    let aux = SchnorrAux::Bip341(Bip341Aux {
+        merkle_root_hash: ByteBuf::new(),
+    });
+
+    join_all(sighashes.into_iter().map(|(input_index, sighash)| {
+        let aux = aux.clone();
+        async move {
+            let signature = sign_with_schnorr(
+                principal,
+                SchnorrAlgorithm::Bip340Secp256k1,
+                TAPROOT_SCHEMA,
+                sighash.to_vec(),
+                Some(aux),
+            )
+            .await;
+            assert_signs_for(&output_key, &sighash, &signature, input_index);
+            TaprootSignature {
+                input_index,
+                signature: hex::encode(signature),
+            }
+        }
+    }))
+    .await
+}
+
+/// Traps unless the signature verifies against the output key, i.e. unless the management canister applied the BIP-341 tweak.
+fn assert_signs_for(output_key: &[u8; 32], sighash: &[u8; 32], signature: &[u8], input_index: u32) {
+    let verifies = Signature::try_from(signature)
+        .ok()
+        .zip(VerifyingKey::from_bytes(output_key).ok())
+        .is_some_and(|(signature, key)| key.verify_prehash(sighash, &signature).is_ok());
+    if !verifies {
+        ic_cdk::trap(&format!(
+            "The signature of input {input_index} does not verify against the Taproot output key"
+        ));
+    }
+}
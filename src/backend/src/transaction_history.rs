@@ -0,0 +1,208 @@
+use crate::catalogue::trusted_icrc_ledger;
+use crate::icrc_index::{get_account_transactions, is_default_account, TransactionWithId};
+use crate::portfolio::format_amount;
+use crate::{read_state, StoredPrincipal};
+use candid::{Nat, Principal};
+use futures::future::join_all;
+use shared::types::account::IcrcAccount;
+use shared::types::catalogue::CatalogueToken;
+use shared::types::custom_token::{IndexId, LedgerId, Token};
+use shared::types::transaction_history::{
+    HistoryTransaction, LedgerCursor, TransactionDirection, TransactionHistoryCursor,
+    TransactionHistoryError, TransactionHistoryPage,
+};
+
+const MAX_LIMIT: u64 = 50;
+
+/// Returns the direction, the counterparty and the value of the transaction for the principal.
+///
+/// Returns `None` for the transactions that do not move the tokens of the principal, e.g. approvals.
+fn direction(
+    principal: &Principal,
+    TransactionWithId { transaction, .. }: &TransactionWithId,
+) -> Option<(TransactionDirection, Option<IcrcAccount>, Nat)> {
+    if let Some(transfer) = transaction.transfer.as_ref() {
+        let value = transfer.amount.clone();
+        return match (
+            is_default_account(&transfer.from, principal),
+            is_default_account(&transfer.to, principal),
+        ) {
+            (true, true) => Some((TransactionDirection::SelfTransfer, None, value)),
+            (false, true) => Some((
+                TransactionDirection::Incoming,
+                Some(transfer.from.clone()),
+                value,
+            )),
+            (true, false) => Some((
+                TransactionDirection::Outgoing,
+                Some(transfer.to.clone()),
+                value,
+            )),
+            (false, false) => None,
+        };
+    }
+    if let Some(mint) = transaction.mint.as_ref() {
+        if is_default_account(&mint.to, principal) {
+            return Some((TransactionDirection::Incoming, None, mint.amount.clone()));
+        }
+    }
+    if let Some(burn) = transaction.burn.as_ref() {
+        if is_default_account(&burn.from, principal) {
+            return Some((TransactionDirection::Outgoing, None, burn.amount.clone()));
+        }
+    }
+    None
+}
+
+fn history_transaction(
+    principal: &Principal,
+    ledger_id: LedgerId,
+    token: &CatalogueToken,
+    transaction: TransactionWithId,
+) -> Option<HistoryTransaction> {
+    let (direction, counterparty, value) = direction(principal, &transaction)?;
+    Some(HistoryTransaction {
+        ledger_id,
+        id: transaction.id,
+        kind: transaction.transaction.kind,
+        direction,
+        counterparty,
+        symbol: token.symbol.clone(),
+        decimals: token.decimals,
+        amount: format_amount(&value, token.decimals),
+        value,
+        timestamp: transaction.transaction.timestamp,
+    })
+}
+
+/// Returns the transactions of the principal on the enabled custom tokens with an index, most recent first.
+///
+/// Only the tokens of the catalogue are listed, through the index and with the metadata of their catalogue entry,
+/// see `trusted_icrc_ledger`. The indexes are queried in parallel for up to `limit` transactions each, following the
+/// cursor of their ledger, and the most recent ones are kept. A page may have fewer transactions than the limit, the
+/// approvals are left out. A ledger whose index fails is reported once and not listed in the next pages.
+pub async fn history(
+    principal: &Principal,
+    cursor: Option<TransactionHistoryCursor>,
+    limit: u64,
+) -> TransactionHistoryPage {
+    if limit == 0 || limit > MAX_LIMIT {
+        ic_cdk::trap(&format!("The limit should be between 1 and {MAX_LIMIT}"));
+    }
+
+    let indexes: Vec<(LedgerId, IndexId, CatalogueToken)> = read_state(|s| {
+        s.custom_token
+            .get(&StoredPrincipal(*principal))
+            .unwrap_or_default()
+            .0
+    })
+    .into_iter()
+    .filter(|t| t.enabled)
+    .filter_map(|t| match t.token {
+        Token::Icrc(token) => {
+            trusted_icrc_ledger(&token.ledger_id)
+                .ok()
+                .and_then(|catalogue_token| {
+                    catalogue_token
+                        .index_id
+                        .map(|index_id| (token.ledger_id, index_id, catalogue_token))
+                })
+        }
+    })
+    .collect();
+
+    // The ledgers of a cursor are the ones of the first page that still have transactions to list: the tokens added
+    // since then would list transactions more recent than the listed ones.
+    let cursors: Vec<LedgerCursor> = match cursor {
+        None => indexes
+            .iter()
+            .map(|(ledger_id, _, _)| LedgerCursor {
+                ledger_id: *ledger_id,
+                start: None,
+            })
+            .collect(),
+        Some(cursor) => cursor.ledgers,
+    };
+    let cursors: Vec<(LedgerCursor, IndexId, &CatalogueToken)> = cursors
+        .into_iter()
+        .filter_map(|cursor| {
+            indexes
+                .iter()
+                .find(|(ledger_id, _, _)| *ledger_id == cursor.ledger_id)
+                .map(|(_, index_id, token)| (cursor, *index_id, token))
+        })
+        .collect();
+
+    let results = join_all(cursors.iter().map(|(cursor, index_id, _)| async {
+        get_account_transactions(*index_id, principal, cursor.start.clone(), limit)
+            .await
+            .map(|result| result.transactions)
+    }))
+    .await;
+
+    // The transactions of each ledger are most recent first, the most recent of all of them are kept.
+    let mut merged: Vec<(usize, &TransactionWithId)> = results
+        .iter()
+        .enumerate()
+        .filter_map(|(i, result)| result.as_ref().ok().map(|transactions| (i, transactions)))
+        .flat_map(|(i, transactions)| transactions.iter().map(move |t| (i, t)))
+        .collect();
+    merged.sort_by(|(a_ledger, a), (b_ledger, b)| {
+        b.transaction
+            .timestamp
+            .cmp(&a.transaction.timestamp)
+            .then(a_ledger.cmp(b_ledger))
+            .then(b.id.cmp(&a.id))
+    });
+    merged.truncate(limit as usize);
+
+    let mut transactions = vec![];
+    let mut errors = vec![];
+    let mut next_cursors = vec![];
+    for (i, ((cursor, _, token), result)) in cursors.into_iter().zip(results.iter()).enumerate() {
+        // A failing index is left out of the cursor, so that it is not called and reported again with every page.
+        let fetched = match result {
+            Ok(fetched) => fetched,
+            Err(error) => {
+                errors.push(TransactionHistoryError {
+                    ledger_id: cursor.ledger_id,
+                    error: error.clone(),
+                });
+                continue;
+            }
+        };
+
+        let listed: Vec<&TransactionWithId> = merged
+            .iter()
+            .filter(|(ledger, _)| *ledger == i)
+            .map(|(_, t)| *t)
+            .collect();
+        // The ledger is exhausted once the index returned fewer transactions than asked and all of them are listed.
+        if listed.len() < fetched.len() || fetched.len() as u64 == limit {
+            next_cursors.push(LedgerCursor {
+                start: listed.last().map(|t| t.id.clone()).or(cursor.start),
+                ledger_id: cursor.ledger_id,
+            });
+        }
+        transactions.extend(
+            listed
+                .into_iter()
+                .filter_map(|t| history_transaction(principal, cursor.ledger_id, token, t.clone())),
+        );
+    }
+
+    transactions.sort_by(|a, b| {
+        b.timestamp
+            .cmp(&a.timestamp)
+            .then(a.ledger_id.cmp(&b.ledger_id))
+            .then(b.id.cmp(&a.id))
+    });
+
+    TransactionHistoryPage {
+        transactions,
+        errors,
+        next_cursor: (!next_cursors.is_empty()).then_some(TransactionHistoryCursor {
+            ledgers: next_cursors,
+        }),
+    }
+}
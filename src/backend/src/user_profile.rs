@@ -0,0 +1,69 @@
+use crate::{Candid, StoredPrincipal, VMem};
+use ic_stable_structures::StableBTreeMap;
+use shared::types::user_profile::{UpdateUserSettingsRequest, UserProfile, UserSettings};
+use shared::types::TokenVersion;
+
+const MAX_HIDDEN_TOKENS: usize = 100;
+const MAX_PREFERRED_NETWORKS: usize = 20;
+const MAX_ID_LENGTH: usize = 64;
+
+/// Checks the length of the lists and the format of the fiat currency, otherwise traps.
+pub fn assert_valid_settings(settings: &UserSettings) {
+    let assert_ids = |ids: &Option<Vec<String>>, max: usize, name: &str| {
+        if let Some(ids) = ids {
+            if ids.len() > max {
+                ic_cdk::trap(&format!("{name} should not exceed {max} entries"));
+            }
+            if ids.iter().any(|id| id.len() > MAX_ID_LENGTH) {
+                ic_cdk::trap(&format!(
+                    "{name} entries should not exceed {MAX_ID_LENGTH} bytes"
+                ));
+            }
+        }
+    };
+
+    assert_ids(&settings.hidden_tokens, MAX_HIDDEN_TOKENS, "Hidden tokens");
+    assert_ids(
+        &settings.preferred_networks,
+        MAX_PREFERRED_NETWORKS,
+        "Preferred networks",
+    );
+
+    if let Some(currency) = settings.fiat_currency.as_ref() {
+        // ISO 4217 alphabetic code, e.g. "USD"
+        if currency.len() != 3 || !currency.bytes().all(|b| b.is_ascii_uppercase()) {
+            ic_cdk::trap(&format!("Invalid fiat currency {currency}"));
+        }
+    }
+}
+
+pub fn set_user_settings(
+    stored_principal: StoredPrincipal,
+    user_profile: &mut StableBTreeMap<StoredPrincipal, Candid<UserProfile>, VMem>,
+    request: UpdateUserSettingsRequest,
+    now: u64,
+) -> UserProfile {
+    let profile = match user_profile.get(&stored_principal) {
+        None => UserProfile {
+            settings: request.settings,
+            created_timestamp: now,
+            updated_timestamp: now,
+            version: None,
+        },
+        Some(Candid(existing)) => {
+            if existing.get_version() != request.current_user_version {
+                ic_cdk::trap("Version mismatch, user profile update not allowed");
+            }
+            UserProfile {
+                settings: request.settings,
+                updated_timestamp: now,
+                ..existing
+            }
+        }
+    }
+    .clone_with_incremented_version();
+
+    user_profile.insert(stored_principal, Candid(profile.clone()));
+
+    profile
+}
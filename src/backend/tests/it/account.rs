@@ -0,0 +1,193 @@
+use crate::utils::mock::CALLER;
+use crate::utils::pocketic::{query_call, setup};
+use candid::Principal;
+use serde_bytes::ByteBuf;
+use shared::types::account::{
+    decode_icrc_account, encode_icrc_account, icp_account_identifier,
+    is_valid_icp_account_identifier, IcrcAccount,
+};
+
+/// The owner of the test vectors of the ICRC-1 textual encoding.
+const OWNER: &str = "k2t6j-2nvnp-4zjm3-25dtz-6xhaa-c7boj-5gayf-oj3xs-i43lp-teztq-6ae";
+
+/// The account identifier of the default subaccount of the anonymous principal.
+const ANONYMOUS_ACCOUNT_IDENTIFIER: &str =
+    "1c7a48ba6a562aa9eaa2481a9049cdf0433b9738c992d698c31d8abf89cadc79";
+
+fn account(subaccount: Option<[u8; 32]>) -> IcrcAccount {
+    IcrcAccount {
+        owner: Principal::from_text(OWNER).unwrap(),
+        subaccount: subaccount.map(ByteBuf::from),
+    }
+}
+
+fn icrc_vectors() -> Vec<(IcrcAccount, String)> {
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    let mut sequence = [0u8; 32];
+    sequence
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, b)| *b = i as u8 + 1);
+
+    vec![
+        (account(None), OWNER.to_string()),
+        (account(Some(one)), format!("{OWNER}-6cc627i.1")),
+        (
+            account(Some(sequence)),
+            format!(
+                "{OWNER}-dfxgiyy.102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20"
+            ),
+        ),
+    ]
+}
+
+#[test]
+fn test_icrc_account_vectors() {
+    for (account, text) in icrc_vectors() {
+        assert_eq!(encode_icrc_account(&account), Ok(text.clone()));
+        assert_eq!(decode_icrc_account(&text), Ok(account));
+    }
+}
+
+#[test]
+fn test_default_subaccount_is_encoded_as_owner() {
+    assert_eq!(
+        encode_icrc_account(&account(Some([0; 32]))),
+        Ok(OWNER.to_string())
+    );
+}
+
+#[test]
+fn test_subaccount_must_be_32_bytes_long() {
+    let account = IcrcAccount {
+        subaccount: Some(ByteBuf::from(vec![1; 31])),
+        ..account(None)
+    };
+
+    assert!(encode_icrc_account(&account).is_err());
+    assert!(icp_account_identifier(&account).is_err());
+}
+
+#[test]
+fn test_decode_rejects_invalid_icrc_accounts() {
+    for text in [
+        // The checksum does not match.
+        format!("{OWNER}-6cc627j.1"),
+        // The checksum is missing.
+        format!("{OWNER}.1"),
+        // The subaccount has leading zeros.
+        format!("{OWNER}-6cc627i.01"),
+        // The default subaccount must be omitted.
+        format!("{OWNER}-6cc627i.0"),
+        // The subaccount is not lowercase.
+        format!("{OWNER}-dfxgiyy.102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F20"),
+        // The subaccount is longer than 32 bytes.
+        format!("{OWNER}-6cc627i.1{}", "0".repeat(64)),
+        "not-a-principal".to_string(),
+    ] {
+        assert!(decode_icrc_account(&text).is_err(), "{text}");
+    }
+}
+
+#[test]
+fn test_icp_account_identifier() {
+    let anonymous = IcrcAccount {
+        owner: Principal::anonymous(),
+        subaccount: None,
+    };
+
+    assert_eq!(
+        icp_account_identifier(&anonymous).unwrap(),
+        ANONYMOUS_ACCOUNT_IDENTIFIER
+    );
+    assert_eq!(
+        icp_account_identifier(&IcrcAccount {
+            subaccount: Some(ByteBuf::from(vec![0; 32])),
+            ..anonymous
+        })
+        .unwrap(),
+        ANONYMOUS_ACCOUNT_IDENTIFIER
+    );
+    assert_eq!(
+        icp_account_identifier(&account(None)).unwrap(),
+        "051b05839339f89053454a4b9865ea0452a4bffe2b1cd41f4982bad10c1e637c"
+    );
+}
+
+#[test]
+fn test_validate_icp_account_identifier() {
+    assert!(is_valid_icp_account_identifier(
+        ANONYMOUS_ACCOUNT_IDENTIFIER
+    ));
+    assert!(is_valid_icp_account_identifier(
+        &ANONYMOUS_ACCOUNT_IDENTIFIER.to_uppercase()
+    ));
+
+    // The checksum does not match the hash.
+    assert!(!is_valid_icp_account_identifier(
+        "1c7a48bb6a562aa9eaa2481a9049cdf0433b9738c992d698c31d8abf89cadc79"
+    ));
+    assert!(!is_valid_icp_account_identifier(
+        &ANONYMOUS_ACCOUNT_IDENTIFIER[..62]
+    ));
+    assert!(!is_valid_icp_account_identifier(&format!(
+        "{}zz",
+        &ANONYMOUS_ACCOUNT_IDENTIFIER[..62]
+    )));
+}
+
+#[test]
+fn test_account_endpoints() {
+    let pic_setup = setup();
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    for (account, text) in icrc_vectors() {
+        let encoded: String =
+            query_call(&pic_setup, caller, "encode_icrc_account", account.clone()).unwrap();
+        assert_eq!(encoded, text);
+
+        let decoded: IcrcAccount =
+            query_call(&pic_setup, caller, "decode_icrc_account", text.clone()).unwrap();
+        assert_eq!(decoded, account);
+
+        let valid: bool =
+            query_call(&pic_setup, caller, "is_valid_icrc_account", text.clone()).unwrap();
+        assert!(valid);
+    }
+
+    let identifier: String = query_call(
+        &pic_setup,
+        Principal::anonymous(),
+        "icp_account_identifier",
+        IcrcAccount {
+            owner: Principal::anonymous(),
+            subaccount: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(identifier, ANONYMOUS_ACCOUNT_IDENTIFIER);
+
+    let valid: bool = query_call(
+        &pic_setup,
+        caller,
+        "is_valid_icp_account_identifier",
+        identifier,
+    )
+    .unwrap();
+    assert!(valid);
+}
+
+#[test]
+fn test_decode_icrc_account_traps_on_invalid_account() {
+    let pic_setup = setup();
+
+    let result: Result<IcrcAccount, String> = query_call(
+        &pic_setup,
+        Principal::from_text(CALLER).unwrap(),
+        "decode_icrc_account",
+        format!("{OWNER}-6cc627j.1"),
+    );
+
+    assert!(result.unwrap_err().contains("Invalid checksum"));
+}
@@ -0,0 +1,283 @@
+use crate::utils::mock::{CALLER, CALLER_ETH_ADDRESS, SEPOLIA_CHAIN_ID, WEENUS_CONTRACT_ADDRESS};
+use crate::utils::pocketic::{query_call, setup, update_call};
+use candid::{Nat, Principal};
+use pocket_ic::PocketIc;
+use shared::types::allowlist::Allowlist;
+use shared::types::transaction::SignRequest;
+use std::time::Duration;
+
+const RECIPIENT: &str = "0x000000000000000000000000000000000000bEEF";
+
+/// A contract the user did not allowlist.
+const ATTACKER_CONTRACT: &str = "0x000000000000000000000000000000000000dEaD";
+
+const CONFIRMATION_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn sign_request(to: &str, data: Option<String>, nonce: u64) -> SignRequest {
+    SignRequest {
+        chain_id: Nat::from(SEPOLIA_CHAIN_ID),
+        to: to.to_string(),
+        gas: Nat::from(60_000u64),
+        max_fee_per_gas: Nat::from(456u64),
+        max_priority_fee_per_gas: Nat::from(789u64),
+        value: Nat::from(0u64),
+        nonce: Nat::from(nonce),
+        data,
+    }
+}
+
+/// The data of an ERC-20 transfer of one unit to the recipient.
+fn transfer_data(recipient: &str) -> String {
+    format!(
+        "0xa9059cbb{:0>64}{:0>64}",
+        recipient.trim_start_matches("0x").to_lowercase(),
+        "1"
+    )
+}
+
+fn wait_for_confirmation(pic: &PocketIc) {
+    pic.advance_time(CONFIRMATION_PERIOD + Duration::from_secs(1));
+    pic.tick();
+    pic.tick();
+}
+
+#[test]
+fn test_allowlist_mode_only_signs_for_allowlisted_recipients() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let _ = update_call::<()>(&pic_setup, caller, "add_allowlist_address", RECIPIENT);
+    let _ = update_call::<()>(
+        &pic_setup,
+        caller,
+        "add_allowlist_address",
+        WEENUS_CONTRACT_ADDRESS,
+    );
+    let _ = update_call::<()>(&pic_setup, caller, "enable_allowlist_mode", ());
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(RECIPIENT, None, 0),
+    );
+
+    assert!(result.is_ok());
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(WEENUS_CONTRACT_ADDRESS, Some(transfer_data(RECIPIENT)), 1),
+    );
+
+    assert!(result.is_ok());
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(
+            WEENUS_CONTRACT_ADDRESS,
+            Some(transfer_data(CALLER_ETH_ADDRESS)),
+            2,
+        ),
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains(&format!(
+        "Recipient {} is not in the allowlist",
+        CALLER_ETH_ADDRESS.to_lowercase()
+    )));
+
+    let result = update_call::<String>(&pic_setup, caller, "sign_prehash", hex::encode([0u8; 32]));
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Signing a precomputed hash is not allowed in allowlist mode"));
+}
+
+#[test]
+fn test_allowlist_mode_checks_the_destination_of_token_transfers() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let _ = update_call::<()>(&pic_setup, caller, "add_allowlist_address", RECIPIENT);
+    let _ = update_call::<()>(
+        &pic_setup,
+        caller,
+        "add_allowlist_address",
+        WEENUS_CONTRACT_ADDRESS,
+    );
+    let _ = update_call::<()>(&pic_setup, caller, "enable_allowlist_mode", ());
+
+    // The whole balance sent to a contract, disguised as a transfer to an allowlisted recipient.
+    let bypass = SignRequest {
+        value: Nat::from(1_000_000_000_000_000_000u64),
+        ..sign_request(
+            ATTACKER_CONTRACT,
+            Some(format!(
+                "0xa9059cbb{:0>64}{:0>64}",
+                RECIPIENT.trim_start_matches("0x").to_lowercase(),
+                "0"
+            )),
+            0,
+        )
+    };
+
+    let result = update_call::<String>(&pic_setup, caller, "sign_transaction", bypass);
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains(&format!(
+        "Recipient {} is not in the allowlist",
+        ATTACKER_CONTRACT.to_lowercase()
+    )));
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        SignRequest {
+            value: Nat::from(1u64),
+            ..sign_request(WEENUS_CONTRACT_ADDRESS, Some(transfer_data(RECIPIENT)), 0)
+        },
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("A token transfer should not carry a value in allowlist mode"));
+}
+
+#[test]
+fn test_allowlist_mode_checks_the_spender_of_token_approvals() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let _ = update_call::<()>(&pic_setup, caller, "add_allowlist_address", RECIPIENT);
+    let _ = update_call::<()>(
+        &pic_setup,
+        caller,
+        "add_allowlist_address",
+        WEENUS_CONTRACT_ADDRESS,
+    );
+    let _ = update_call::<()>(&pic_setup, caller, "enable_allowlist_mode", ());
+
+    // An approval of the whole balance to a spender the user did not allowlist.
+    let approve_data = format!(
+        "0x095ea7b3{:0>64}{}",
+        ATTACKER_CONTRACT.trim_start_matches("0x").to_lowercase(),
+        "f".repeat(64)
+    );
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(WEENUS_CONTRACT_ADDRESS, Some(approve_data), 0),
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains(&format!(
+        "Spender {} is not in the allowlist",
+        ATTACKER_CONTRACT.to_lowercase()
+    )));
+
+    // A call the allowlist cannot decode, e.g. a contract-specific approval.
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(WEENUS_CONTRACT_ADDRESS, Some("0x12345678".to_string()), 0),
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Only token transfers and approvals can be signed in allowlist mode"));
+
+    let result = update_call::<String>(&pic_setup, caller, "personal_sign", hex::encode(b"hello"));
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Signing a message is not allowed in allowlist mode"));
+}
+
+#[test]
+fn test_allowlist_additions_are_time_locked() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let _ = update_call::<()>(&pic_setup, caller, "enable_allowlist_mode", ());
+    let _ = update_call::<()>(&pic_setup, caller, "add_allowlist_address", RECIPIENT);
+
+    let allowlist = query_call::<Allowlist>(&pic_setup, caller, "get_allowlist", ()).unwrap();
+
+    assert!(allowlist.addresses.is_empty());
+    assert_eq!(allowlist.pending_addresses.len(), 1);
+    assert_eq!(
+        allowlist.pending_addresses[0].address,
+        RECIPIENT.to_lowercase()
+    );
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(RECIPIENT, None, 0),
+    );
+
+    assert!(result.is_err());
+
+    wait_for_confirmation(&pic_setup.0);
+
+    let allowlist = query_call::<Allowlist>(&pic_setup, caller, "get_allowlist", ()).unwrap();
+
+    assert_eq!(allowlist.addresses, vec![RECIPIENT.to_lowercase()]);
+    assert!(allowlist.pending_addresses.is_empty());
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(RECIPIENT, None, 0),
+    );
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_disabling_allowlist_mode_is_time_locked() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let _ = update_call::<()>(&pic_setup, caller, "enable_allowlist_mode", ());
+    let _ = update_call::<()>(&pic_setup, caller, "disable_allowlist_mode", ());
+
+    let allowlist = query_call::<Allowlist>(&pic_setup, caller, "get_allowlist", ()).unwrap();
+
+    assert!(allowlist.enabled);
+    assert!(allowlist.pending_disable_timestamp.is_some());
+
+    wait_for_confirmation(&pic_setup.0);
+
+    let allowlist = query_call::<Allowlist>(&pic_setup, caller, "get_allowlist", ()).unwrap();
+
+    assert!(!allowlist.enabled);
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(CALLER_ETH_ADDRESS, None, 0),
+    );
+
+    assert!(result.is_ok());
+}
@@ -0,0 +1,131 @@
+use crate::utils::mock::{
+    CALLER, CALLER_ETH_ADDRESS, SEPOLIA_CHAIN_ID, WEENUS_CONTRACT_ADDRESS, WEENUS_DECIMALS,
+    WEENUS_SYMBOL,
+};
+use crate::utils::pocketic::{mock_call, setup, setup_evm_rpc, update_call};
+use candid::{Nat, Principal};
+use pocket_ic::PocketIc;
+use shared::types::balance::{EthBalances, TokenBalance};
+use shared::types::token::UserToken;
+
+fn setup_balances() -> ((PocketIc, Principal), Principal) {
+    let pic_setup = setup();
+    let mock = setup_evm_rpc(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let token = UserToken {
+        chain_id: SEPOLIA_CHAIN_ID,
+        contract_address: WEENUS_CONTRACT_ADDRESS.to_string(),
+        decimals: Some(WEENUS_DECIMALS),
+        symbol: Some(WEENUS_SYMBOL.to_string()),
+        version: None,
+    };
+    update_call::<()>(&pic_setup, caller, "add_user_token", token).unwrap();
+
+    mock_call::<()>(
+        &pic_setup.0,
+        mock,
+        "mock_balance",
+        (None::<String>, "0xde0b6b3a7640000".to_string()),
+    );
+    mock_call::<()>(
+        &pic_setup.0,
+        mock,
+        "mock_balance",
+        (
+            Some(WEENUS_CONTRACT_ADDRESS.to_string()),
+            "0x3e8".to_string(),
+        ),
+    );
+
+    (pic_setup, mock)
+}
+
+#[test]
+fn test_eth_balances() {
+    let (pic_setup, _) = setup_balances();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let balances =
+        update_call::<EthBalances>(&pic_setup, caller, "eth_balances", SEPOLIA_CHAIN_ID).unwrap();
+
+    assert_eq!(
+        balances,
+        EthBalances {
+            chain_id: SEPOLIA_CHAIN_ID,
+            address: CALLER_ETH_ADDRESS.to_string(),
+            balances: vec![
+                TokenBalance {
+                    contract_address: None,
+                    balance: Ok(Nat::from(1_000_000_000_000_000_000u64)),
+                },
+                TokenBalance {
+                    contract_address: Some(WEENUS_CONTRACT_ADDRESS.to_string()),
+                    balance: Ok(Nat::from(1_000u64)),
+                },
+            ],
+        }
+    );
+}
+
+#[test]
+fn test_eth_balances_tolerate_a_minority_of_faulty_providers() {
+    let (pic_setup, mock) = setup_balances();
+
+    mock_call::<()>(
+        &pic_setup.0,
+        mock,
+        "mock_faulty_providers",
+        (vec!["EthSepolia(Ankr)".to_string()],),
+    );
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let balances =
+        update_call::<EthBalances>(&pic_setup, caller, "eth_balances", SEPOLIA_CHAIN_ID).unwrap();
+
+    assert_eq!(
+        balances.balances[0].balance,
+        Ok(Nat::from(1_000_000_000_000_000_000u64))
+    );
+    assert_eq!(balances.balances[1].balance, Ok(Nat::from(1_000u64)));
+}
+
+#[test]
+fn test_eth_balances_without_consensus() {
+    let (pic_setup, mock) = setup_balances();
+
+    mock_call::<()>(
+        &pic_setup.0,
+        mock,
+        "mock_faulty_providers",
+        (vec![
+            "EthSepolia(Ankr)".to_string(),
+            "EthSepolia(BlockPi)".to_string(),
+        ],),
+    );
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let balances =
+        update_call::<EthBalances>(&pic_setup, caller, "eth_balances", SEPOLIA_CHAIN_ID).unwrap();
+
+    assert!(balances
+        .balances
+        .iter()
+        .all(|b| b.balance == Err("The providers disagree".to_string())));
+}
+
+#[test]
+fn test_eth_balances_of_unsupported_chain() {
+    let (pic_setup, _) = setup_balances();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<EthBalances>(&pic_setup, caller, "eth_balances", 12345u64);
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("Chain 12345 is not supported"));
+}
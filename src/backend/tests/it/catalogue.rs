@@ -0,0 +1,327 @@
+use crate::utils::mock::CALLER;
+use crate::utils::pocketic::{controller, query_call, query_call_with_args, setup, update_call};
+use candid::Principal;
+use lazy_static::lazy_static;
+use shared::types::catalogue::{
+    CatalogueToken, CatalogueTokenId, CatalogueTokenPage, ListedUserCatalogueToken, Network,
+    RiskFlag, UserCatalogueToken,
+};
+
+const LOGO: &str = "data:image/svg+xml;base64,PHN2Zz48L3N2Zz4=";
+
+lazy_static! {
+    static ref ICRC_TOKEN: CatalogueToken = CatalogueToken {
+        id: CatalogueTokenId::Icrc(Principal::from_text("ddsp7-7iaaa-aaaaq-aacqq-cai").unwrap()),
+        symbol: "CHAT".to_string(),
+        name: "OpenChat".to_string(),
+        decimals: 8,
+        logo_hash: None,
+        index_id: Some(Principal::from_text("dnqcx-eyaaa-aaaaq-aacrq-cai").unwrap()),
+        risk_flags: vec![],
+        enabled_networks: vec![Network::InternetComputer],
+        version: None,
+    };
+    static ref ERC20_TOKEN: CatalogueToken = CatalogueToken {
+        id: CatalogueTokenId::Erc20 {
+            chain_id: 11155111,
+            contract_address: "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238".to_string(),
+        },
+        symbol: "USDC".to_string(),
+        name: "USD Coin".to_string(),
+        decimals: 6,
+        logo_hash: None,
+        index_id: None,
+        risk_flags: vec![RiskFlag::Unverified],
+        enabled_networks: vec![Network::Evm(11155111)],
+        version: None,
+    };
+}
+
+fn list(
+    pic_setup: &(pocket_ic::PocketIc, Principal),
+    network: Network,
+    cursor: Option<String>,
+) -> CatalogueTokenPage {
+    query_call_with_args::<CatalogueTokenPage>(
+        pic_setup,
+        Principal::anonymous(),
+        "list_catalogue_tokens",
+        (network, cursor),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_set_and_list_catalogue_tokens() {
+    let pic_setup = setup();
+
+    let logo_hash =
+        update_call::<String>(&pic_setup, controller(), "set_catalogue_logo", LOGO).unwrap();
+
+    let icrc_token = CatalogueToken {
+        logo_hash: Some(logo_hash.clone()),
+        ..ICRC_TOKEN.clone()
+    };
+
+    for token in [icrc_token.clone(), ERC20_TOKEN.clone()] {
+        let result = update_call::<()>(&pic_setup, controller(), "set_catalogue_token", token);
+
+        assert!(result.is_ok());
+    }
+
+    let page = list(&pic_setup, Network::InternetComputer, None);
+
+    assert_eq!(
+        page.tokens,
+        vec![CatalogueToken {
+            version: Some(1),
+            ..icrc_token
+        }]
+    );
+    assert_eq!(page.next_cursor, None);
+
+    let page = list(&pic_setup, Network::Evm(11155111), None);
+
+    assert_eq!(page.tokens.len(), 1);
+    assert_eq!(page.tokens[0].risk_flags, vec![RiskFlag::Unverified]);
+
+    let logo = query_call::<Option<String>>(
+        &pic_setup,
+        Principal::anonymous(),
+        "get_catalogue_logo",
+        logo_hash,
+    );
+
+    assert_eq!(logo.unwrap(), Some(LOGO.to_string()));
+}
+
+#[test]
+fn test_update_catalogue_token() {
+    let pic_setup = setup();
+
+    let _ = update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_catalogue_token",
+        ICRC_TOKEN.clone(),
+    );
+
+    let result = update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_catalogue_token",
+        ICRC_TOKEN.clone(),
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Version mismatch, catalogue token update not allowed"));
+
+    let update = CatalogueToken {
+        risk_flags: vec![RiskFlag::Deprecated],
+        version: Some(1),
+        ..ICRC_TOKEN.clone()
+    };
+
+    let result = update_call::<()>(&pic_setup, controller(), "set_catalogue_token", update);
+
+    assert!(result.is_ok());
+
+    let page = list(&pic_setup, Network::InternetComputer, None);
+
+    assert_eq!(page.tokens[0].risk_flags, vec![RiskFlag::Deprecated]);
+    assert_eq!(page.tokens[0].version, Some(2));
+
+    let result = update_call::<()>(
+        &pic_setup,
+        controller(),
+        "remove_catalogue_token",
+        ICRC_TOKEN.id.clone(),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(
+        list(&pic_setup, Network::InternetComputer, None).tokens,
+        vec![]
+    );
+}
+
+#[test]
+fn test_cannot_set_catalogue_token_with_unknown_logo() {
+    let pic_setup = setup();
+
+    let token = CatalogueToken {
+        logo_hash: Some("00".repeat(32)),
+        ..ERC20_TOKEN.clone()
+    };
+
+    let result = update_call::<()>(&pic_setup, controller(), "set_catalogue_token", token);
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("Unknown logo"));
+}
+
+#[test]
+fn test_user_cannot_set_catalogue_token() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        caller,
+        "set_catalogue_token",
+        ICRC_TOKEN.clone(),
+    );
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "Caller is not a controller.".to_string()
+    );
+}
+
+#[test]
+fn test_cannot_set_catalogue_token_of_unknown_chain() {
+    let pic_setup = setup();
+
+    let tokens = [
+        CatalogueToken {
+            id: CatalogueTokenId::Erc20 {
+                chain_id: 56,
+                contract_address: "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238".to_string(),
+            },
+            ..ERC20_TOKEN.clone()
+        },
+        CatalogueToken {
+            enabled_networks: vec![Network::Evm(11155111), Network::Evm(56)],
+            ..ERC20_TOKEN.clone()
+        },
+    ];
+
+    for token in tokens {
+        let result = update_call::<()>(&pic_setup, controller(), "set_catalogue_token", token);
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Chain 56 is not supported"));
+    }
+}
+
+fn list_user_catalogue_tokens(
+    pic_setup: &(pocket_ic::PocketIc, Principal),
+) -> Vec<ListedUserCatalogueToken> {
+    query_call(
+        pic_setup,
+        Principal::from_text(CALLER).unwrap(),
+        "list_user_catalogue_tokens",
+        (),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_user_catalogue_token_overrides_catalogue_entry() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_catalogue_token",
+        ERC20_TOKEN.clone(),
+    )
+    .unwrap();
+
+    let user_token = UserCatalogueToken {
+        id: ERC20_TOKEN.id.clone(),
+        enabled: true,
+        symbol: Some("MYUSDC".to_string()),
+        name: None,
+        version: None,
+    };
+    let result = update_call::<()>(
+        &pic_setup,
+        caller,
+        "set_user_catalogue_token",
+        user_token.clone(),
+    );
+
+    assert!(result.is_ok());
+
+    // The catalogue updates are listed, the overridden symbol is kept.
+    update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_catalogue_token",
+        CatalogueToken {
+            name: "USD Coin (Sepolia)".to_string(),
+            version: Some(1),
+            ..ERC20_TOKEN.clone()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        list_user_catalogue_tokens(&pic_setup),
+        vec![ListedUserCatalogueToken {
+            user_token: UserCatalogueToken {
+                version: Some(1),
+                ..user_token
+            },
+            token: Some(CatalogueToken {
+                symbol: "MYUSDC".to_string(),
+                name: "USD Coin (Sepolia)".to_string(),
+                version: Some(2),
+                ..ERC20_TOKEN.clone()
+            }),
+            denylisted: None,
+        }]
+    );
+
+    // The reference outlives the catalogue entry.
+    update_call::<()>(
+        &pic_setup,
+        controller(),
+        "remove_catalogue_token",
+        ERC20_TOKEN.id.clone(),
+    )
+    .unwrap();
+
+    let listed = list_user_catalogue_tokens(&pic_setup);
+
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].token, None);
+
+    update_call::<()>(
+        &pic_setup,
+        caller,
+        "remove_user_catalogue_token",
+        ERC20_TOKEN.id.clone(),
+    )
+    .unwrap();
+
+    assert_eq!(list_user_catalogue_tokens(&pic_setup), vec![]);
+}
+
+#[test]
+fn test_cannot_add_unknown_catalogue_token() {
+    let pic_setup = setup();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        Principal::from_text(CALLER).unwrap(),
+        "set_user_catalogue_token",
+        UserCatalogueToken {
+            id: ICRC_TOKEN.id.clone(),
+            enabled: true,
+            symbol: None,
+            name: None,
+            version: None,
+        },
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("Unknown catalogue token"));
+}
@@ -0,0 +1,161 @@
+use crate::utils::mock::{
+    CALLER, CALLER_ETH_ADDRESS, SEPOLIA_CHAIN_ID, WEENUS_CONTRACT_ADDRESS, WEENUS_DECIMALS,
+    WEENUS_SYMBOL,
+};
+use crate::utils::pocketic::{controller, query_call, setup, update_call};
+use candid::{Nat, Principal};
+use lazy_static::lazy_static;
+use shared::types::chain::{ChainConfig, TransactionType};
+use shared::types::token::UserToken;
+use shared::types::transaction::SignRequest;
+
+const UNKNOWN_CHAIN_ID: u64 = 123456789;
+
+lazy_static! {
+    static ref SEPOLIA_CHAIN: ChainConfig = ChainConfig {
+        chain_id: SEPOLIA_CHAIN_ID,
+        name: "Sepolia".to_string(),
+        native_symbol: "SepoliaETH".to_string(),
+        supported_tx_types: vec![TransactionType::Eip1559],
+        max_gas_limit: 30_000_000,
+        enabled: true,
+    };
+}
+
+fn sign_request(chain_id: u64, gas: u64) -> SignRequest {
+    SignRequest {
+        chain_id: Nat::from(chain_id),
+        to: CALLER_ETH_ADDRESS.to_string(),
+        gas: Nat::from(gas),
+        max_fee_per_gas: Nat::from(456u64),
+        max_priority_fee_per_gas: Nat::from(789u64),
+        value: Nat::from(1u64),
+        nonce: Nat::from(0u64),
+        data: None,
+    }
+}
+
+#[test]
+fn test_list_default_supported_chains() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let results = query_call::<Vec<ChainConfig>>(&pic_setup, caller, "list_supported_chains", ());
+
+    assert!(results.is_ok());
+
+    let chain_ids: Vec<u64> = results.unwrap().iter().map(|c| c.chain_id).collect();
+
+    assert_eq!(chain_ids, vec![1, SEPOLIA_CHAIN_ID]);
+}
+
+#[test]
+fn test_controller_can_disable_chain() {
+    let pic_setup = setup();
+
+    let disabled_chain = ChainConfig {
+        enabled: false,
+        ..SEPOLIA_CHAIN.clone()
+    };
+
+    let result = update_call::<()>(&pic_setup, controller(), "set_chain", disabled_chain);
+
+    assert!(result.is_ok());
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let results = query_call::<Vec<ChainConfig>>(&pic_setup, caller, "list_supported_chains", ());
+
+    assert!(results
+        .unwrap()
+        .iter()
+        .all(|c| c.chain_id != SEPOLIA_CHAIN_ID));
+
+    let sign_result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(SEPOLIA_CHAIN_ID, 123),
+    );
+
+    assert!(sign_result.is_err());
+    assert!(sign_result
+        .unwrap_err()
+        .contains("Chain 11155111 is disabled"));
+}
+
+#[test]
+fn test_user_cannot_set_chain() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(&pic_setup, caller, "set_chain", SEPOLIA_CHAIN.clone());
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "Caller is not a controller.".to_string()
+    );
+}
+
+#[test]
+fn test_cannot_sign_transaction_for_unknown_chain() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(UNKNOWN_CHAIN_ID, 123),
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Chain 123456789 is not supported"));
+}
+
+#[test]
+fn test_cannot_sign_transaction_above_max_gas_limit() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(SEPOLIA_CHAIN_ID, 30_000_001),
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Gas limit should not exceed 30000000 on chain 11155111"));
+}
+
+#[test]
+fn test_cannot_add_user_token_for_unknown_chain() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let token = UserToken {
+        chain_id: UNKNOWN_CHAIN_ID,
+        contract_address: WEENUS_CONTRACT_ADDRESS.to_string(),
+        decimals: Some(WEENUS_DECIMALS),
+        symbol: Some(WEENUS_SYMBOL.to_string()),
+        version: None,
+    };
+
+    let result = update_call::<()>(&pic_setup, caller, "add_user_token", token);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Chain 123456789 is not supported"));
+}
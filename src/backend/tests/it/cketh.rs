@@ -0,0 +1,279 @@
+use crate::utils::mock::{CALLER, SEPOLIA_CHAIN_ID};
+use crate::utils::pocketic::{mock_call, setup, setup_cketh_minter, setup_evm_rpc, update_call};
+use candid::{CandidType, Deserialize, Nat, Principal};
+use shared::types::cketh::{CkEthConversion, CkEthDepositArgs, ConversionState, ConversionStatus};
+use shared::types::transaction::SignRequest;
+
+const ETH_HELPER_CONTRACT_ADDRESS: &str = "0x2D39863d30716aaf2B7fFFd85Dd03Dda2BFC2E38";
+const DEPOSIT_TRANSACTION_HASH: &str =
+    "0x8f1a5c0e1d3b4c7e9a2f6d8b0c4e7a9f1b3d5c7e9a0b2c4d6e8f0a1b3c5d7e9f";
+
+#[derive(CandidType, Deserialize)]
+struct EthTransaction {
+    transaction_hash: String,
+}
+
+#[derive(CandidType, Deserialize)]
+enum TxFinalizedStatus {
+    Success {
+        transaction_hash: String,
+        effective_transaction_fee: Option<Nat>,
+    },
+}
+
+#[derive(CandidType, Deserialize)]
+enum RetrieveEthStatus {
+    TxSent(EthTransaction),
+    TxFinalized(TxFinalizedStatus),
+}
+
+#[test]
+fn test_cketh_deposit_request() {
+    let pic_setup = setup();
+    setup_cketh_minter(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let request = update_call::<SignRequest>(
+        &pic_setup,
+        caller,
+        "cketh_deposit_request",
+        CkEthDepositArgs {
+            chain_id: SEPOLIA_CHAIN_ID,
+            amount: Nat::from(1_000_000_000_000_000u64),
+            nonce: Nat::from(3u64),
+            max_fee_per_gas: Nat::from(456u64),
+            max_priority_fee_per_gas: Nat::from(789u64),
+        },
+    )
+    .unwrap();
+
+    // The principal is encoded as its length, its bytes, then zeros.
+    let principal = caller.as_slice();
+    let encoded_principal = format!("{:02x}{:0<62}", principal.len(), hex::encode(principal));
+
+    assert_eq!(request.to, ETH_HELPER_CONTRACT_ADDRESS);
+    assert_eq!(request.chain_id, Nat::from(SEPOLIA_CHAIN_ID));
+    assert_eq!(request.value, Nat::from(1_000_000_000_000_000u64));
+    assert_eq!(request.nonce, Nat::from(3u64));
+    assert_eq!(request.data, Some(format!("0xb214faa5{encoded_principal}")));
+}
+
+#[test]
+fn test_conversion_status() {
+    let pic_setup = setup();
+    let evm_rpc = setup_evm_rpc(&pic_setup);
+    let minter = setup_cketh_minter(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let conversions = [
+        CkEthConversion::Deposit {
+            chain_id: SEPOLIA_CHAIN_ID,
+            transaction_hash: DEPOSIT_TRANSACTION_HASH.to_string(),
+        },
+        CkEthConversion::Withdrawal {
+            ledger_block_index: 5,
+        },
+        CkEthConversion::Withdrawal {
+            ledger_block_index: 6,
+        },
+        CkEthConversion::Withdrawal {
+            ledger_block_index: 7,
+        },
+    ];
+    for conversion in conversions.iter() {
+        update_call::<()>(&pic_setup, caller, "track_cketh_conversion", conversion).unwrap();
+    }
+
+    mock_call::<()>(
+        &pic_setup.0,
+        evm_rpc,
+        "mock_transaction_receipt",
+        (DEPOSIT_TRANSACTION_HASH.to_string(), "0x1".to_string()),
+    );
+    mock_call::<()>(
+        &pic_setup.0,
+        minter,
+        "mock_retrieve_eth_status",
+        (
+            5u64,
+            RetrieveEthStatus::TxFinalized(TxFinalizedStatus::Success {
+                transaction_hash: "0x01".to_string(),
+                effective_transaction_fee: None,
+            }),
+        ),
+    );
+    mock_call::<()>(
+        &pic_setup.0,
+        minter,
+        "mock_retrieve_eth_status",
+        (
+            6u64,
+            RetrieveEthStatus::TxSent(EthTransaction {
+                transaction_hash: "0x02".to_string(),
+            }),
+        ),
+    );
+
+    let statuses =
+        update_call::<Vec<ConversionStatus>>(&pic_setup, caller, "conversion_status", ()).unwrap();
+
+    let states: Vec<(CkEthConversion, ConversionState)> = statuses
+        .into_iter()
+        .map(|s| (s.conversion.conversion, s.state))
+        .collect();
+
+    // The most recent conversions come first.
+    assert_eq!(
+        states,
+        vec![
+            (conversions[3].clone(), ConversionState::NotFound),
+            (
+                conversions[2].clone(),
+                ConversionState::Sent {
+                    transaction_hash: "0x02".to_string()
+                }
+            ),
+            (
+                conversions[1].clone(),
+                ConversionState::Completed {
+                    transaction_hash: "0x01".to_string()
+                }
+            ),
+            (
+                conversions[0].clone(),
+                ConversionState::Completed {
+                    transaction_hash: DEPOSIT_TRANSACTION_HASH.to_string()
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_pending_deposit() {
+    let pic_setup = setup();
+    setup_evm_rpc(&pic_setup);
+    setup_cketh_minter(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let deposit = CkEthConversion::Deposit {
+        chain_id: SEPOLIA_CHAIN_ID,
+        transaction_hash: DEPOSIT_TRANSACTION_HASH.to_string(),
+    };
+    update_call::<()>(&pic_setup, caller, "track_cketh_conversion", deposit).unwrap();
+
+    let statuses =
+        update_call::<Vec<ConversionStatus>>(&pic_setup, caller, "conversion_status", ()).unwrap();
+
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(statuses[0].state, ConversionState::Pending);
+}
+
+#[test]
+fn test_deposit_status_without_evm_rpc_is_an_error() {
+    let pic_setup = setup();
+    setup_cketh_minter(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let deposit = CkEthConversion::Deposit {
+        chain_id: SEPOLIA_CHAIN_ID,
+        transaction_hash: DEPOSIT_TRANSACTION_HASH.to_string(),
+    };
+    update_call::<()>(&pic_setup, caller, "track_cketh_conversion", deposit).unwrap();
+
+    let statuses =
+        update_call::<Vec<ConversionStatus>>(&pic_setup, caller, "conversion_status", ()).unwrap();
+
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(
+        statuses[0].state,
+        ConversionState::Error("The EVM RPC canister is not configured".to_string())
+    );
+}
+
+#[test]
+fn test_cannot_convert_eth_of_another_chain_than_the_minter() {
+    let pic_setup = setup();
+    setup_cketh_minter(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<SignRequest>(
+        &pic_setup,
+        caller,
+        "cketh_deposit_request",
+        CkEthDepositArgs {
+            chain_id: 1,
+            amount: Nat::from(1_000_000_000_000_000u64),
+            nonce: Nat::from(3u64),
+            max_fee_per_gas: Nat::from(456u64),
+            max_priority_fee_per_gas: Nat::from(789u64),
+        },
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains(&format!(
+        "The ckETH minter converts the ETH of chain {SEPOLIA_CHAIN_ID}, not 1"
+    )));
+
+    let result = update_call::<()>(
+        &pic_setup,
+        caller,
+        "track_cketh_conversion",
+        CkEthConversion::Deposit {
+            chain_id: 1,
+            transaction_hash: DEPOSIT_TRANSACTION_HASH.to_string(),
+        },
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains(&format!(
+        "The ckETH minter converts the ETH of chain {SEPOLIA_CHAIN_ID}, not 1"
+    )));
+}
+
+#[test]
+fn test_cannot_track_invalid_deposit() {
+    let pic_setup = setup();
+    setup_cketh_minter(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        caller,
+        "track_cketh_conversion",
+        CkEthConversion::Deposit {
+            chain_id: SEPOLIA_CHAIN_ID,
+            transaction_hash: "0x1234".to_string(),
+        },
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("Invalid transaction hash"));
+}
+
+#[test]
+fn test_cannot_track_withdrawal_without_minter() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        caller,
+        "track_cketh_conversion",
+        CkEthConversion::Withdrawal {
+            ledger_block_index: 5,
+        },
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("The ckETH minter is not configured"));
+}
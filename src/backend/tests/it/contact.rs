@@ -0,0 +1,201 @@
+use crate::utils::mock::{CALLER, CALLER_ETH_ADDRESS};
+use crate::utils::pocketic::{query_call, setup, update_call};
+use candid::Principal;
+use lazy_static::lazy_static;
+use serde_bytes::ByteBuf;
+use shared::types::contact::{Contact, ContactAddress, IcrcAccount};
+use shared::types::TokenVersion;
+
+lazy_static! {
+    static ref CONTACT: Contact = Contact {
+        label: "Alice".to_string(),
+        addresses: vec![
+            ContactAddress::Ethereum(CALLER_ETH_ADDRESS.to_string()),
+            ContactAddress::Icrc(IcrcAccount {
+                owner: Principal::from_text(CALLER).unwrap(),
+                subaccount: Some(ByteBuf::from([1u8; 32].to_vec())),
+            }),
+            ContactAddress::Bitcoin("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string()),
+            ContactAddress::Bitcoin("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2".to_string()),
+        ],
+        version: None,
+    };
+}
+
+#[test]
+fn test_add_contact() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(&pic_setup, caller, "set_contact", CONTACT.clone());
+
+    assert!(result.is_ok());
+
+    let results = query_call::<Vec<Contact>>(&pic_setup, caller, "list_contacts", ());
+
+    assert_eq!(
+        results.unwrap(),
+        vec![CONTACT.clone_with_incremented_version()]
+    );
+}
+
+#[test]
+fn test_update_contact() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let _ = update_call::<()>(&pic_setup, caller, "set_contact", CONTACT.clone());
+
+    let results = query_call::<Vec<Contact>>(&pic_setup, caller, "list_contacts", ());
+
+    let update_contact = Contact {
+        addresses: vec![ContactAddress::Ethereum(CALLER_ETH_ADDRESS.to_string())],
+        version: results.unwrap()[0].version,
+        ..CONTACT.clone()
+    };
+
+    let update_result =
+        update_call::<()>(&pic_setup, caller, "set_contact", update_contact.clone());
+
+    assert!(update_result.is_ok());
+
+    let updated_results = query_call::<Vec<Contact>>(&pic_setup, caller, "list_contacts", ());
+
+    assert_eq!(
+        updated_results.unwrap(),
+        vec![update_contact.clone_with_incremented_version()]
+    );
+}
+
+#[test]
+fn test_cannot_update_contact_without_version() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let _ = update_call::<()>(&pic_setup, caller, "set_contact", CONTACT.clone());
+
+    let update_result = update_call::<()>(&pic_setup, caller, "set_contact", CONTACT.clone());
+
+    assert!(update_result.is_err());
+    assert!(update_result
+        .unwrap_err()
+        .contains("Version mismatch, contact update not allowed"));
+}
+
+#[test]
+fn test_cannot_add_more_than_the_maximum_number_of_contacts() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    for i in 0..100 {
+        let contact = Contact {
+            label: format!("Contact {i}"),
+            ..CONTACT.clone()
+        };
+        update_call::<()>(&pic_setup, caller, "set_contact", contact).unwrap();
+    }
+
+    let contact = Contact {
+        label: "One too many".to_string(),
+        ..CONTACT.clone()
+    };
+    let result = update_call::<()>(&pic_setup, caller, "set_contact", contact);
+
+    assert!(result
+        .unwrap_err()
+        .contains("Contact list length should not exceed 100"));
+}
+
+#[test]
+fn test_remove_contact() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let _ = update_call::<()>(&pic_setup, caller, "set_contact", CONTACT.clone());
+
+    let remove_result =
+        update_call::<()>(&pic_setup, caller, "remove_contact", CONTACT.label.clone());
+
+    assert!(remove_result.is_ok());
+
+    let results = query_call::<Vec<Contact>>(&pic_setup, caller, "list_contacts", ());
+
+    assert_eq!(results.unwrap().len(), 0);
+}
+
+#[test]
+fn test_cannot_add_contact_with_invalid_addresses() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let invalid_addresses = [
+        (
+            ContactAddress::Ethereum("invalid_address".to_string()),
+            "failed to parse contract address",
+        ),
+        (
+            ContactAddress::Bitcoin("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3".to_string()),
+            "failed to parse bitcoin address",
+        ),
+        (
+            ContactAddress::Icrc(IcrcAccount {
+                owner: caller,
+                subaccount: Some(ByteBuf::from(vec![1u8; 31])),
+            }),
+            "Subaccount should be 32 bytes long",
+        ),
+    ];
+
+    for (address, error) in invalid_addresses {
+        let contact = Contact {
+            addresses: vec![address],
+            ..CONTACT.clone()
+        };
+
+        let result = update_call::<()>(&pic_setup, caller, "set_contact", contact);
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains(error));
+    }
+}
+
+#[test]
+fn test_user_cannot_list_another_user_contacts() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let _ = update_call::<()>(&pic_setup, caller, "set_contact", CONTACT.clone());
+
+    let another_caller =
+        Principal::from_text("yaa3n-twfur-6xz6e-3z7ep-xln56-222kz-w2b2m-y5wqz-vu6kk-s3fdg-lqe")
+            .unwrap();
+
+    let results = query_call::<Vec<Contact>>(&pic_setup, another_caller, "list_contacts", ());
+
+    assert_eq!(results.unwrap().len(), 0);
+}
+
+#[test]
+fn test_anonymous_cannot_add_contact() {
+    let pic_setup = setup();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        Principal::anonymous(),
+        "set_contact",
+        CONTACT.clone(),
+    );
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "Anonymous caller not authorized.".to_string()
+    );
+}
@@ -1,10 +1,16 @@
 use crate::utils::assertion::assert_custom_tokens_eq;
 use crate::utils::mock::CALLER;
-use crate::utils::pocketic::{query_call, setup, update_call};
+use crate::utils::pocketic::{
+    controller, mock_call, query_call, setup, setup_icrc_ledger, update_call,
+};
 use candid::Principal;
 use lazy_static::lazy_static;
-use shared::types::custom_token::{CustomToken, CustomTokenId, IcrcToken, Token};
+use pocket_ic::PocketIc;
+use shared::types::custom_token::{
+    CustomToken, CustomTokenId, IcrcToken, IcrcTokenMetadata, Token,
+};
 use shared::types::TokenVersion;
+use std::time::Duration;
 
 lazy_static! {
     static ref ICRC_TOKEN: IcrcToken = IcrcToken {
@@ -354,3 +360,196 @@ fn test_user_cannot_list_another_custom_tokens() {
 
     assert_eq!(results_tokens.len(), 0);
 }
+
+#[test]
+fn test_cannot_add_custom_token_of_missing_ledger_when_validated() {
+    let pic_setup = setup();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_custom_token_validation",
+        true,
+    );
+
+    assert!(result.is_ok());
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(&pic_setup, caller, "set_custom_token", USER_TOKEN.clone());
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("failed to get the metadata of ledger ddsp7-7iaaa-aaaaq-aacqq-cai"));
+
+    let list_result = query_call::<Vec<CustomToken>>(&pic_setup, caller, "list_custom_tokens", ());
+
+    assert_eq!(list_result.unwrap(), vec![]);
+
+    let metadata_result = query_call::<Option<IcrcTokenMetadata>>(
+        &pic_setup,
+        caller,
+        "get_icrc_token_metadata",
+        ICRC_TOKEN.ledger_id,
+    );
+
+    assert_eq!(metadata_result.unwrap(), None);
+}
+
+#[test]
+fn test_user_cannot_enable_custom_token_validation() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(&pic_setup, caller, "set_custom_token_validation", true);
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "Caller is not a controller.".to_string()
+    );
+}
+
+fn icrc_token(ledger_id: Principal, index_id: Principal) -> CustomToken {
+    CustomToken {
+        token: Token::Icrc(IcrcToken {
+            ledger_id,
+            index_id: Some(index_id),
+        }),
+        enabled: true,
+        version: None,
+    }
+}
+
+fn setup_with_validation() -> (PocketIc, Principal) {
+    let pic_setup = setup();
+
+    update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_custom_token_validation",
+        true,
+    )
+    .unwrap();
+
+    pic_setup
+}
+
+fn cached_metadata(
+    pic_setup: &(PocketIc, Principal),
+    ledger_id: Principal,
+) -> Option<IcrcTokenMetadata> {
+    query_call(
+        pic_setup,
+        Principal::from_text(CALLER).unwrap(),
+        "get_icrc_token_metadata",
+        ledger_id,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_validated_icrc_token_metadata_is_cached() {
+    let pic_setup = setup_with_validation();
+    let ledger_id = setup_icrc_ledger(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        caller,
+        "set_custom_token",
+        icrc_token(ledger_id, ledger_id),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(
+        cached_metadata(&pic_setup, ledger_id).unwrap().index_id,
+        Some(ledger_id)
+    );
+
+    // Another user adding the token hits the cache: the stopped ledger is not called.
+    pic_setup
+        .0
+        .stop_canister(ledger_id, Some(controller()))
+        .unwrap();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        Principal::from_slice(&[7; 29]),
+        "set_custom_token",
+        icrc_token(ledger_id, ledger_id),
+    );
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_cached_icrc_token_metadata_expires() {
+    let pic_setup = setup_with_validation();
+    let ledger_id = setup_icrc_ledger(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    update_call::<()>(
+        &pic_setup,
+        caller,
+        "set_custom_token",
+        icrc_token(ledger_id, ledger_id),
+    )
+    .unwrap();
+
+    pic_setup
+        .0
+        .advance_time(Duration::from_secs(24 * 60 * 60 + 1));
+
+    assert_eq!(cached_metadata(&pic_setup, ledger_id), None);
+}
+
+#[test]
+fn test_cannot_add_icrc_token_with_index_of_another_ledger() {
+    let pic_setup = setup_with_validation();
+    let ledger_id = setup_icrc_ledger(&pic_setup);
+    // An index claiming to belong to the ledger, which designates itself as its index.
+    let index_id = setup_icrc_ledger(&pic_setup);
+    mock_call::<()>(&pic_setup.0, index_id, "mock_ledger_id", (ledger_id,));
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        caller,
+        "set_custom_token",
+        icrc_token(ledger_id, index_id),
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains(&format!(
+        "Index {index_id} does not belong to ledger {ledger_id}"
+    )));
+    assert_eq!(cached_metadata(&pic_setup, ledger_id), None);
+
+    // Once the index of the ledger is cached, it cannot be replaced by another user.
+    update_call::<()>(
+        &pic_setup,
+        caller,
+        "set_custom_token",
+        icrc_token(ledger_id, ledger_id),
+    )
+    .unwrap();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        Principal::from_slice(&[7; 29]),
+        "set_custom_token",
+        icrc_token(ledger_id, index_id),
+    );
+
+    assert!(result.is_err());
+    assert_eq!(
+        cached_metadata(&pic_setup, ledger_id).unwrap().index_id,
+        Some(ledger_id)
+    );
+}
@@ -0,0 +1,54 @@
+use crate::utils::mock::CALLER;
+use crate::utils::pocketic::{controller, query_call, setup, update_call};
+use candid::Principal;
+use shared::types::cycles::{CyclesConsumer, CyclesUsage};
+
+const SIGN_WITH_ECDSA_FEE: u128 = 26_153_846_153;
+
+#[test]
+fn test_top_cycle_consumers() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    for _ in 0..2 {
+        let result = update_call::<String>(
+            &pic_setup,
+            caller,
+            "personal_sign",
+            hex::encode("test message"),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    let results =
+        query_call::<Vec<CyclesConsumer>>(&pic_setup, controller(), "top_cycle_consumers", 10u32);
+
+    assert_eq!(
+        results.unwrap(),
+        vec![CyclesConsumer {
+            principal: caller,
+            usage: CyclesUsage {
+                cycles: 2 * SIGN_WITH_ECDSA_FEE,
+                signatures: 2,
+            },
+        }]
+    );
+}
+
+#[test]
+fn test_user_cannot_list_top_cycle_consumers() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result =
+        query_call::<Vec<CyclesConsumer>>(&pic_setup, caller, "top_cycle_consumers", 10u32);
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "Caller is not a controller.".to_string()
+    );
+}
@@ -0,0 +1,154 @@
+use crate::utils::mock::{
+    CALLER, SEPOLIA_CHAIN_ID, WEENUS_CONTRACT_ADDRESS, WEENUS_DECIMALS, WEENUS_SYMBOL,
+};
+use crate::utils::pocketic::{controller, query_call, setup, update_call};
+use candid::{Nat, Principal};
+use lazy_static::lazy_static;
+use shared::types::denylist::{DenylistEntry, DenylistedId};
+use shared::types::token::{ListedUserToken, UserToken};
+use shared::types::transaction::SignRequest;
+
+const SPENDER: &str = "0x000000000000000000000000000000000000dEaD";
+
+lazy_static! {
+    static ref WEENUS_TOKEN: UserToken = UserToken {
+        chain_id: SEPOLIA_CHAIN_ID,
+        contract_address: WEENUS_CONTRACT_ADDRESS.to_string(),
+        decimals: Some(WEENUS_DECIMALS),
+        symbol: Some(WEENUS_SYMBOL.to_string()),
+        version: None,
+    };
+    static ref WEENUS_ENTRY: DenylistEntry = DenylistEntry {
+        id: DenylistedId::Evm {
+            chain_id: SEPOLIA_CHAIN_ID,
+            address: WEENUS_CONTRACT_ADDRESS.to_lowercase(),
+        },
+        reason: "Lookalike token".to_string(),
+    };
+}
+
+#[test]
+fn test_cannot_add_denylisted_token() {
+    let pic_setup = setup();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_denylist_entry",
+        WEENUS_ENTRY.clone(),
+    );
+
+    assert!(result.is_ok());
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(&pic_setup, caller, "add_user_token", WEENUS_TOKEN.clone());
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Token is denylisted: Lookalike token"));
+
+    let denylist = query_call::<Vec<DenylistEntry>>(&pic_setup, caller, "list_denylist", ());
+
+    assert_eq!(denylist.unwrap(), vec![WEENUS_ENTRY.clone()]);
+}
+
+#[test]
+fn test_list_user_tokens_flags_denylisted_token() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let _ = update_call::<()>(&pic_setup, caller, "add_user_token", WEENUS_TOKEN.clone());
+
+    let _ = update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_denylist_entry",
+        WEENUS_ENTRY.clone(),
+    );
+
+    let tokens =
+        query_call::<Vec<ListedUserToken>>(&pic_setup, caller, "list_user_tokens", ()).unwrap();
+
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].denylisted, Some(true));
+
+    let _ = update_call::<()>(
+        &pic_setup,
+        controller(),
+        "remove_denylist_entry",
+        WEENUS_ENTRY.id.clone(),
+    );
+
+    let tokens =
+        query_call::<Vec<ListedUserToken>>(&pic_setup, caller, "list_user_tokens", ()).unwrap();
+
+    assert_eq!(tokens[0].denylisted, None);
+}
+
+#[test]
+fn test_cannot_sign_approval_to_denylisted_spender() {
+    let pic_setup = setup();
+
+    let _ = update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_denylist_entry",
+        DenylistEntry {
+            id: DenylistedId::Evm {
+                chain_id: SEPOLIA_CHAIN_ID,
+                address: SPENDER.to_string(),
+            },
+            reason: "Drainer".to_string(),
+        },
+    );
+
+    // approve(SPENDER, 2^256 - 1)
+    let data = format!(
+        "0x095ea7b3{:0>64}{}",
+        SPENDER.trim_start_matches("0x").to_lowercase(),
+        "f".repeat(64)
+    );
+
+    let sign_request = SignRequest {
+        chain_id: Nat::from(SEPOLIA_CHAIN_ID),
+        to: WEENUS_CONTRACT_ADDRESS.to_string(),
+        gas: Nat::from(60_000u64),
+        max_fee_per_gas: Nat::from(456u64),
+        max_priority_fee_per_gas: Nat::from(789u64),
+        value: Nat::from(0u64),
+        nonce: Nat::from(0u64),
+        data: Some(data),
+    };
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<String>(&pic_setup, caller, "sign_transaction", sign_request);
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains(
+        "Approvals to 0x000000000000000000000000000000000000dead are not allowed: Drainer"
+    ));
+}
+
+#[test]
+fn test_user_cannot_set_denylist_entry() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        caller,
+        "set_denylist_entry",
+        WEENUS_ENTRY.clone(),
+    );
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "Caller is not a controller.".to_string()
+    );
+}
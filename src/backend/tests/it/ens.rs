@@ -0,0 +1,151 @@
+use crate::utils::mock::{CALLER, CALLER_ETH_ADDRESS};
+use crate::utils::pocketic::{mock_call, setup, setup_evm_rpc, update_call};
+use candid::Principal;
+use ethers_core::utils::keccak256;
+use pocket_ic::PocketIc;
+
+const ENS_REGISTRY_ADDRESS: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+const RESOLVER_ADDRESS: &str = "0x231b0Ee14048e9dCcD1d247744d114a4EB5E8E63";
+const OTHER_ADDRESS: &str = "0x7439E9Bb6D8a84dd3A23fe621A30F95403F87fB9";
+
+/// The namehash of `vitalik.eth`, from the ENS documentation.
+const VITALIK_ETH_NODE: &str = "ee6c4522aab0003e8d14cd40a6af439055fd2577951148c14b6cea9a53475835";
+
+fn namehash(name: &str) -> String {
+    hex::encode(name.rsplit('.').fold([0u8; 32], |node, label| {
+        keccak256([node, keccak256(label.as_bytes())].concat())
+    }))
+}
+
+fn abi_address(address: &str) -> String {
+    format!("0x{:0>64}", address.trim_start_matches("0x").to_lowercase())
+}
+
+fn abi_string(value: &str) -> String {
+    format!(
+        "0x{:0>64x}{:0>64x}{:0<64}",
+        32,
+        value.len(),
+        hex::encode(value)
+    )
+}
+
+fn mock_eth_call(
+    pic: &PocketIc,
+    mock: Principal,
+    to: &str,
+    selector: &str,
+    node: &str,
+    result: String,
+) {
+    mock_call::<()>(
+        pic,
+        mock,
+        "mock_eth_call",
+        (to.to_string(), format!("0x{selector}{node}"), result),
+    );
+}
+
+/// Registers `vitalik.eth` resolving to the address, and the primary name of the caller.
+fn mock_ens(pic: &PocketIc, mock: Principal, address: &str) {
+    let reverse_node = namehash(&format!(
+        "{}.addr.reverse",
+        CALLER_ETH_ADDRESS.trim_start_matches("0x").to_lowercase()
+    ));
+
+    for node in [VITALIK_ETH_NODE.to_string(), reverse_node.clone()] {
+        mock_eth_call(
+            pic,
+            mock,
+            ENS_REGISTRY_ADDRESS,
+            "0178b8bf",
+            &node,
+            abi_address(RESOLVER_ADDRESS),
+        );
+    }
+    mock_eth_call(
+        pic,
+        mock,
+        RESOLVER_ADDRESS,
+        "3b3b57de",
+        VITALIK_ETH_NODE,
+        abi_address(address),
+    );
+    mock_eth_call(
+        pic,
+        mock,
+        RESOLVER_ADDRESS,
+        "691f3431",
+        &reverse_node,
+        abi_string("vitalik.eth"),
+    );
+}
+
+#[test]
+fn test_namehash() {
+    assert_eq!(namehash("vitalik.eth"), VITALIK_ETH_NODE);
+}
+
+#[test]
+fn test_resolve_ens() {
+    let pic_setup = setup();
+    let mock = setup_evm_rpc(&pic_setup);
+
+    mock_ens(&pic_setup.0, mock, CALLER_ETH_ADDRESS);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let address =
+        update_call::<Option<String>>(&pic_setup, caller, "resolve_ens", "Vitalik.eth").unwrap();
+
+    assert_eq!(address, Some(CALLER_ETH_ADDRESS.to_string()));
+
+    let address =
+        update_call::<Option<String>>(&pic_setup, caller, "resolve_ens", "unknown.eth").unwrap();
+
+    assert_eq!(address, None);
+}
+
+#[test]
+fn test_cannot_resolve_invalid_ens_name() {
+    let pic_setup = setup();
+    setup_evm_rpc(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<Option<String>>(&pic_setup, caller, "resolve_ens", "vitalik..eth");
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("Invalid ENS name"));
+}
+
+#[test]
+fn test_reverse_ens() {
+    let pic_setup = setup();
+    let mock = setup_evm_rpc(&pic_setup);
+
+    mock_ens(&pic_setup.0, mock, CALLER_ETH_ADDRESS);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let name = update_call::<Option<String>>(&pic_setup, caller, "reverse_ens", CALLER_ETH_ADDRESS)
+        .unwrap();
+
+    assert_eq!(name, Some("vitalik.eth".to_string()));
+}
+
+#[test]
+fn test_reverse_ens_requires_forward_match() {
+    let pic_setup = setup();
+    let mock = setup_evm_rpc(&pic_setup);
+
+    // The caller claims a name that resolves to another address.
+    mock_ens(&pic_setup.0, mock, OTHER_ADDRESS);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let name = update_call::<Option<String>>(&pic_setup, caller, "reverse_ens", CALLER_ETH_ADDRESS)
+        .unwrap();
+
+    assert_eq!(name, None);
+}
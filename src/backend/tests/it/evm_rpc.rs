@@ -0,0 +1,166 @@
+use crate::utils::mock::{CALLER, CALLER_ETH_ADDRESS, SEPOLIA_CHAIN_ID};
+use crate::utils::pocketic::{mock_call, setup, setup_evm_rpc, update_call};
+use candid::{CandidType, Deserialize, Nat, Principal};
+use shared::types::transaction::{
+    ProviderSendResult, SendTransactionResponse, SendTransactionStatus, SignRequest,
+};
+
+#[derive(CandidType, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(CandidType, Deserialize)]
+enum RpcError {
+    JsonRpcError(JsonRpcError),
+}
+
+#[derive(CandidType, Deserialize)]
+enum SendRawTransactionStatus {
+    Ok(Option<String>),
+    NonceTooLow,
+}
+
+fn sign_request(chain_id: u64) -> SignRequest {
+    SignRequest {
+        chain_id: Nat::from(chain_id),
+        to: CALLER_ETH_ADDRESS.to_string(),
+        gas: Nat::from(21_000u64),
+        max_fee_per_gas: Nat::from(456u64),
+        max_priority_fee_per_gas: Nat::from(789u64),
+        value: Nat::from(1u64),
+        nonce: Nat::from(0u64),
+        data: None,
+    }
+}
+
+fn raw_transactions(pic_setup: &(pocket_ic::PocketIc, Principal), mock: Principal) -> Vec<String> {
+    mock_call(&pic_setup.0, mock, "raw_transactions", ())
+}
+
+#[test]
+fn test_sign_and_send_transaction() {
+    let pic_setup = setup();
+    let mock = setup_evm_rpc(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let response = update_call::<SendTransactionResponse>(
+        &pic_setup,
+        caller,
+        "sign_and_send_transaction",
+        sign_request(SEPOLIA_CHAIN_ID),
+    )
+    .unwrap();
+
+    assert!(response.transaction_hash.starts_with("0x"));
+    assert_eq!(response.transaction_hash.len(), 66);
+    assert_eq!(
+        raw_transactions(&pic_setup, mock),
+        vec![response.raw_transaction]
+    );
+    assert_eq!(response.provider_results.len(), 4);
+    assert!(response
+        .provider_results
+        .iter()
+        .all(|r| r.status == SendTransactionStatus::Ok));
+}
+
+#[test]
+fn test_sign_and_send_transaction_returns_the_result_of_each_provider() {
+    let pic_setup = setup();
+    let mock = setup_evm_rpc(&pic_setup);
+
+    let results: Vec<Result<SendRawTransactionStatus, RpcError>> = vec![
+        Ok(SendRawTransactionStatus::Ok(None)),
+        Ok(SendRawTransactionStatus::NonceTooLow),
+        Err(RpcError::JsonRpcError(JsonRpcError {
+            code: -32000,
+            message: "already known".to_string(),
+        })),
+    ];
+    mock_call::<()>(
+        &pic_setup.0,
+        mock,
+        "mock_send_raw_transaction_results",
+        (results,),
+    );
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let response = update_call::<SendTransactionResponse>(
+        &pic_setup,
+        caller,
+        "sign_and_send_transaction",
+        sign_request(SEPOLIA_CHAIN_ID),
+    )
+    .unwrap();
+
+    assert_eq!(response.provider_results.len(), 3);
+    assert_eq!(
+        response.provider_results[0],
+        ProviderSendResult {
+            provider: "EthSepolia(Ankr)".to_string(),
+            status: SendTransactionStatus::Ok,
+        }
+    );
+    assert_eq!(
+        response.provider_results[1].status,
+        SendTransactionStatus::NonceTooLow
+    );
+    assert!(matches!(
+        &response.provider_results[2].status,
+        SendTransactionStatus::Error(err) if err.contains("already known")
+    ));
+}
+
+#[test]
+fn test_cannot_sign_and_send_transaction_without_evm_rpc_canister() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<SendTransactionResponse>(
+        &pic_setup,
+        caller,
+        "sign_and_send_transaction",
+        sign_request(SEPOLIA_CHAIN_ID),
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("The EVM RPC canister is not configured"));
+}
+
+#[test]
+fn test_cannot_sign_and_send_transaction_on_unsupported_chain() {
+    let pic_setup = setup();
+    setup_evm_rpc(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<SendTransactionResponse>(
+        &pic_setup,
+        caller,
+        "sign_and_send_transaction",
+        sign_request(137),
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Chain 137 is not supported by the EVM RPC canister"));
+}
+
+#[test]
+fn test_user_cannot_set_evm_rpc_canister() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(&pic_setup, caller, "set_evm_rpc_canister", Some(caller));
+
+    assert!(result.is_err());
+}
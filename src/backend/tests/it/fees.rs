@@ -0,0 +1,84 @@
+use crate::utils::mock::{CALLER, SEPOLIA_CHAIN_ID};
+use crate::utils::pocketic::{mock_call, setup, setup_evm_rpc, update_call};
+use candid::{Nat, Principal};
+use pocket_ic::PocketIc;
+use shared::types::fee::{FeeEstimate, FeeSuggestion};
+use std::time::Duration;
+
+fn mock_fee_history(pic: &PocketIc, mock: Principal, base_fee_per_gas: &str) {
+    let fee_history = format!(
+        r#"{{
+            "oldestBlock": "0x10",
+            "baseFeePerGas": ["0x1", "0x2", "0x3", "{base_fee_per_gas}"],
+            "gasUsedRatio": [0.5, 0.5, 0.5],
+            "reward": [["0x1", "0x5", "0xa"], ["0x3", "0x7", "0xc"], ["0x2", "0x6", "0xb"]]
+        }}"#
+    );
+    mock_call::<()>(pic, mock, "mock_fee_history", (fee_history,));
+}
+
+fn estimate(base_fee_per_gas: u64, priority_fee: u64) -> FeeEstimate {
+    FeeEstimate {
+        max_fee_per_gas: Nat::from(2 * base_fee_per_gas + priority_fee),
+        max_priority_fee_per_gas: Nat::from(priority_fee),
+    }
+}
+
+#[test]
+fn test_suggest_fees() {
+    let pic_setup = setup();
+    let mock = setup_evm_rpc(&pic_setup);
+
+    mock_fee_history(&pic_setup.0, mock, "0x3b9aca00");
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let suggestion =
+        update_call::<FeeSuggestion>(&pic_setup, caller, "suggest_fees", SEPOLIA_CHAIN_ID).unwrap();
+
+    assert_eq!(suggestion.chain_id, SEPOLIA_CHAIN_ID);
+    assert_eq!(suggestion.base_fee_per_gas, Nat::from(1_000_000_000u64));
+    assert_eq!(suggestion.low, estimate(1_000_000_000, 2));
+    assert_eq!(suggestion.medium, estimate(1_000_000_000, 6));
+    assert_eq!(suggestion.high, estimate(1_000_000_000, 11));
+}
+
+#[test]
+fn test_suggested_fees_are_cached_briefly() {
+    let pic_setup = setup();
+    let mock = setup_evm_rpc(&pic_setup);
+
+    mock_fee_history(&pic_setup.0, mock, "0x3b9aca00");
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let suggestion =
+        update_call::<FeeSuggestion>(&pic_setup, caller, "suggest_fees", SEPOLIA_CHAIN_ID).unwrap();
+
+    mock_fee_history(&pic_setup.0, mock, "0x77359400");
+
+    let cached_suggestion =
+        update_call::<FeeSuggestion>(&pic_setup, caller, "suggest_fees", SEPOLIA_CHAIN_ID).unwrap();
+
+    assert_eq!(cached_suggestion, suggestion);
+
+    pic_setup.0.advance_time(Duration::from_secs(60));
+
+    let new_suggestion =
+        update_call::<FeeSuggestion>(&pic_setup, caller, "suggest_fees", SEPOLIA_CHAIN_ID).unwrap();
+
+    assert_eq!(new_suggestion.base_fee_per_gas, Nat::from(2_000_000_000u64));
+}
+
+#[test]
+fn test_cannot_suggest_fees_for_unknown_chain() {
+    let pic_setup = setup();
+    setup_evm_rpc(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<FeeSuggestion>(&pic_setup, caller, "suggest_fees", 1234u64);
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("Chain 1234 is not supported"));
+}
@@ -1,6 +1,30 @@
+mod account;
 mod address;
+mod allowlist;
+mod balance;
+mod catalogue;
+mod chain;
+mod cketh;
+mod contact;
 mod custom_token;
+mod cycles;
+mod denylist;
+mod ens;
+mod evm_rpc;
+mod fees;
+mod nonce;
+mod notification;
+mod portfolio;
+mod portfolio_history;
+mod rate_limit;
+mod recovery;
+mod scheduled_transaction;
+mod shared_account;
 mod sign;
+mod solana;
+mod taproot;
 mod token;
+mod transaction_history;
 mod upgrade;
+mod user_profile;
 mod utils;
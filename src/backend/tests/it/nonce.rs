@@ -0,0 +1,181 @@
+use crate::utils::mock::{CALLER, CALLER_ETH_ADDRESS, SEPOLIA_CHAIN_ID};
+use crate::utils::pocketic::{query_call, setup, update_call};
+use candid::{Nat, Principal};
+use shared::types::transaction::SignRequest;
+use std::time::Duration;
+
+fn sign_request(nonce: u64, value: u64, max_fee_per_gas: u64) -> SignRequest {
+    SignRequest {
+        chain_id: Nat::from(SEPOLIA_CHAIN_ID),
+        to: CALLER_ETH_ADDRESS.to_string(),
+        gas: Nat::from(21_000u64),
+        max_fee_per_gas: Nat::from(max_fee_per_gas),
+        max_priority_fee_per_gas: Nat::from(1u64),
+        value: Nat::from(value),
+        nonce: Nat::from(nonce),
+        data: None,
+    }
+}
+
+#[test]
+fn test_next_nonce_hint() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let hint =
+        query_call::<Option<u64>>(&pic_setup, caller, "next_nonce_hint", SEPOLIA_CHAIN_ID).unwrap();
+
+    assert_eq!(hint, None);
+
+    for nonce in [0, 2, 1] {
+        let result = update_call::<String>(
+            &pic_setup,
+            caller,
+            "sign_transaction",
+            sign_request(nonce, 1, 456),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    let hint =
+        query_call::<Option<u64>>(&pic_setup, caller, "next_nonce_hint", SEPOLIA_CHAIN_ID).unwrap();
+
+    assert_eq!(hint, Some(3));
+
+    let hint = query_call::<Option<u64>>(&pic_setup, caller, "next_nonce_hint", 1u64).unwrap();
+
+    assert_eq!(hint, None);
+}
+
+#[test]
+fn test_cannot_sign_conflicting_transactions_with_same_nonce() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(0, 1, 456),
+    );
+
+    assert!(result.is_ok());
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(0, 2, 456),
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains(&format!(
+        "Nonce 0 was already signed for another transaction on chain {SEPOLIA_CHAIN_ID}"
+    )));
+}
+
+#[test]
+fn test_can_replace_transaction_with_higher_fees() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(0, 1, 456),
+    );
+
+    assert!(result.is_ok());
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(0, 1, 912),
+    );
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_cannot_sign_nonce_above_u64() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let mut request = sign_request(0, 1, 456);
+    request.nonce = Nat::from(u64::MAX) + 1u64;
+
+    let result = update_call::<String>(&pic_setup, caller, "sign_transaction", request);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains(&format!("Nonce should not exceed {}", u64::MAX)));
+
+    let hint =
+        query_call::<Option<u64>>(&pic_setup, caller, "next_nonce_hint", SEPOLIA_CHAIN_ID).unwrap();
+
+    assert_eq!(hint, None);
+}
+
+#[test]
+fn test_cannot_sign_nonce_below_tracked_nonces() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    for nonce in 1..=32 {
+        // Each signature takes a signing token, which refills every 10 seconds.
+        pic_setup.0.advance_time(Duration::from_secs(10));
+
+        let result = update_call::<String>(
+            &pic_setup,
+            caller,
+            "sign_transaction",
+            sign_request(nonce, 1, 456),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    pic_setup.0.advance_time(Duration::from_secs(10));
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(0, 1, 456),
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains(&format!(
+        "Nonce 0 is below the nonces tracked on chain {SEPOLIA_CHAIN_ID}"
+    )));
+}
+
+#[test]
+fn test_no_next_nonce_hint_after_highest_nonce() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_transaction",
+        sign_request(u64::MAX, 1, 456),
+    );
+
+    assert!(result.is_ok());
+
+    let hint =
+        query_call::<Option<u64>>(&pic_setup, caller, "next_nonce_hint", SEPOLIA_CHAIN_ID).unwrap();
+
+    assert_eq!(hint, None);
+}
@@ -0,0 +1,205 @@
+use crate::utils::mock::CALLER;
+use crate::utils::pocketic::{
+    add_to_catalogue, mock_call, query_call, setup, setup_icrc_ledger, update_call,
+};
+use candid::{Nat, Principal};
+use pocket_ic::PocketIc;
+use shared::types::account::IcrcAccount;
+use shared::types::custom_token::{CustomToken, IcrcToken, Token};
+use shared::types::notification::{Notification, NotificationId, NotificationPage};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+const SENDER: &str = "2vxsx-fae";
+
+fn account(owner: &str) -> IcrcAccount {
+    IcrcAccount {
+        owner: Principal::from_text(owner).unwrap(),
+        subaccount: None,
+    }
+}
+
+/// Installs a ledger that is its own index, lists it in the catalogue, adds it as a custom token and opts the caller
+/// in.
+fn setup_notifications() -> ((PocketIc, Principal), Principal) {
+    let pic_setup = setup();
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let ledger_id = setup_icrc_ledger(&pic_setup);
+    add_to_catalogue(&pic_setup, ledger_id, Some(ledger_id));
+    let token = CustomToken {
+        token: Token::Icrc(IcrcToken {
+            ledger_id,
+            index_id: Some(ledger_id),
+        }),
+        enabled: true,
+        version: None,
+    };
+    update_call::<()>(&pic_setup, caller, "set_custom_token", token).unwrap();
+    update_call::<()>(&pic_setup, caller, "enable_notifications", ()).unwrap();
+
+    (pic_setup, ledger_id)
+}
+
+fn wait_for_poll(pic: &PocketIc) {
+    pic.advance_time(POLL_INTERVAL + Duration::from_secs(1));
+    for _ in 0..5 {
+        pic.tick();
+    }
+}
+
+fn transfer(pic: &PocketIc, ledger_id: Principal, from: &str, to: &str, amount: u64) -> Nat {
+    mock_call(
+        pic,
+        ledger_id,
+        "mock_transfer",
+        (account(from), account(to), Nat::from(amount)),
+    )
+}
+
+fn list_notifications(
+    pic_setup: &(PocketIc, Principal),
+    cursor: Option<NotificationId>,
+) -> NotificationPage {
+    query_call(
+        pic_setup,
+        Principal::from_text(CALLER).unwrap(),
+        "list_notifications",
+        cursor,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_incoming_transfers_are_notified() {
+    let (pic_setup, ledger_id) = setup_notifications();
+    let pic = &pic_setup.0;
+
+    // The transactions before the first poll are not notified.
+    transfer(pic, ledger_id, SENDER, CALLER, 100);
+    wait_for_poll(pic);
+
+    let transfer_id = transfer(pic, ledger_id, SENDER, CALLER, 200);
+    let mint_id: Nat = mock_call(
+        pic,
+        ledger_id,
+        "mock_mint",
+        (account(CALLER), Nat::from(300u64)),
+    );
+    // The outgoing transfers are not notified.
+    transfer(pic, ledger_id, CALLER, SENDER, 50);
+    wait_for_poll(pic);
+
+    let page = list_notifications(&pic_setup, None);
+
+    assert_eq!(page.next_cursor, None);
+    assert_eq!(page.notifications.len(), 2);
+    let timestamp = |n: &Notification| n.timestamp;
+    assert_eq!(
+        page.notifications,
+        vec![
+            Notification {
+                id: 1,
+                ledger_id,
+                transaction_id: mint_id,
+                from: None,
+                amount: Nat::from(300u64),
+                timestamp: timestamp(&page.notifications[0]),
+                read: false,
+            },
+            Notification {
+                id: 0,
+                ledger_id,
+                transaction_id: transfer_id,
+                from: Some(account(SENDER)),
+                amount: Nat::from(200u64),
+                timestamp: timestamp(&page.notifications[1]),
+                read: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_first_transfer_after_an_empty_poll_is_notified() {
+    let (pic_setup, ledger_id) = setup_notifications();
+    let pic = &pic_setup.0;
+
+    wait_for_poll(pic);
+    transfer(pic, ledger_id, SENDER, CALLER, 100);
+    wait_for_poll(pic);
+
+    assert_eq!(list_notifications(&pic_setup, None).notifications.len(), 1);
+}
+
+#[test]
+fn test_mark_notifications_read() {
+    let (pic_setup, ledger_id) = setup_notifications();
+    let pic = &pic_setup.0;
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    wait_for_poll(pic);
+    transfer(pic, ledger_id, SENDER, CALLER, 100);
+    transfer(pic, ledger_id, SENDER, CALLER, 200);
+    wait_for_poll(pic);
+
+    update_call::<()>(
+        &pic_setup,
+        caller,
+        "mark_notifications_read",
+        vec![0u64, 42],
+    )
+    .unwrap();
+
+    let read: Vec<(NotificationId, bool)> = list_notifications(&pic_setup, None)
+        .notifications
+        .iter()
+        .map(|n| (n.id, n.read))
+        .collect();
+    assert_eq!(read, vec![(1, false), (0, true)]);
+}
+
+#[test]
+fn test_list_notifications_is_paginated() {
+    let (pic_setup, ledger_id) = setup_notifications();
+    let pic = &pic_setup.0;
+
+    wait_for_poll(pic);
+    for _ in 0..2 {
+        for amount in 1..=15 {
+            transfer(pic, ledger_id, SENDER, CALLER, amount);
+        }
+        wait_for_poll(pic);
+    }
+
+    let first = list_notifications(&pic_setup, None);
+    assert_eq!(first.notifications.len(), 20);
+    assert_eq!(first.notifications[0].id, 29);
+    assert_eq!(first.next_cursor, Some(10));
+
+    let second = list_notifications(&pic_setup, first.next_cursor);
+    let ids: Vec<NotificationId> = second.notifications.iter().map(|n| n.id).collect();
+    assert_eq!(ids, (0..10).rev().collect::<Vec<_>>());
+    assert_eq!(second.next_cursor, None);
+}
+
+#[test]
+fn test_disable_notifications_deletes_them() {
+    let (pic_setup, ledger_id) = setup_notifications();
+    let pic = &pic_setup.0;
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    wait_for_poll(pic);
+    transfer(pic, ledger_id, SENDER, CALLER, 100);
+    wait_for_poll(pic);
+    assert_eq!(list_notifications(&pic_setup, None).notifications.len(), 1);
+
+    update_call::<()>(&pic_setup, caller, "disable_notifications", ()).unwrap();
+    transfer(pic, ledger_id, SENDER, CALLER, 200);
+    wait_for_poll(pic);
+
+    let enabled: bool = query_call(&pic_setup, caller, "is_notifications_enabled", ()).unwrap();
+    assert!(!enabled);
+    assert_eq!(list_notifications(&pic_setup, None).notifications, vec![]);
+}
@@ -0,0 +1,204 @@
+use crate::utils::mock::{
+    CALLER, SEPOLIA_CHAIN_ID, WEENUS_CONTRACT_ADDRESS, WEENUS_DECIMALS, WEENUS_SYMBOL,
+};
+use crate::utils::pocketic::{
+    add_to_catalogue, mock_call, setup, setup_evm_rpc, setup_icrc_ledger, update_call,
+};
+use candid::{Nat, Principal};
+use pocket_ic::PocketIc;
+use shared::types::account::IcrcAccount;
+use shared::types::custom_token::{CustomToken, IcrcToken, Token};
+use shared::types::portfolio::{Portfolio, PortfolioBalance, PortfolioToken};
+use shared::types::token::UserToken;
+
+/// A ledger that is not installed.
+const MISSING_LEDGER_ID: &str = "ddsp7-7iaaa-aaaaq-aacqq-cai";
+
+/// A ledger that is not in the catalogue.
+const UNLISTED_LEDGER_ID: &str = "mxzaz-hqaaa-aaaar-qaada-cai";
+
+fn add_custom_token(pic_setup: &(PocketIc, Principal), ledger_id: Principal, enabled: bool) {
+    let token = CustomToken {
+        token: Token::Icrc(IcrcToken {
+            ledger_id,
+            index_id: None,
+        }),
+        enabled,
+        version: None,
+    };
+    update_call::<()>(
+        pic_setup,
+        Principal::from_text(CALLER).unwrap(),
+        "set_custom_token",
+        token,
+    )
+    .unwrap();
+}
+
+fn setup_icrc_balance(pic_setup: &(PocketIc, Principal), balance: u64) -> Principal {
+    let ledger_id = setup_icrc_ledger(pic_setup);
+    add_to_catalogue(pic_setup, ledger_id, None);
+    add_custom_token(pic_setup, ledger_id, true);
+
+    let account = IcrcAccount {
+        owner: Principal::from_text(CALLER).unwrap(),
+        subaccount: None,
+    };
+    mock_call::<()>(
+        &pic_setup.0,
+        ledger_id,
+        "mock_balance",
+        (account, Nat::from(balance)),
+    );
+
+    ledger_id
+}
+
+#[test]
+fn test_get_portfolio() {
+    let pic_setup = setup();
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let ledger_id = setup_icrc_balance(&pic_setup, 150_000_000);
+
+    let mock = setup_evm_rpc(&pic_setup);
+    let token = UserToken {
+        chain_id: SEPOLIA_CHAIN_ID,
+        contract_address: WEENUS_CONTRACT_ADDRESS.to_string(),
+        decimals: Some(WEENUS_DECIMALS),
+        symbol: Some(WEENUS_SYMBOL.to_string()),
+        version: None,
+    };
+    update_call::<()>(&pic_setup, caller, "add_user_token", token).unwrap();
+    mock_call::<()>(
+        &pic_setup.0,
+        mock,
+        "mock_balance",
+        (None::<String>, "0xde0b6b3a7640000".to_string()),
+    );
+    mock_call::<()>(
+        &pic_setup.0,
+        mock,
+        "mock_balance",
+        (
+            Some(WEENUS_CONTRACT_ADDRESS.to_string()),
+            "0x1bc16d674ec80001".to_string(),
+        ),
+    );
+
+    let portfolio = update_call::<Portfolio>(&pic_setup, caller, "get_portfolio", ()).unwrap();
+
+    assert_eq!(portfolio.errors, vec![]);
+    assert!(portfolio.balances.contains(&PortfolioBalance {
+        token: PortfolioToken::Icrc { ledger_id },
+        symbol: Some("MCK".to_string()),
+        decimals: 8,
+        balance: Nat::from(150_000_000u64),
+        amount: "1.5".to_string(),
+    }));
+    assert!(portfolio.balances.contains(&PortfolioBalance {
+        token: PortfolioToken::Evm {
+            chain_id: SEPOLIA_CHAIN_ID,
+            contract_address: None,
+        },
+        symbol: Some("SepoliaETH".to_string()),
+        decimals: 18,
+        balance: Nat::from(1_000_000_000_000_000_000u64),
+        amount: "1".to_string(),
+    }));
+    assert!(portfolio.balances.contains(&PortfolioBalance {
+        token: PortfolioToken::Evm {
+            chain_id: SEPOLIA_CHAIN_ID,
+            contract_address: Some(WEENUS_CONTRACT_ADDRESS.to_string()),
+        },
+        symbol: Some(WEENUS_SYMBOL.to_string()),
+        decimals: WEENUS_DECIMALS,
+        balance: Nat::from(2_000_000_000_000_000_001u64),
+        amount: "2.000000000000000001".to_string(),
+    }));
+}
+
+#[test]
+fn test_get_portfolio_reports_errors_per_source() {
+    let pic_setup = setup();
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let ledger_id = setup_icrc_balance(&pic_setup, 42);
+    let missing_ledger_id = Principal::from_text(MISSING_LEDGER_ID).unwrap();
+    add_to_catalogue(&pic_setup, missing_ledger_id, None);
+    add_custom_token(&pic_setup, missing_ledger_id, true);
+    let unlisted_ledger_id = Principal::from_text(UNLISTED_LEDGER_ID).unwrap();
+    add_custom_token(&pic_setup, unlisted_ledger_id, true);
+
+    let portfolio = update_call::<Portfolio>(&pic_setup, caller, "get_portfolio", ()).unwrap();
+
+    assert_eq!(
+        portfolio.balances,
+        vec![PortfolioBalance {
+            token: PortfolioToken::Icrc { ledger_id },
+            symbol: Some("MCK".to_string()),
+            decimals: 8,
+            balance: Nat::from(42u64),
+            amount: "0.00000042".to_string(),
+        }]
+    );
+
+    let missing_ledger_error = portfolio
+        .errors
+        .iter()
+        .find(|e| {
+            e.token
+                == PortfolioToken::Icrc {
+                    ledger_id: missing_ledger_id,
+                }
+        })
+        .expect("the missing ledger should be reported");
+    assert!(missing_ledger_error
+        .error
+        .contains("failed to get the balance on ledger"));
+
+    let unlisted_ledger_error = portfolio
+        .errors
+        .iter()
+        .find(|e| {
+            e.token
+                == PortfolioToken::Icrc {
+                    ledger_id: unlisted_ledger_id,
+                }
+        })
+        .expect("the unlisted ledger should be reported");
+    assert_eq!(
+        unlisted_ledger_error.error,
+        format!("Ledger {unlisted_ledger_id} is not in the catalogue")
+    );
+
+    // The EVM RPC canister is not configured.
+    assert!(portfolio
+        .errors
+        .iter()
+        .filter(|e| matches!(e.token, PortfolioToken::Evm { .. }))
+        .all(|e| e.error == "The EVM RPC canister is not configured"));
+}
+
+#[test]
+fn test_get_portfolio_skips_disabled_custom_tokens() {
+    let pic_setup = setup();
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    add_custom_token(
+        &pic_setup,
+        Principal::from_text(MISSING_LEDGER_ID).unwrap(),
+        false,
+    );
+
+    let portfolio = update_call::<Portfolio>(&pic_setup, caller, "get_portfolio", ()).unwrap();
+
+    assert!(portfolio
+        .balances
+        .iter()
+        .all(|b| matches!(b.token, PortfolioToken::Evm { .. })));
+    assert!(portfolio
+        .errors
+        .iter()
+        .all(|e| matches!(e.token, PortfolioToken::Evm { .. })));
+}
@@ -0,0 +1,189 @@
+use crate::utils::mock::CALLER;
+use crate::utils::pocketic::{
+    add_to_catalogue, mock_call, query_call_with_args, setup, setup_icrc_ledger, update_call,
+};
+use candid::{Nat, Principal};
+use pocket_ic::PocketIc;
+use shared::types::account::IcrcAccount;
+use shared::types::custom_token::{CustomToken, IcrcToken, Token};
+use shared::types::portfolio::{Portfolio, PortfolioSnapshot, PortfolioToken, SnapshotBalance};
+use std::time::Duration;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// More users than the snapshots of one slice of a run.
+const USERS: u8 = 25;
+
+fn wait_for_snapshots(pic: &PocketIc) {
+    pic.advance_time(DAY + Duration::from_secs(1));
+    for _ in 0..20 {
+        pic.tick();
+    }
+}
+
+fn portfolio_history(
+    pic_setup: &(PocketIc, Principal),
+    caller: Principal,
+) -> Vec<PortfolioSnapshot> {
+    query_call_with_args(
+        pic_setup,
+        caller,
+        "get_portfolio_history",
+        (0u64, u64::MAX, 100u64),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_daily_snapshot_of_opted_in_users() {
+    let pic_setup = setup();
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let ledger_id = setup_icrc_ledger(&pic_setup);
+    add_to_catalogue(&pic_setup, ledger_id, None);
+    let token = CustomToken {
+        token: Token::Icrc(IcrcToken {
+            ledger_id,
+            index_id: None,
+        }),
+        enabled: true,
+        version: None,
+    };
+    update_call::<()>(&pic_setup, caller, "set_custom_token", token).unwrap();
+    let account = IcrcAccount {
+        owner: caller,
+        subaccount: None,
+    };
+    mock_call::<()>(
+        &pic_setup.0,
+        ledger_id,
+        "mock_balance",
+        (account, Nat::from(42u64)),
+    );
+
+    update_call::<()>(&pic_setup, caller, "enable_portfolio_history", ()).unwrap();
+
+    wait_for_snapshots(&pic_setup.0);
+
+    let history = portfolio_history(&pic_setup, caller);
+
+    assert_eq!(history.len(), 1);
+    assert_eq!(
+        history[0].balances,
+        vec![SnapshotBalance {
+            token: PortfolioToken::Icrc { ledger_id },
+            decimals: 8,
+            balance: Nat::from(42u64),
+        }]
+    );
+    // The EVM RPC canister is not configured.
+    assert!(history[0]
+        .failed_tokens
+        .iter()
+        .all(|t| matches!(t, PortfolioToken::Evm { .. })));
+}
+
+#[test]
+fn test_snapshots_of_all_opted_in_users_are_taken_in_slices() {
+    let pic_setup = setup();
+
+    let users: Vec<Principal> = (1..=USERS)
+        .map(|i| Principal::from_slice(&[i, 1]))
+        .collect();
+    for user in users.iter() {
+        update_call::<()>(&pic_setup, *user, "enable_portfolio_history", ()).unwrap();
+    }
+    let not_opted_in = Principal::from_text(CALLER).unwrap();
+
+    wait_for_snapshots(&pic_setup.0);
+
+    for user in users {
+        assert_eq!(portfolio_history(&pic_setup, user).len(), 1);
+    }
+    assert_eq!(portfolio_history(&pic_setup, not_opted_in), vec![]);
+}
+
+#[test]
+fn test_disable_portfolio_history_deletes_the_history() {
+    let pic_setup = setup();
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    update_call::<()>(&pic_setup, caller, "enable_portfolio_history", ()).unwrap();
+    wait_for_snapshots(&pic_setup.0);
+    assert_eq!(portfolio_history(&pic_setup, caller).len(), 1);
+
+    update_call::<()>(&pic_setup, caller, "disable_portfolio_history", ()).unwrap();
+
+    let enabled: bool =
+        query_call_with_args(&pic_setup, caller, "is_portfolio_history_enabled", ()).unwrap();
+    assert!(!enabled);
+    assert_eq!(portfolio_history(&pic_setup, caller), vec![]);
+
+    wait_for_snapshots(&pic_setup.0);
+    assert_eq!(portfolio_history(&pic_setup, caller), vec![]);
+}
+
+#[test]
+fn test_get_portfolio_history_filters_by_period() {
+    let pic_setup = setup();
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    update_call::<()>(&pic_setup, caller, "enable_portfolio_history", ()).unwrap();
+    wait_for_snapshots(&pic_setup.0);
+    wait_for_snapshots(&pic_setup.0);
+
+    let history = portfolio_history(&pic_setup, caller);
+    assert_eq!(history.len(), 2);
+
+    let from = history[1].timestamp;
+    let recent: Vec<PortfolioSnapshot> = query_call_with_args(
+        &pic_setup,
+        caller,
+        "get_portfolio_history",
+        (from, u64::MAX, 100u64),
+    )
+    .unwrap();
+    assert_eq!(recent, vec![history[1].clone()]);
+
+    let oldest: Vec<PortfolioSnapshot> = query_call_with_args(
+        &pic_setup,
+        caller,
+        "get_portfolio_history",
+        (0u64, u64::MAX, 1u64),
+    )
+    .unwrap();
+    assert_eq!(oldest, vec![history[0].clone()]);
+
+    let result: Result<Vec<PortfolioSnapshot>, String> = query_call_with_args(
+        &pic_setup,
+        caller,
+        "get_portfolio_history",
+        (from, from - 1, 100u64),
+    );
+    assert!(result
+        .unwrap_err()
+        .contains("The start of the period should not be after its end"));
+
+    let result: Result<Vec<PortfolioSnapshot>, String> = query_call_with_args(
+        &pic_setup,
+        caller,
+        "get_portfolio_history",
+        (0u64, u64::MAX, 101u64),
+    );
+    assert!(result
+        .unwrap_err()
+        .contains("The limit should be between 1 and 100"));
+}
+
+#[test]
+fn test_only_the_canister_takes_snapshots() {
+    let pic_setup = setup();
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<Portfolio>(&pic_setup, caller, "portfolio_snapshot_of", caller);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Caller is not the canister itself."));
+}
@@ -0,0 +1,139 @@
+use crate::utils::mock::{CALLER, SEPOLIA_CHAIN_ID};
+use crate::utils::pocketic::{
+    controller, mock_call, query_call, setup, setup_evm_rpc, update_call,
+};
+use candid::Principal;
+use serde_bytes::ByteBuf;
+use shared::http::{HttpRequest, HttpResponse};
+use shared::types::balance::EthBalances;
+use shared::types::rate_limit::RateLimitConfig;
+
+#[test]
+fn test_signing_calls_are_rate_limited() {
+    let pic_setup = setup();
+
+    let rate_limit = RateLimitConfig {
+        capacity: 2,
+        refill_interval_secs: 3600,
+    };
+
+    let result = update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_signing_rate_limit",
+        rate_limit,
+    );
+
+    assert!(result.is_ok());
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    for _ in 0..2 {
+        let sign_result = update_call::<String>(
+            &pic_setup,
+            caller,
+            "personal_sign",
+            hex::encode("test message"),
+        );
+
+        assert!(sign_result.is_ok());
+    }
+
+    let sign_result =
+        update_call::<String>(&pic_setup, caller, "sign_prehash", hex::encode([0u8; 32]));
+
+    assert!(sign_result.is_err());
+    assert_eq!(
+        sign_result.unwrap_err(),
+        "Signing rate limit exceeded.".to_string()
+    );
+
+    let metrics = query_call::<HttpResponse>(
+        &pic_setup,
+        caller,
+        "http_request",
+        HttpRequest {
+            method: "GET".to_string(),
+            url: "/metrics".to_string(),
+            headers: vec![],
+            body: ByteBuf::new(),
+        },
+    )
+    .unwrap();
+
+    assert!(String::from_utf8(metrics.body.into_vec())
+        .unwrap()
+        .contains("ic_eth_wallet_signing_rate_limit_hits 1"));
+}
+
+#[test]
+fn test_user_cannot_set_signing_rate_limit() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(
+        &pic_setup,
+        caller,
+        "set_signing_rate_limit",
+        RateLimitConfig {
+            capacity: 1000,
+            refill_interval_secs: 1,
+        },
+    );
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "Caller is not a controller.".to_string()
+    );
+}
+
+#[test]
+fn test_external_calls_are_rate_limited() {
+    let pic_setup = setup();
+    let mock = setup_evm_rpc(&pic_setup);
+
+    mock_call::<()>(
+        &pic_setup.0,
+        mock,
+        "mock_balance",
+        (None::<String>, "0x0".to_string()),
+    );
+
+    let result = update_call::<()>(
+        &pic_setup,
+        controller(),
+        "set_external_call_rate_limit",
+        RateLimitConfig {
+            capacity: 1,
+            refill_interval_secs: 3600,
+        },
+    );
+
+    assert!(result.is_ok());
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<EthBalances>(&pic_setup, caller, "eth_balances", SEPOLIA_CHAIN_ID);
+
+    assert!(result.is_ok());
+
+    let result = update_call::<EthBalances>(&pic_setup, caller, "eth_balances", SEPOLIA_CHAIN_ID);
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "External call rate limit exceeded.".to_string()
+    );
+
+    // The signing calls have their own bucket.
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "personal_sign",
+        hex::encode("test message"),
+    );
+
+    assert!(result.is_ok());
+}
@@ -0,0 +1,349 @@
+use crate::utils::mock::{CALLER, CALLER_ETH_ADDRESS};
+use crate::utils::pocketic::{query_call, setup, setup_evm_rpc, update_call};
+use candid::Principal;
+use lazy_static::lazy_static;
+use shared::types::allowlist::Allowlist;
+use shared::types::portfolio::{Portfolio, PortfolioToken};
+use shared::types::recovery::{ApproveRecoveryRequest, GuardianConfig, RecoveryRequest};
+use shared::types::shared_account::{CreateSharedAccountRequest, SharedAccount};
+use std::time::Duration;
+
+const RECIPIENT: &str = "0x000000000000000000000000000000000000bEEF";
+
+const RECOVERY_TIME_LOCK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+lazy_static! {
+    static ref GUARDIANS: Vec<Principal> = vec![
+        Principal::from_text("tujls-xtgeu-jb7uz-z4x33-c7ao3-lpecx-ronz6-bxnnw-3gern-tly54-jqe")
+            .unwrap(),
+        Principal::from_text("me55w-zox66-mw5di-b7c7m-sobti-lmhgc-t3umd-u7mvv-dcro2-zvmon-bqe")
+            .unwrap(),
+    ];
+    static ref NEW_PRINCIPAL: Principal =
+        Principal::from_text("nrajg-aewzt-wd72p-3thfu-mh4ne-wi2h7-4bdun-vnibu-2uq3b-pauzp-fae")
+            .unwrap();
+}
+
+fn approve_request() -> ApproveRecoveryRequest {
+    ApproveRecoveryRequest {
+        user: Principal::from_text(CALLER).unwrap(),
+        new_principal: *NEW_PRINCIPAL,
+    }
+}
+
+fn setup_guardians(pic_setup: &(pocket_ic::PocketIc, Principal)) {
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<()>(
+        pic_setup,
+        caller,
+        "set_guardians",
+        GuardianConfig {
+            guardians: GUARDIANS.clone(),
+            threshold: 2,
+        },
+    );
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_guardians_recover_keys() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    setup_guardians(&pic_setup);
+
+    for guardian in GUARDIANS.iter() {
+        let result =
+            update_call::<()>(&pic_setup, *guardian, "approve_recovery", approve_request());
+
+        assert!(result.is_ok());
+    }
+
+    let recovery = query_call::<Option<RecoveryRequest>>(
+        &pic_setup,
+        *NEW_PRINCIPAL,
+        "get_recovery_request",
+        caller,
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(recovery.approvals, GUARDIANS.clone());
+    assert!(recovery.unlock_timestamp.is_some());
+
+    let result = update_call::<()>(&pic_setup, *NEW_PRINCIPAL, "complete_recovery", caller);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("The recovery is still time-locked"));
+
+    pic_setup.0.advance_time(RECOVERY_TIME_LOCK);
+
+    let result = update_call::<()>(&pic_setup, *NEW_PRINCIPAL, "complete_recovery", caller);
+
+    assert!(result.is_ok());
+
+    let address = update_call::<String>(&pic_setup, *NEW_PRINCIPAL, "caller_eth_address", ());
+
+    assert_eq!(address.unwrap(), CALLER_ETH_ADDRESS.to_string());
+
+    let result = update_call::<String>(&pic_setup, caller, "caller_eth_address", ());
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("The keys of the caller were recovered by"));
+}
+
+#[test]
+fn test_user_cancels_recovery() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    setup_guardians(&pic_setup);
+
+    for guardian in GUARDIANS.iter() {
+        let _ = update_call::<()>(&pic_setup, *guardian, "approve_recovery", approve_request());
+    }
+
+    let result = update_call::<()>(&pic_setup, caller, "cancel_recovery", ());
+
+    assert!(result.is_ok());
+
+    pic_setup.0.advance_time(RECOVERY_TIME_LOCK);
+
+    let result = update_call::<()>(&pic_setup, *NEW_PRINCIPAL, "complete_recovery", caller);
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("No recovery of"));
+}
+
+#[test]
+fn test_recovery_requires_threshold() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    setup_guardians(&pic_setup);
+
+    let _ = update_call::<()>(
+        &pic_setup,
+        GUARDIANS[0],
+        "approve_recovery",
+        approve_request(),
+    );
+
+    pic_setup.0.advance_time(RECOVERY_TIME_LOCK);
+
+    let result = update_call::<()>(&pic_setup, *NEW_PRINCIPAL, "complete_recovery", caller);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("The recovery has not been approved by enough guardians"));
+}
+
+#[test]
+fn test_non_guardian_cannot_approve_recovery() {
+    let pic_setup = setup();
+
+    setup_guardians(&pic_setup);
+
+    let result = update_call::<()>(
+        &pic_setup,
+        *NEW_PRINCIPAL,
+        "approve_recovery",
+        approve_request(),
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("Caller is not a guardian of"));
+}
+
+#[test]
+fn test_recovery_moves_the_state_of_the_user() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    setup_guardians(&pic_setup);
+
+    let _ = update_call::<()>(&pic_setup, caller, "add_allowlist_address", RECIPIENT);
+    let _ = update_call::<()>(&pic_setup, caller, "enable_allowlist_mode", ());
+
+    for guardian in GUARDIANS.iter() {
+        let _ = update_call::<()>(&pic_setup, *guardian, "approve_recovery", approve_request());
+    }
+
+    pic_setup.0.advance_time(RECOVERY_TIME_LOCK);
+
+    let result = update_call::<()>(&pic_setup, *NEW_PRINCIPAL, "complete_recovery", caller);
+
+    assert!(result.is_ok());
+
+    let allowlist =
+        query_call::<Allowlist>(&pic_setup, *NEW_PRINCIPAL, "get_allowlist", ()).unwrap();
+
+    assert!(allowlist.enabled);
+    assert_eq!(allowlist.addresses, vec![RECIPIENT.to_string()]);
+
+    let guardians =
+        query_call::<Option<GuardianConfig>>(&pic_setup, *NEW_PRINCIPAL, "get_guardians", ())
+            .unwrap();
+
+    assert_eq!(guardians.unwrap().guardians, GUARDIANS.clone());
+
+    let allowlist = query_call::<Allowlist>(&pic_setup, caller, "get_allowlist", ()).unwrap();
+
+    assert!(!allowlist.enabled);
+    assert!(allowlist.addresses.is_empty());
+}
+
+#[test]
+fn test_recovery_moves_the_shared_accounts_of_the_user() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    setup_guardians(&pic_setup);
+
+    let account = update_call::<SharedAccount>(
+        &pic_setup,
+        caller,
+        "create_shared_account",
+        CreateSharedAccountRequest {
+            name: "Operations".to_string(),
+            members: vec![caller, GUARDIANS[0]],
+            threshold: 2,
+        },
+    )
+    .unwrap();
+
+    for guardian in GUARDIANS.iter() {
+        let _ = update_call::<()>(&pic_setup, *guardian, "approve_recovery", approve_request());
+    }
+
+    pic_setup.0.advance_time(RECOVERY_TIME_LOCK);
+
+    let result = update_call::<()>(&pic_setup, *NEW_PRINCIPAL, "complete_recovery", caller);
+
+    assert!(result.is_ok());
+
+    let accounts =
+        query_call::<Vec<SharedAccount>>(&pic_setup, *NEW_PRINCIPAL, "list_shared_accounts", ())
+            .unwrap();
+
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].members, vec![*NEW_PRINCIPAL, GUARDIANS[0]]);
+
+    let accounts =
+        query_call::<Vec<SharedAccount>>(&pic_setup, caller, "list_shared_accounts", ()).unwrap();
+
+    assert!(accounts.is_empty());
+
+    let result =
+        update_call::<String>(&pic_setup, caller, "shared_account_eth_address", account.id);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("The keys of the caller were recovered by"));
+}
+
+#[test]
+fn test_member_of_a_shared_account_cannot_complete_recovery() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    setup_guardians(&pic_setup);
+
+    for guardian in GUARDIANS.iter() {
+        let _ = update_call::<()>(&pic_setup, *guardian, "approve_recovery", approve_request());
+    }
+
+    let _ = update_call::<SharedAccount>(
+        &pic_setup,
+        GUARDIANS[0],
+        "create_shared_account",
+        CreateSharedAccountRequest {
+            name: "Operations".to_string(),
+            members: vec![GUARDIANS[0], *NEW_PRINCIPAL],
+            threshold: 1,
+        },
+    );
+
+    pic_setup.0.advance_time(RECOVERY_TIME_LOCK);
+
+    let result = update_call::<()>(&pic_setup, *NEW_PRINCIPAL, "complete_recovery", caller);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("The caller already holds keys or state"));
+}
+
+#[test]
+fn test_new_principal_with_state_cannot_complete_recovery() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    setup_guardians(&pic_setup);
+
+    for guardian in GUARDIANS.iter() {
+        let _ = update_call::<()>(&pic_setup, *guardian, "approve_recovery", approve_request());
+    }
+
+    let _ = update_call::<()>(
+        &pic_setup,
+        *NEW_PRINCIPAL,
+        "add_allowlist_address",
+        RECIPIENT,
+    );
+
+    pic_setup.0.advance_time(RECOVERY_TIME_LOCK);
+
+    let result = update_call::<()>(&pic_setup, *NEW_PRINCIPAL, "complete_recovery", caller);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("The caller already holds keys or state"));
+}
+
+#[test]
+fn test_portfolio_of_recovered_principal_reports_the_recovery() {
+    let pic_setup = setup();
+    setup_evm_rpc(&pic_setup);
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    setup_guardians(&pic_setup);
+
+    for guardian in GUARDIANS.iter() {
+        let _ = update_call::<()>(&pic_setup, *guardian, "approve_recovery", approve_request());
+    }
+
+    pic_setup.0.advance_time(RECOVERY_TIME_LOCK);
+
+    let _ = update_call::<()>(&pic_setup, *NEW_PRINCIPAL, "complete_recovery", caller);
+
+    let portfolio = update_call::<Portfolio>(&pic_setup, caller, "get_portfolio", ()).unwrap();
+
+    let evm_errors: Vec<_> = portfolio
+        .errors
+        .iter()
+        .filter(|e| matches!(e.token, PortfolioToken::Evm { .. }))
+        .collect();
+
+    assert!(!evm_errors.is_empty());
+    assert!(evm_errors
+        .iter()
+        .all(|e| e.error.contains("The keys of the caller were recovered by")));
+}
@@ -0,0 +1,186 @@
+use crate::utils::mock::{CALLER, CALLER_ETH_ADDRESS, SEPOLIA_CHAIN_ID};
+use crate::utils::pocketic::{query_call, setup, update_call, update_call_with_args};
+use candid::{Nat, Principal};
+use pocket_ic::PocketIc;
+use shared::types::scheduled_transaction::{
+    ScheduleTransactionRequest, ScheduledTransaction, ScheduledTransactionStatus,
+};
+use shared::types::transaction::SignRequest;
+use std::time::{Duration, UNIX_EPOCH};
+
+const DELAY: Duration = Duration::from_secs(60 * 60);
+
+fn schedule_request(pic: &PocketIc) -> ScheduleTransactionRequest {
+    let now = pic.get_time().duration_since(UNIX_EPOCH).unwrap();
+
+    ScheduleTransactionRequest {
+        request: SignRequest {
+            chain_id: Nat::from(SEPOLIA_CHAIN_ID),
+            to: CALLER_ETH_ADDRESS.to_string(),
+            gas: Nat::from(21_000u64),
+            max_fee_per_gas: Nat::from(456u64),
+            max_priority_fee_per_gas: Nat::from(789u64),
+            value: Nat::from(1u64),
+            nonce: Nat::from(0u64),
+            data: None,
+        },
+        release_timestamp: (now + DELAY).as_nanos() as u64,
+    }
+}
+
+fn wait_for_release(pic: &PocketIc) {
+    pic.advance_time(DELAY + Duration::from_secs(1));
+    // The timer, the call of the canister to itself and the signature.
+    for _ in 0..10 {
+        pic.tick();
+    }
+}
+
+#[test]
+fn test_scheduled_transaction_is_signed_after_release() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let id = update_call::<u64>(
+        &pic_setup,
+        caller,
+        "schedule_transaction",
+        schedule_request(&pic_setup.0),
+    )
+    .unwrap();
+
+    let signed = query_call::<Option<String>>(&pic_setup, caller, "get_signed_transaction", id);
+
+    assert_eq!(signed.unwrap(), None);
+
+    wait_for_release(&pic_setup.0);
+
+    let signed =
+        query_call::<Option<String>>(&pic_setup, caller, "get_signed_transaction", id).unwrap();
+
+    assert!(signed.unwrap().starts_with("0x02"));
+}
+
+#[test]
+fn test_scheduled_transaction_fails_if_no_longer_allowed_at_release() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let id = update_call::<u64>(
+        &pic_setup,
+        caller,
+        "schedule_transaction",
+        schedule_request(&pic_setup.0),
+    )
+    .unwrap();
+
+    // The recipient is not allowlisted.
+    update_call::<()>(&pic_setup, caller, "enable_allowlist_mode", ()).unwrap();
+
+    wait_for_release(&pic_setup.0);
+
+    let transactions = query_call::<Vec<ScheduledTransaction>>(
+        &pic_setup,
+        caller,
+        "list_scheduled_transactions",
+        (),
+    )
+    .unwrap();
+
+    match &transactions[0].status {
+        ScheduledTransactionStatus::Failed { reason } => {
+            assert!(reason.contains("is not in the allowlist"))
+        }
+        status => panic!("unexpected status {status:?}"),
+    }
+
+    let signed = query_call::<Option<String>>(&pic_setup, caller, "get_signed_transaction", id);
+
+    assert_eq!(signed.unwrap(), None);
+}
+
+#[test]
+fn test_only_the_canister_signs_scheduled_transactions() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call_with_args::<String>(
+        &pic_setup,
+        caller,
+        "sign_scheduled_transaction",
+        (caller, schedule_request(&pic_setup.0).request),
+    );
+
+    assert_eq!(
+        result.unwrap_err(),
+        "Caller is not the canister itself.".to_string()
+    );
+}
+
+#[test]
+fn test_cancel_scheduled_transaction() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let id = update_call::<u64>(
+        &pic_setup,
+        caller,
+        "schedule_transaction",
+        schedule_request(&pic_setup.0),
+    )
+    .unwrap();
+
+    let result = update_call::<()>(&pic_setup, caller, "cancel_scheduled_transaction", id);
+
+    assert!(result.is_ok());
+
+    wait_for_release(&pic_setup.0);
+
+    let transactions = query_call::<Vec<ScheduledTransaction>>(
+        &pic_setup,
+        caller,
+        "list_scheduled_transactions",
+        (),
+    )
+    .unwrap();
+
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(
+        transactions[0].status,
+        ScheduledTransactionStatus::Cancelled
+    );
+
+    let signed = query_call::<Option<String>>(&pic_setup, caller, "get_signed_transaction", id);
+
+    assert_eq!(signed.unwrap(), None);
+
+    let result = update_call::<()>(&pic_setup, caller, "cancel_scheduled_transaction", id);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains(&format!("Transaction {id} is not pending")));
+}
+
+#[test]
+fn test_cannot_schedule_transaction_in_the_past() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let request = ScheduleTransactionRequest {
+        release_timestamp: 0,
+        ..schedule_request(&pic_setup.0)
+    };
+
+    let result = update_call::<u64>(&pic_setup, caller, "schedule_transaction", request);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Release timestamp should be in the future"));
+}
@@ -0,0 +1,328 @@
+use crate::utils::mock::{CALLER, CALLER_ETH_ADDRESS, SEPOLIA_CHAIN_ID};
+use crate::utils::pocketic::{query_call, query_call_with_args, setup, update_call};
+use candid::{Nat, Principal};
+use lazy_static::lazy_static;
+use shared::types::shared_account::{
+    CreateSharedAccountRequest, Proposal, ProposalPage, ProposalRef, ProposalStatus,
+    ProposeTransactionRequest, SharedAccount,
+};
+use shared::types::transaction::SignRequest;
+use std::time::Duration;
+
+lazy_static! {
+    static ref MEMBER: Principal =
+        Principal::from_text("nrajg-aewzt-wd72p-3thfu-mh4ne-wi2h7-4bdun-vnibu-2uq3b-pauzp-fae")
+            .unwrap();
+    static ref OUTSIDER: Principal =
+        Principal::from_text("tujls-xtgeu-jb7uz-z4x33-c7ao3-lpecx-ronz6-bxnnw-3gern-tly54-jqe")
+            .unwrap();
+}
+
+fn create_account(pic_setup: &(pocket_ic::PocketIc, Principal)) -> SharedAccount {
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    update_call::<SharedAccount>(
+        pic_setup,
+        caller,
+        "create_shared_account",
+        CreateSharedAccountRequest {
+            name: "Operations".to_string(),
+            members: vec![caller, *MEMBER],
+            threshold: 2,
+        },
+    )
+    .unwrap()
+}
+
+fn propose_request(account: &SharedAccount) -> ProposeTransactionRequest {
+    ProposeTransactionRequest {
+        account_id: account.id,
+        request: SignRequest {
+            chain_id: Nat::from(SEPOLIA_CHAIN_ID),
+            to: CALLER_ETH_ADDRESS.to_string(),
+            gas: Nat::from(21_000u64),
+            max_fee_per_gas: Nat::from(456u64),
+            max_priority_fee_per_gas: Nat::from(789u64),
+            value: Nat::from(1u64),
+            nonce: Nat::from(0u64),
+            data: None,
+        },
+    }
+}
+
+#[test]
+fn test_shared_account_has_its_own_address() {
+    let pic_setup = setup();
+
+    let account = create_account(&pic_setup);
+
+    let address = update_call::<String>(
+        &pic_setup,
+        *MEMBER,
+        "shared_account_eth_address",
+        account.id,
+    )
+    .unwrap();
+
+    assert_ne!(address, CALLER_ETH_ADDRESS.to_string());
+
+    let accounts =
+        query_call::<Vec<SharedAccount>>(&pic_setup, *MEMBER, "list_shared_accounts", ()).unwrap();
+
+    assert_eq!(accounts, vec![account]);
+}
+
+#[test]
+fn test_proposal_is_signed_once_threshold_is_reached() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let account = create_account(&pic_setup);
+
+    let proposal_id = update_call::<u64>(
+        &pic_setup,
+        caller,
+        "propose_transaction",
+        propose_request(&account),
+    )
+    .unwrap();
+
+    let proposals = query_call_with_args::<ProposalPage>(
+        &pic_setup,
+        *MEMBER,
+        "list_proposals",
+        (account.id, None::<u64>),
+    )
+    .unwrap()
+    .proposals;
+
+    assert_eq!(proposals.len(), 1);
+    assert_eq!(proposals[0].status, ProposalStatus::Open);
+    assert_eq!(proposals[0].approvals.len(), 1);
+
+    let proposal_ref = ProposalRef {
+        account_id: account.id,
+        proposal_id,
+    };
+
+    let result = update_call::<Proposal>(&pic_setup, caller, "approve_proposal", proposal_ref);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains(&format!("Caller already approved proposal {proposal_id}")));
+
+    let proposal =
+        update_call::<Proposal>(&pic_setup, *MEMBER, "approve_proposal", proposal_ref).unwrap();
+
+    assert_eq!(proposal.approvals.len(), 2);
+    match proposal.status {
+        ProposalStatus::Signed(raw_transaction) => assert!(raw_transaction.starts_with("0x02")),
+        status => panic!("unexpected status {status:?}"),
+    }
+
+    let result = update_call::<Proposal>(&pic_setup, *OUTSIDER, "approve_proposal", proposal_ref);
+
+    assert!(result.is_err());
+
+    let result = update_call::<Proposal>(&pic_setup, caller, "approve_proposal", proposal_ref);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains(&format!("Proposal {proposal_id} is not open")));
+}
+
+#[test]
+fn test_shared_accounts_created_per_principal_are_capped() {
+    let pic_setup = setup();
+
+    for _ in 0..10 {
+        create_account(&pic_setup);
+    }
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<SharedAccount>(
+        &pic_setup,
+        caller,
+        "create_shared_account",
+        CreateSharedAccountRequest {
+            name: "Operations".to_string(),
+            members: vec![caller, *MEMBER],
+            threshold: 2,
+        },
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Shared accounts created by the caller should not exceed 10"));
+
+    let accounts =
+        query_call::<Vec<SharedAccount>>(&pic_setup, *MEMBER, "list_shared_accounts", ()).unwrap();
+
+    assert_eq!(accounts.len(), 10);
+}
+
+#[test]
+fn test_cancel_proposal() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let account = create_account(&pic_setup);
+
+    let proposal_id = update_call::<u64>(
+        &pic_setup,
+        caller,
+        "propose_transaction",
+        propose_request(&account),
+    )
+    .unwrap();
+
+    let proposal_ref = ProposalRef {
+        account_id: account.id,
+        proposal_id,
+    };
+
+    let result = update_call::<()>(&pic_setup, caller, "cancel_proposal", proposal_ref);
+
+    assert!(result.is_ok());
+
+    let result = update_call::<Proposal>(&pic_setup, *MEMBER, "approve_proposal", proposal_ref);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains(&format!("Proposal {proposal_id} is not open")));
+}
+
+#[test]
+fn test_outsider_cannot_propose_transaction() {
+    let pic_setup = setup();
+
+    let account = create_account(&pic_setup);
+
+    let result = update_call::<u64>(
+        &pic_setup,
+        *OUTSIDER,
+        "propose_transaction",
+        propose_request(&account),
+    );
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains(&format!(
+        "Caller is not a member of shared account {}",
+        account.id
+    )));
+}
+
+#[test]
+fn test_list_proposals_is_paginated() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let account = create_account(&pic_setup);
+
+    for _ in 0..25 {
+        // Proposing takes a signing token, which refills every 10 seconds.
+        pic_setup.0.advance_time(Duration::from_secs(10));
+
+        let proposal_id = update_call::<u64>(
+            &pic_setup,
+            caller,
+            "propose_transaction",
+            propose_request(&account),
+        )
+        .unwrap();
+
+        let _ = update_call::<()>(
+            &pic_setup,
+            caller,
+            "cancel_proposal",
+            ProposalRef {
+                account_id: account.id,
+                proposal_id,
+            },
+        );
+    }
+
+    let page = query_call_with_args::<ProposalPage>(
+        &pic_setup,
+        caller,
+        "list_proposals",
+        (account.id, None::<u64>),
+    )
+    .unwrap();
+
+    assert_eq!(page.proposals.len(), 20);
+    assert!(page.next_cursor.is_some());
+
+    let next_page = query_call_with_args::<ProposalPage>(
+        &pic_setup,
+        caller,
+        "list_proposals",
+        (account.id, page.next_cursor),
+    )
+    .unwrap();
+
+    assert_eq!(next_page.proposals.len(), 5);
+    assert_eq!(next_page.next_cursor, None);
+    assert!(next_page.proposals[0].id > page.proposals[19].id);
+}
+
+#[test]
+fn test_member_leaves_shared_account() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let account = create_account(&pic_setup);
+
+    let proposal_id = update_call::<u64>(
+        &pic_setup,
+        caller,
+        "propose_transaction",
+        propose_request(&account),
+    )
+    .unwrap();
+
+    let result = update_call::<()>(&pic_setup, *MEMBER, "leave_shared_account", account.id);
+
+    assert!(result.is_ok());
+
+    let accounts =
+        query_call::<Vec<SharedAccount>>(&pic_setup, *MEMBER, "list_shared_accounts", ()).unwrap();
+
+    assert!(accounts.is_empty());
+
+    let accounts =
+        query_call::<Vec<SharedAccount>>(&pic_setup, caller, "list_shared_accounts", ()).unwrap();
+
+    assert_eq!(accounts[0].members, vec![caller]);
+    assert_eq!(accounts[0].threshold, 1);
+
+    // The threshold was lowered below the approvals of the proposal, approving again signs it.
+    let proposal = update_call::<Proposal>(
+        &pic_setup,
+        caller,
+        "approve_proposal",
+        ProposalRef {
+            account_id: account.id,
+            proposal_id,
+        },
+    )
+    .unwrap();
+
+    assert!(matches!(proposal.status, ProposalStatus::Signed(_)));
+
+    let result = update_call::<()>(&pic_setup, caller, "leave_shared_account", account.id);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("The last member cannot leave the shared account"));
+}
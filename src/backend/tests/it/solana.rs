@@ -0,0 +1,130 @@
+use crate::utils::mock::CALLER;
+use crate::utils::pocketic::{setup, update_call};
+use candid::Principal;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+const SYSTEM_PROGRAM_ID: [u8; 32] = [0; 32];
+const RECENT_BLOCKHASH: [u8; 32] = [7; 32];
+
+/// A serialized transfer message with the fee payer as single signer.
+fn transfer_message(fee_payer: &[u8], versioned: bool) -> Vec<u8> {
+    let mut message = vec![];
+    if versioned {
+        message.push(0x80);
+    }
+    // Header: one signature, no read-only signed account, one read-only unsigned account.
+    message.extend_from_slice(&[1, 0, 1]);
+    message.push(2);
+    message.extend_from_slice(fee_payer);
+    message.extend_from_slice(&SYSTEM_PROGRAM_ID);
+    message.extend_from_slice(&RECENT_BLOCKHASH);
+    // One instruction of the system program using the fee payer account and empty data.
+    message.extend_from_slice(&[1, 1, 1, 0, 0]);
+    if versioned {
+        // No address table lookups.
+        message.push(0);
+    }
+    message
+}
+
+fn caller_pubkey(pic_setup: &(pocket_ic::PocketIc, Principal), caller: Principal) -> Vec<u8> {
+    let address = update_call::<String>(pic_setup, caller, "caller_sol_address", ()).unwrap();
+
+    bs58::decode(address).into_vec().unwrap()
+}
+
+#[test]
+fn test_caller_sol_address() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let pubkey = caller_pubkey(&pic_setup, caller);
+
+    assert_eq!(pubkey.len(), 32);
+    assert_eq!(pubkey, caller_pubkey(&pic_setup, caller));
+}
+
+#[test]
+fn test_sign_solana_legacy_and_v0_messages() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let pubkey = caller_pubkey(&pic_setup, caller);
+    let verifying_key = VerifyingKey::try_from(pubkey.as_slice()).unwrap();
+
+    for versioned in [false, true] {
+        let message = transfer_message(&pubkey, versioned);
+
+        let signature = update_call::<String>(
+            &pic_setup,
+            caller,
+            "sign_solana_message",
+            bs58::encode(&message).into_string(),
+        )
+        .unwrap();
+
+        let signature_bytes = bs58::decode(signature).into_vec().unwrap();
+        let signature = Signature::from_slice(&signature_bytes).unwrap();
+
+        assert!(verifying_key.verify(&message, &signature).is_ok());
+    }
+}
+
+#[test]
+fn test_cannot_sign_solana_message_of_another_signer() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let message = transfer_message(&[1; 32], false);
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_solana_message",
+        bs58::encode(&message).into_string(),
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("is not a required signer of the message"));
+}
+
+#[test]
+fn test_cannot_sign_solana_message_with_unsupported_version() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let mut message = transfer_message(&[1; 32], true);
+    message[0] = 0x81;
+
+    let result = update_call::<String>(
+        &pic_setup,
+        caller,
+        "sign_solana_message",
+        bs58::encode(&message).into_string(),
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Unsupported message version 1"));
+}
+
+#[test]
+fn test_anonymous_cannot_get_sol_address() {
+    let pic_setup = setup();
+
+    let result =
+        update_call::<String>(&pic_setup, Principal::anonymous(), "caller_sol_address", ());
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "Anonymous caller not authorized.".to_string()
+    );
+}
@@ -0,0 +1,210 @@
+use crate::utils::mock::CALLER;
+use crate::utils::pocketic::{setup, update_call};
+use candid::Principal;
+use ic_cdk::api::management_canister::bitcoin::BitcoinNetwork;
+use k256::schnorr::signature::hazmat::PrehashVerifier;
+use k256::schnorr::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use shared::types::taproot::TaprootSignature;
+
+/// A PSBT with a single input spending the specified script and a single P2WPKH output.
+fn psbt_spending(script: &[u8]) -> Vec<u8> {
+    let mut unsigned_tx = vec![];
+    unsigned_tx.extend_from_slice(&2u32.to_le_bytes());
+    // One input without script sig.
+    unsigned_tx.push(1);
+    unsigned_tx.extend_from_slice(&[1; 32]);
+    unsigned_tx.extend_from_slice(&0u32.to_le_bytes());
+    unsigned_tx.push(0);
+    unsigned_tx.extend_from_slice(&0xffff_fffdu32.to_le_bytes());
+    // One output.
+    unsigned_tx.push(1);
+    unsigned_tx.extend_from_slice(&1_000u64.to_le_bytes());
+    unsigned_tx.push(22);
+    unsigned_tx.extend_from_slice(&[0x00, 0x14]);
+    unsigned_tx.extend_from_slice(&[2; 20]);
+    unsigned_tx.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut witness_utxo = vec![];
+    witness_utxo.extend_from_slice(&2_000u64.to_le_bytes());
+    witness_utxo.push(script.len() as u8);
+    witness_utxo.extend_from_slice(script);
+
+    let mut psbt = b"psbt\xff".to_vec();
+    // Global map with the unsigned transaction.
+    psbt.extend_from_slice(&[1, 0x00]);
+    psbt.push(unsigned_tx.len() as u8);
+    psbt.extend_from_slice(&unsigned_tx);
+    psbt.push(0);
+    // Input map with the witness UTXO.
+    psbt.extend_from_slice(&[1, 0x01]);
+    psbt.push(witness_utxo.len() as u8);
+    psbt.extend_from_slice(&witness_utxo);
+    psbt.push(0);
+    // Empty output map.
+    psbt.push(0);
+    psbt
+}
+
+/// The BIP-341 key path sighash of the single input of `psbt_spending(script)`.
+fn key_spend_sighash(script: &[u8]) -> [u8; 32] {
+    let sha256 = |data: &[u8]| -> [u8; 32] { Sha256::digest(data).into() };
+    let tagged_hash = |tag: &str, data: &[u8]| -> [u8; 32] {
+        let tag_hash = sha256(tag.as_bytes());
+        sha256(&[tag_hash.as_slice(), &tag_hash, data].concat())
+    };
+
+    let outpoint = [[1; 32].as_slice(), &0u32.to_le_bytes()].concat();
+    let script_pubkey = [&[script.len() as u8], script].concat();
+    let output = [
+        1_000u64.to_le_bytes().as_slice(),
+        &[22, 0x00, 0x14],
+        &[2; 20],
+    ]
+    .concat();
+
+    tagged_hash(
+        "TapSighash",
+        &[
+            // The epoch and SIGHASH_DEFAULT
+            [0x00, 0x00].as_slice(),
+            &2u32.to_le_bytes(),
+            &0u32.to_le_bytes(),
+            &sha256(&outpoint),
+            &sha256(&2_000u64.to_le_bytes()),
+            &sha256(&script_pubkey),
+            &sha256(&0xffff_fffdu32.to_le_bytes()),
+            &sha256(&output),
+            // Key path spend without annex, of the first input
+            &[0x00],
+            &0u32.to_le_bytes(),
+        ]
+        .concat(),
+    )
+}
+
+fn caller_script(pic_setup: &(pocket_ic::PocketIc, Principal), caller: Principal) -> Vec<u8> {
+    let address = update_call::<String>(
+        pic_setup,
+        caller,
+        "caller_btc_taproot_address",
+        BitcoinNetwork::Regtest,
+    )
+    .unwrap();
+
+    let (_, version, program) = bech32::segwit::decode(&address).unwrap();
+    assert_eq!(version, bech32::segwit::VERSION_1);
+
+    [&[0x51, 0x20], program.as_slice()].concat()
+}
+
+#[test]
+fn test_caller_btc_taproot_address() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    for (network, prefix) in [
+        (BitcoinNetwork::Mainnet, "bc1p"),
+        (BitcoinNetwork::Testnet, "tb1p"),
+        (BitcoinNetwork::Regtest, "bcrt1p"),
+    ] {
+        let address =
+            update_call::<String>(&pic_setup, caller, "caller_btc_taproot_address", network)
+                .unwrap();
+
+        assert!(address.starts_with(prefix));
+    }
+
+    assert_eq!(
+        caller_script(&pic_setup, caller),
+        caller_script(&pic_setup, caller)
+    );
+}
+
+#[test]
+fn test_sign_btc_taproot_psbt() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let script = caller_script(&pic_setup, caller);
+    let psbt = psbt_spending(&script);
+
+    let signatures = update_call::<Vec<TaprootSignature>>(
+        &pic_setup,
+        caller,
+        "sign_btc_taproot_psbt",
+        hex::encode(psbt),
+    )
+    .unwrap();
+
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].input_index, 0);
+
+    // The signature only verifies against the tweaked output key if the management canister honoured the BIP-341 aux.
+    let output_key = VerifyingKey::from_bytes(&script[2..]).unwrap();
+    let signature =
+        Signature::try_from(hex::decode(&signatures[0].signature).unwrap().as_slice()).unwrap();
+    assert!(output_key
+        .verify_prehash(&key_spend_sighash(&script), &signature)
+        .is_ok());
+}
+
+#[test]
+fn test_cannot_sign_btc_taproot_psbt_of_another_output() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let psbt = psbt_spending(&[[0x51, 0x20].as_slice(), &[3; 32]].concat());
+
+    let result = update_call::<Vec<TaprootSignature>>(
+        &pic_setup,
+        caller,
+        "sign_btc_taproot_psbt",
+        hex::encode(psbt),
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("No input of the PSBT spends the Taproot output of the caller"));
+}
+
+#[test]
+fn test_cannot_sign_invalid_btc_taproot_psbt() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = update_call::<Vec<TaprootSignature>>(
+        &pic_setup,
+        caller,
+        "sign_btc_taproot_psbt",
+        hex::encode(b"pbst\xff\x00"),
+    );
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("failed to parse the PSBT: invalid magic bytes"));
+}
+
+#[test]
+fn test_anonymous_cannot_get_btc_taproot_address() {
+    let pic_setup = setup();
+
+    let result = update_call::<String>(
+        &pic_setup,
+        Principal::anonymous(),
+        "caller_btc_taproot_address",
+        BitcoinNetwork::Mainnet,
+    );
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "Anonymous caller not authorized.".to_string()
+    );
+}
@@ -0,0 +1,288 @@
+use crate::utils::mock::CALLER;
+use crate::utils::pocketic::{
+    add_to_catalogue, mock_call, setup, setup_icrc_ledger, update_call, update_call_with_args,
+};
+use candid::utils::ArgumentEncoder;
+use candid::{Nat, Principal};
+use pocket_ic::PocketIc;
+use shared::types::account::IcrcAccount;
+use shared::types::custom_token::{CustomToken, IcrcToken, Token};
+use shared::types::transaction_history::{
+    TransactionDirection, TransactionHistoryCursor, TransactionHistoryPage,
+};
+use std::time::Duration;
+
+const OTHER: &str = "2vxsx-fae";
+
+/// An index that is not installed.
+const MISSING_INDEX_ID: &str = "ddsp7-7iaaa-aaaaq-aacqq-cai";
+
+fn account(owner: &str) -> IcrcAccount {
+    IcrcAccount {
+        owner: Principal::from_text(owner).unwrap(),
+        subaccount: None,
+    }
+}
+
+fn add_custom_token(pic_setup: &(PocketIc, Principal), ledger_id: Principal, index_id: Principal) {
+    let token = CustomToken {
+        token: Token::Icrc(IcrcToken {
+            ledger_id,
+            index_id: Some(index_id),
+        }),
+        enabled: true,
+        version: None,
+    };
+    update_call::<()>(
+        pic_setup,
+        Principal::from_text(CALLER).unwrap(),
+        "set_custom_token",
+        token,
+    )
+    .unwrap();
+}
+
+/// Installs a ledger that is its own index, lists it in the catalogue and adds it as a custom token.
+fn setup_ledger(pic_setup: &(PocketIc, Principal)) -> Principal {
+    let ledger_id = setup_icrc_ledger(pic_setup);
+    add_to_catalogue(pic_setup, ledger_id, Some(ledger_id));
+    add_custom_token(pic_setup, ledger_id, ledger_id);
+    ledger_id
+}
+
+/// Records a transaction on the ledger a second after the previous one.
+fn record(pic: &PocketIc, ledger_id: Principal, method: &str, args: impl ArgumentEncoder) {
+    pic.advance_time(Duration::from_secs(1));
+    mock_call::<Nat>(pic, ledger_id, method, args);
+}
+
+fn transaction_history(
+    pic_setup: &(PocketIc, Principal),
+    cursor: Option<TransactionHistoryCursor>,
+    limit: u64,
+) -> Result<TransactionHistoryPage, String> {
+    update_call_with_args(
+        pic_setup,
+        Principal::from_text(CALLER).unwrap(),
+        "get_transaction_history",
+        (cursor, limit),
+    )
+}
+
+#[test]
+fn test_transaction_history_merges_the_ledgers() {
+    let pic_setup = setup();
+    let pic = &pic_setup.0;
+    let first = setup_ledger(&pic_setup);
+    let second = setup_ledger(&pic_setup);
+
+    let amount = |value: u64| Nat::from(value);
+    record(
+        pic,
+        first,
+        "mock_transfer",
+        (account(OTHER), account(CALLER), amount(100)),
+    );
+    record(
+        pic,
+        second,
+        "mock_mint",
+        (account(CALLER), amount(150_000_000)),
+    );
+    record(
+        pic,
+        first,
+        "mock_transfer",
+        (account(CALLER), account(OTHER), amount(30)),
+    );
+    record(pic, second, "mock_burn", (account(CALLER), amount(2)));
+    record(
+        pic,
+        first,
+        "mock_transfer",
+        (account(CALLER), account(CALLER), amount(1)),
+    );
+    // The transactions of other accounts are not listed.
+    record(
+        pic,
+        first,
+        "mock_transfer",
+        (account(OTHER), account(OTHER), amount(7)),
+    );
+
+    let page = transaction_history(&pic_setup, None, 10).unwrap();
+
+    assert_eq!(page.errors, vec![]);
+    assert_eq!(page.next_cursor, None);
+    let listed: Vec<(
+        Principal,
+        &str,
+        TransactionDirection,
+        Option<IcrcAccount>,
+        &str,
+    )> = page
+        .transactions
+        .iter()
+        .map(|t| {
+            (
+                t.ledger_id,
+                t.kind.as_str(),
+                t.direction,
+                t.counterparty.clone(),
+                t.amount.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        listed,
+        vec![
+            (
+                first,
+                "transfer",
+                TransactionDirection::SelfTransfer,
+                None,
+                "0.00000001"
+            ),
+            (
+                second,
+                "burn",
+                TransactionDirection::Outgoing,
+                None,
+                "0.00000002"
+            ),
+            (
+                first,
+                "transfer",
+                TransactionDirection::Outgoing,
+                Some(account(OTHER)),
+                "0.0000003"
+            ),
+            (second, "mint", TransactionDirection::Incoming, None, "1.5"),
+            (
+                first,
+                "transfer",
+                TransactionDirection::Incoming,
+                Some(account(OTHER)),
+                "0.000001"
+            ),
+        ]
+    );
+    assert!(page
+        .transactions
+        .windows(2)
+        .all(|w| w[0].timestamp > w[1].timestamp));
+}
+
+#[test]
+fn test_transaction_history_is_paginated() {
+    let pic_setup = setup();
+    let pic = &pic_setup.0;
+    let first = setup_ledger(&pic_setup);
+    let second = setup_ledger(&pic_setup);
+
+    for value in 1..=4u64 {
+        record(pic, first, "mock_mint", (account(CALLER), Nat::from(value)));
+        record(
+            pic,
+            second,
+            "mock_mint",
+            (account(CALLER), Nat::from(value)),
+        );
+        record(
+            pic,
+            second,
+            "mock_mint",
+            (account(CALLER), Nat::from(value)),
+        );
+    }
+
+    let all = transaction_history(&pic_setup, None, 50).unwrap();
+    assert_eq!(all.transactions.len(), 12);
+
+    let mut listed = vec![];
+    let mut cursor = None;
+    loop {
+        let page = transaction_history(&pic_setup, cursor, 5).unwrap();
+        assert!(page.transactions.len() <= 5);
+        listed.extend(page.transactions);
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    assert_eq!(listed, all.transactions);
+}
+
+#[test]
+fn test_transaction_history_reports_errors_per_ledger() {
+    let pic_setup = setup();
+    let pic = &pic_setup.0;
+    let ledger_id = setup_ledger(&pic_setup);
+    let missing_index_id = Principal::from_text(MISSING_INDEX_ID).unwrap();
+    let other_ledger_id = setup_icrc_ledger(&pic_setup);
+    add_to_catalogue(&pic_setup, other_ledger_id, Some(missing_index_id));
+    add_custom_token(&pic_setup, other_ledger_id, missing_index_id);
+
+    record(
+        pic,
+        ledger_id,
+        "mock_mint",
+        (account(CALLER), Nat::from(1u64)),
+    );
+
+    let page = transaction_history(&pic_setup, None, 10).unwrap();
+
+    assert_eq!(page.transactions.len(), 1);
+    assert_eq!(page.errors.len(), 1);
+    assert_eq!(page.errors[0].ledger_id, other_ledger_id);
+    // The failing ledger is reported once, it is not listed in the next pages.
+    assert_eq!(page.next_cursor, None);
+}
+
+#[test]
+fn test_transaction_history_lists_only_the_catalogue_indexes() {
+    let pic_setup = setup();
+    let pic = &pic_setup.0;
+    let ledger_id = setup_icrc_ledger(&pic_setup);
+    // The index of the custom token is not the one of the catalogue entry.
+    let other_ledger_id = setup_icrc_ledger(&pic_setup);
+    add_to_catalogue(&pic_setup, ledger_id, Some(ledger_id));
+    add_custom_token(&pic_setup, ledger_id, other_ledger_id);
+    // A ledger outside of the catalogue is not called.
+    let unlisted_ledger_id = setup_icrc_ledger(&pic_setup);
+    add_custom_token(&pic_setup, unlisted_ledger_id, unlisted_ledger_id);
+
+    record(
+        pic,
+        ledger_id,
+        "mock_mint",
+        (account(CALLER), Nat::from(1u64)),
+    );
+    record(
+        pic,
+        unlisted_ledger_id,
+        "mock_mint",
+        (account(CALLER), Nat::from(2u64)),
+    );
+
+    let page = transaction_history(&pic_setup, None, 10).unwrap();
+
+    assert_eq!(page.errors, vec![]);
+    assert_eq!(page.transactions.len(), 1);
+    assert_eq!(page.transactions[0].ledger_id, ledger_id);
+    assert_eq!(page.transactions[0].symbol, "MCK");
+}
+
+#[test]
+fn test_transaction_history_limit_is_bounded() {
+    let pic_setup = setup();
+
+    for limit in [0, 51] {
+        let result = transaction_history(&pic_setup, None, limit);
+
+        assert!(result
+            .unwrap_err()
+            .contains("The limit should be between 1 and 50"));
+    }
+}
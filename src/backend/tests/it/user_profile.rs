@@ -0,0 +1,126 @@
+use crate::utils::mock::CALLER;
+use crate::utils::pocketic::{query_call, setup, update_call};
+use candid::Principal;
+use lazy_static::lazy_static;
+use shared::types::user_profile::{UpdateUserSettingsRequest, UserProfile, UserSettings};
+
+lazy_static! {
+    static ref SETTINGS: UserSettings = UserSettings {
+        hidden_tokens: Some(vec!["SepoliaETH".to_string()]),
+        preferred_networks: Some(vec!["ETH".to_string(), "ICP".to_string()]),
+        fiat_currency: Some("CHF".to_string()),
+        show_testnets: Some(true),
+    };
+}
+
+#[test]
+fn test_get_user_profile_before_update() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let result = query_call::<Option<UserProfile>>(&pic_setup, caller, "get_user_profile", ());
+
+    assert_eq!(result.unwrap(), None);
+}
+
+#[test]
+fn test_update_user_settings() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let request = UpdateUserSettingsRequest {
+        settings: SETTINGS.clone(),
+        current_user_version: None,
+    };
+
+    let profile =
+        update_call::<UserProfile>(&pic_setup, caller, "update_user_settings", request).unwrap();
+
+    assert_eq!(profile.settings, SETTINGS.clone());
+    assert_eq!(profile.version, Some(1));
+
+    let update_request = UpdateUserSettingsRequest {
+        settings: UserSettings {
+            show_testnets: Some(false),
+            ..SETTINGS.clone()
+        },
+        current_user_version: profile.version,
+    };
+
+    let updated_profile = update_call::<UserProfile>(
+        &pic_setup,
+        caller,
+        "update_user_settings",
+        update_request.clone(),
+    )
+    .unwrap();
+
+    assert_eq!(updated_profile.settings, update_request.settings);
+    assert_eq!(updated_profile.version, Some(2));
+    assert_eq!(updated_profile.created_timestamp, profile.created_timestamp);
+
+    let result = query_call::<Option<UserProfile>>(&pic_setup, caller, "get_user_profile", ());
+
+    assert_eq!(result.unwrap(), Some(updated_profile));
+}
+
+#[test]
+fn test_cannot_update_user_settings_with_invalid_version() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let request = UpdateUserSettingsRequest {
+        settings: SETTINGS.clone(),
+        current_user_version: None,
+    };
+
+    let _ = update_call::<UserProfile>(&pic_setup, caller, "update_user_settings", request.clone());
+
+    let result = update_call::<UserProfile>(&pic_setup, caller, "update_user_settings", request);
+
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .contains("Version mismatch, user profile update not allowed"));
+}
+
+#[test]
+fn test_cannot_update_user_settings_with_invalid_fiat_currency() {
+    let pic_setup = setup();
+
+    let caller = Principal::from_text(CALLER).unwrap();
+
+    let request = UpdateUserSettingsRequest {
+        settings: UserSettings {
+            fiat_currency: Some("usd".to_string()),
+            ..SETTINGS.clone()
+        },
+        current_user_version: None,
+    };
+
+    let result = update_call::<UserProfile>(&pic_setup, caller, "update_user_settings", request);
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("Invalid fiat currency usd"));
+}
+
+#[test]
+fn test_anonymous_cannot_get_user_profile() {
+    let pic_setup = setup();
+
+    let result = query_call::<Option<UserProfile>>(
+        &pic_setup,
+        Principal::anonymous(),
+        "get_user_profile",
+        (),
+    );
+
+    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err(),
+        "Anonymous caller not authorized.".to_string()
+    );
+}
@@ -1,4 +1,5 @@
 pub const CALLER: &str = "xzg7k-thc6c-idntg-knmtz-2fbhh-utt3e-snqw6-5xph3-54pbp-7axl5-tae";
+pub const CONTROLLER: &str = "d4ncq-zeppr-vhznm-gfgwk-qagkv-h2cwa-tcky6-ems37-mxfhn-ywlsb-tqe";
 pub const CALLER_ETH_ADDRESS: &str = "0xdd7fec4C49CD2Dd4eaa884D22D92503EabA5A791";
 
 pub const SEPOLIA_CHAIN_ID: u64 = 11155111;
@@ -1,18 +1,29 @@
-use crate::utils::mock::CALLER;
-use candid::{decode_one, encode_one, CandidType, Principal};
-use pocket_ic::{CallError, PocketIc, WasmResult};
+use crate::utils::mock::{CALLER, CONTROLLER, SEPOLIA_CHAIN_ID};
+use candid::utils::ArgumentEncoder;
+use candid::{decode_one, encode_args, encode_one, CandidType, Principal};
+use pocket_ic::{CallError, PocketIc, PocketIcBuilder, WasmResult};
 use serde::Deserialize;
+use shared::types::catalogue::{CatalogueToken, CatalogueTokenId, Network};
+use shared::types::cketh::CkEthMinter;
 use shared::types::{Arg, InitArg};
 use std::env;
 use std::fs::read;
 
 const BACKEND_WASM: &str = "../../target/wasm32-unknown-unknown/release/backend.wasm";
+const MOCK_EVM_RPC_WASM: &str = "../../target/wasm32-unknown-unknown/release/mock_evm_rpc.wasm";
+const MOCK_CKETH_MINTER_WASM: &str =
+    "../../target/wasm32-unknown-unknown/release/mock_cketh_minter.wasm";
+const MOCK_ICRC_LEDGER_WASM: &str =
+    "../../target/wasm32-unknown-unknown/release/mock_icrc_ledger.wasm";
 
 // Oisy's backend require an ecdsa_key_name for initialization.
 // PocketIC does not get mounted with "key_1" or "test_key_1" available in the management canister. If the canister request those ecdsa_public_key, it throws an error.
 // Instead, we can use the master_ecdsa_public_key suffixed with the subnet ID. PocketID adds the suffix because it can have multiple subnets.
 const SUBNET_ID: &str = "fscpm-uiaaa-aaaaa-aaaap-yai";
 
+// Since its server version 5.0.0, PocketIC provisions the threshold Schnorr keys "key_1", "test_key_1" and "dfx_test_key" on the fiduciary subnet, which therefore has to be part of the instance.
+const SCHNORR_KEY_NAME: &str = "key_1";
+
 pub fn setup() -> (PocketIc, Principal) {
     let (pic, canister_id) = init();
 
@@ -26,7 +37,12 @@ pub fn setup() -> (PocketIc, Principal) {
 
     let arg = init_arg();
 
-    pic.install_canister(canister_id, wasm_bytes, encode_one(&arg).unwrap(), None);
+    pic.install_canister(
+        canister_id,
+        wasm_bytes,
+        encode_one(arg).unwrap(),
+        Some(controller()),
+    );
 
     (pic, canister_id)
 }
@@ -38,11 +54,114 @@ pub fn setup_with_custom_wasm(wasm_path: &str) -> (PocketIc, Principal) {
 
     let arg = init_arg();
 
-    pic.install_canister(canister_id, wasm_bytes, encode_one(&arg).unwrap(), None);
+    pic.install_canister(
+        canister_id,
+        wasm_bytes,
+        encode_one(arg).unwrap(),
+        Some(controller()),
+    );
 
     (pic, canister_id)
 }
 
+/// Installs a mock canister, the wasm path can be overridden by the environment variable.
+fn install_mock(pic: &PocketIc, wasm_path_var: &str, default_wasm_path: &str) -> Principal {
+    let mock_wasm_path = env::var(wasm_path_var).unwrap_or_else(|_| default_wasm_path.to_string());
+    let wasm_bytes = read(&mock_wasm_path)
+        .unwrap_or_else(|_| panic!("Could not find the mock wasm: {}", mock_wasm_path));
+
+    let canister_id = pic.create_canister_with_settings(Some(controller()), None);
+    pic.add_cycles(canister_id, 2_000_000_000_000);
+    pic.install_canister(canister_id, wasm_bytes, vec![], Some(controller()));
+
+    canister_id
+}
+
+/// Installs a mock of the EVM RPC canister and configures the backend to call it.
+pub fn setup_evm_rpc(pic_setup: &(PocketIc, Principal)) -> Principal {
+    let canister_id = install_mock(&pic_setup.0, "MOCK_EVM_RPC_WASM_PATH", MOCK_EVM_RPC_WASM);
+
+    update_call::<()>(
+        pic_setup,
+        controller(),
+        "set_evm_rpc_canister",
+        Some(canister_id),
+    )
+    .expect("setting the EVM RPC canister should succeed");
+
+    canister_id
+}
+
+/// Installs a mock of the ckETH minter and configures the backend to call it.
+pub fn setup_cketh_minter(pic_setup: &(PocketIc, Principal)) -> Principal {
+    let canister_id = install_mock(
+        &pic_setup.0,
+        "MOCK_CKETH_MINTER_WASM_PATH",
+        MOCK_CKETH_MINTER_WASM,
+    );
+
+    update_call::<()>(
+        pic_setup,
+        controller(),
+        "set_cketh_minter",
+        Some(CkEthMinter {
+            canister_id,
+            chain_id: SEPOLIA_CHAIN_ID,
+        }),
+    )
+    .expect("setting the ckETH minter should succeed");
+
+    canister_id
+}
+
+/// Installs a mock of an ICRC ledger, to be added as a custom token.
+pub fn setup_icrc_ledger(pic_setup: &(PocketIc, Principal)) -> Principal {
+    install_mock(
+        &pic_setup.0,
+        "MOCK_ICRC_LEDGER_WASM_PATH",
+        MOCK_ICRC_LEDGER_WASM,
+    )
+}
+
+/// Lists a mock ICRC ledger in the catalogue, so that the backend calls it on behalf of the users.
+pub fn add_to_catalogue(
+    pic_setup: &(PocketIc, Principal),
+    ledger_id: Principal,
+    index_id: Option<Principal>,
+) {
+    update_call::<()>(
+        pic_setup,
+        controller(),
+        "set_catalogue_token",
+        CatalogueToken {
+            id: CatalogueTokenId::Icrc(ledger_id),
+            symbol: "MCK".to_string(),
+            name: "Mock".to_string(),
+            decimals: 8,
+            logo_hash: None,
+            index_id,
+            risk_flags: vec![],
+            enabled_networks: vec![Network::InternetComputer],
+            version: None,
+        },
+    )
+    .expect("listing the ledger in the catalogue should succeed");
+}
+
+/// Calls a method of a mock canister, e.g. to set its responses.
+pub fn mock_call<T>(pic: &PocketIc, mock: Principal, method: &str, args: impl ArgumentEncoder) -> T
+where
+    T: for<'a> Deserialize<'a> + CandidType,
+{
+    match pic
+        .update_call(mock, controller(), method, encode_args(args).unwrap())
+        .unwrap_or_else(|e| panic!("Mock call error: {}", e.description))
+    {
+        WasmResult::Reply(reply) => decode_one(&reply).unwrap(),
+        WasmResult::Reject(error) => panic!("Mock call rejected: {}", error),
+    }
+}
+
 pub fn upgrade((pic, canister_id): &(PocketIc, Principal)) -> Result<(), String> {
     let backend_wasm_path =
         env::var("BACKEND_WASM_PATH").unwrap_or_else(|_| BACKEND_WASM.to_string());
@@ -57,8 +176,8 @@ pub fn upgrade((pic, canister_id): &(PocketIc, Principal)) -> Result<(), String>
     pic.upgrade_canister(
         canister_id.clone(),
         wasm_bytes,
-        encode_one(&arg).unwrap(),
-        None,
+        encode_one(arg).unwrap(),
+        Some(controller()),
     )
     .map_err(|e| match e {
         CallError::Reject(e) => e,
@@ -72,18 +191,29 @@ pub fn upgrade((pic, canister_id): &(PocketIc, Principal)) -> Result<(), String>
 }
 
 fn init() -> (PocketIc, Principal) {
-    let pic = PocketIc::new();
-    let canister_id =
-        pic.create_canister_on_subnet(None, None, Principal::from_text(SUBNET_ID).unwrap());
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_fiduciary_subnet()
+        .build();
+    let canister_id = pic.create_canister_on_subnet(
+        Some(controller()),
+        None,
+        Principal::from_text(SUBNET_ID).unwrap(),
+    );
     pic.add_cycles(canister_id, 2_000_000_000_000);
 
     (pic, canister_id)
 }
 
+pub fn controller() -> Principal {
+    Principal::from_text(CONTROLLER).unwrap()
+}
+
 fn init_arg() -> Arg {
     Arg::Init(InitArg {
         ecdsa_key_name: format!("master_ecdsa_public_key_{}", SUBNET_ID).to_string(),
         allowed_callers: vec![Principal::from_text(CALLER).unwrap()],
+        schnorr_key_name: Some(SCHNORR_KEY_NAME.to_string()),
     })
 }
 
@@ -114,6 +244,30 @@ where
     })
 }
 
+pub fn update_call_with_args<T>(
+    (pic, canister_id): &(PocketIc, Principal),
+    caller: Principal,
+    method: &str,
+    args: impl ArgumentEncoder,
+) -> Result<T, String>
+where
+    T: for<'a> Deserialize<'a> + CandidType,
+{
+    pic.update_call(*canister_id, caller, method, encode_args(args).unwrap())
+        .map_err(|e| {
+            format!(
+                "Update call error. RejectionCode: {:?}, Error: {}",
+                e.code, e.description
+            )
+        })
+        .and_then(|reply| match reply {
+            WasmResult::Reply(reply) => {
+                decode_one(&reply).map_err(|_| "Decoding failed".to_string())
+            }
+            WasmResult::Reject(error) => Err(error),
+        })
+}
+
 pub fn query_call<T>(
     (pic, canister_id): &(PocketIc, Principal),
     caller: Principal,
@@ -140,3 +294,28 @@ where
         WasmResult::Reject(error) => Err(error),
     })
 }
+
+/// Like `query_call`, for the methods taking several arguments.
+pub fn query_call_with_args<T>(
+    (pic, canister_id): &(PocketIc, Principal),
+    caller: Principal,
+    method: &str,
+    args: impl ArgumentEncoder,
+) -> Result<T, String>
+where
+    T: for<'a> Deserialize<'a> + CandidType,
+{
+    pic.query_call(*canister_id, caller, method, encode_args(args).unwrap())
+        .map_err(|e| {
+            format!(
+                "Query call error. RejectionCode: {:?}, Error: {}",
+                e.code, e.description
+            )
+        })
+        .and_then(|reply| match reply {
+            WasmResult::Reply(reply) => {
+                decode_one(&reply).map_err(|_| "Decoding failed".to_string())
+            }
+            WasmResult::Reject(error) => Err(error),
+        })
+}
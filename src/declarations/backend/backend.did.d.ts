@@ -2,7 +2,22 @@ import type { ActorMethod } from '@dfinity/agent';
 import type { IDL } from '@dfinity/candid';
 import type { Principal } from '@dfinity/principal';
 
+export interface Allowlist {
+	enabled: boolean;
+	addresses: Array<string>;
+	pending_addresses: Array<PendingAllowlistAddress>;
+	pending_disable_timestamp: [] | [bigint];
+}
+export interface Approval {
+	member: Principal;
+	timestamp: bigint;
+}
+export interface ApproveRecoveryRequest {
+	new_principal: Principal;
+	user: Principal;
+}
 export type Arg = { Upgrade: null } | { Init: InitArg };
+export type BitcoinNetwork = { mainnet: null } | { regtest: null } | { testnet: null };
 export interface CanisterStatusResultV2 {
 	controller: Principal;
 	status: CanisterStatusType;
@@ -15,11 +30,84 @@ export interface CanisterStatusResultV2 {
 	module_hash: [] | [Uint8Array | number[]];
 }
 export type CanisterStatusType = { stopped: null } | { stopping: null } | { running: null };
+export interface CatalogueToken {
+	id: CatalogueTokenId;
+	decimals: number;
+	enabled_networks: Array<Network>;
+	risk_flags: Array<RiskFlag>;
+	name: string;
+	version: [] | [bigint];
+	index_id: [] | [Principal];
+	logo_hash: [] | [string];
+	symbol: string;
+}
+export type CatalogueTokenId = { Erc20: UserTokenId } | { Icrc: Principal };
+export interface CatalogueTokenPage {
+	tokens: Array<CatalogueToken>;
+	next_cursor: [] | [string];
+}
+export interface ChainConfig {
+	max_gas_limit: bigint;
+	name: string;
+	native_symbol: string;
+	supported_tx_types: Array<TransactionType>;
+	enabled: boolean;
+	chain_id: bigint;
+}
+export type CkEthConversion =
+	| {
+			Deposit: {
+				transaction_hash: string;
+				chain_id: bigint;
+			};
+	  }
+	| { Withdrawal: { ledger_block_index: bigint } };
+export interface CkEthDepositArgs {
+	max_priority_fee_per_gas: bigint;
+	max_fee_per_gas: bigint;
+	chain_id: bigint;
+	nonce: bigint;
+	amount: bigint;
+}
+export interface CkEthMinter {
+	canister_id: Principal;
+	chain_id: bigint;
+}
+export interface Contact {
+	label: string;
+	version: [] | [bigint];
+	addresses: Array<ContactAddress>;
+}
+export type ContactAddress = { Icrc: IcrcAccount } | { Ethereum: string } | { Bitcoin: string };
+export type ConversionState =
+	| { Error: string }
+	| { Failed: { reason: string } }
+	| { Sent: { transaction_hash: string } }
+	| { NotFound: null }
+	| { Completed: { transaction_hash: string } }
+	| { Pending: null };
+export interface ConversionStatus {
+	state: ConversionState;
+	conversion: TrackedConversion;
+}
+export interface CreateSharedAccountRequest {
+	members: Array<Principal>;
+	threshold: number;
+	name: string;
+}
 export interface CustomToken {
 	token: Token;
 	version: [] | [bigint];
 	enabled: boolean;
 }
+export interface CyclesConsumer {
+	principal: Principal;
+	usage: CyclesUsage;
+}
+export interface CyclesUsage {
+	cycles: bigint;
+	signatures: bigint;
+}
 export interface DefiniteCanisterSettingsArgs {
 	controller: Principal;
 	freezing_threshold: bigint;
@@ -27,6 +115,51 @@ export interface DefiniteCanisterSettingsArgs {
 	memory_allocation: bigint;
 	compute_allocation: bigint;
 }
+export interface DenylistEntry {
+	id: DenylistedId;
+	reason: string;
+}
+export type DenylistedId =
+	| {
+			Evm: {
+				chain_id: bigint;
+				address: string;
+			};
+	  }
+	| { Icrc: Principal };
+export interface EthBalances {
+	chain_id: bigint;
+	address: string;
+	balances: Array<TokenBalance>;
+}
+export interface FeeEstimate {
+	max_priority_fee_per_gas: bigint;
+	max_fee_per_gas: bigint;
+}
+export interface FeeSuggestion {
+	low: FeeEstimate;
+	base_fee_per_gas: bigint;
+	high: FeeEstimate;
+	chain_id: bigint;
+	timestamp: bigint;
+	medium: FeeEstimate;
+}
+export interface GuardianConfig {
+	guardians: Array<Principal>;
+	threshold: number;
+}
+export interface HistoryTransaction {
+	id: bigint;
+	direction: TransactionDirection;
+	decimals: number;
+	value: bigint;
+	kind: string;
+	ledger_id: Principal;
+	counterparty: [] | [IcrcAccount];
+	timestamp: bigint;
+	amount: string;
+	symbol: string;
+}
 export interface HttpRequest {
 	url: string;
 	method: string;
@@ -38,13 +171,176 @@ export interface HttpResponse {
 	headers: Array<[string, string]>;
 	status_code: number;
 }
+export interface IcrcAccount {
+	owner: Principal;
+	subaccount: [] | [Uint8Array | number[]];
+}
 export interface IcrcToken {
 	ledger_id: Principal;
 	index_id: [] | [Principal];
 }
+export interface IcrcTokenMetadata {
+	fee: bigint;
+	decimals: number;
+	validated_timestamp: bigint;
+	index_id: [] | [Principal];
+	logo_hash: [] | [string];
+	symbol: string;
+}
 export interface InitArg {
 	ecdsa_key_name: string;
 	allowed_callers: Array<Principal>;
+	schnorr_key_name: [] | [string];
+}
+export interface LedgerCursor {
+	ledger_id: Principal;
+	start: [] | [bigint];
+}
+export interface ListedCustomToken {
+	token: Token;
+	version: [] | [bigint];
+	enabled: boolean;
+	denylisted: [] | [boolean];
+}
+export interface ListedUserCatalogueToken {
+	token: [] | [CatalogueToken];
+	denylisted: [] | [boolean];
+	user_token: UserCatalogueToken;
+}
+export interface ListedUserToken {
+	decimals: [] | [number];
+	version: [] | [bigint];
+	denylisted: [] | [boolean];
+	chain_id: bigint;
+	contract_address: string;
+	symbol: [] | [string];
+}
+export type Network = { Evm: bigint } | { InternetComputer: null };
+export interface Notification {
+	id: bigint;
+	transaction_id: bigint;
+	from: [] | [IcrcAccount];
+	read: boolean;
+	ledger_id: Principal;
+	timestamp: bigint;
+	amount: bigint;
+}
+export interface NotificationPage {
+	notifications: Array<Notification>;
+	next_cursor: [] | [bigint];
+}
+export interface PendingAllowlistAddress {
+	address: string;
+	confirmation_timestamp: bigint;
+}
+export interface Portfolio {
+	errors: Array<PortfolioError>;
+	timestamp: bigint;
+	balances: Array<PortfolioBalance>;
+}
+export interface PortfolioBalance {
+	decimals: number;
+	token: PortfolioToken;
+	balance: bigint;
+	amount: string;
+	symbol: [] | [string];
+}
+export interface PortfolioError {
+	token: PortfolioToken;
+	error: string;
+}
+export interface PortfolioSnapshot {
+	failed_tokens: Array<PortfolioToken>;
+	timestamp: bigint;
+	balances: Array<SnapshotBalance>;
+}
+export type PortfolioToken =
+	| {
+			Evm: {
+				chain_id: bigint;
+				contract_address: [] | [string];
+			};
+	  }
+	| { Icrc: { ledger_id: Principal } };
+export interface Proposal {
+	id: bigint;
+	status: ProposalStatus;
+	request: SignRequest;
+	proposer: Principal;
+	created_timestamp: bigint;
+	approvals: Array<Approval>;
+}
+export interface ProposalPage {
+	next_cursor: [] | [bigint];
+	proposals: Array<Proposal>;
+}
+export interface ProposalRef {
+	account_id: bigint;
+	proposal_id: bigint;
+}
+export type ProposalStatus =
+	| { Open: null }
+	| { Approved: null }
+	| { Cancelled: null }
+	| { Signed: string };
+export interface ProposeTransactionRequest {
+	account_id: bigint;
+	request: SignRequest;
+}
+export interface ProviderSendResult {
+	status: SendTransactionStatus;
+	provider: string;
+}
+export interface RateLimitConfig {
+	refill_interval_secs: bigint;
+	capacity: number;
+}
+export interface RecoveryRequest {
+	new_principal: Principal;
+	unlock_timestamp: [] | [bigint];
+	created_timestamp: bigint;
+	approvals: Array<Principal>;
+}
+export type Result = { Ok: bigint } | { Err: string };
+export type RiskFlag =
+	| { Bridged: null }
+	| { Deprecated: null }
+	| { Unverified: null }
+	| { LowLiquidity: null };
+export interface ScheduleTransactionRequest {
+	request: SignRequest;
+	release_timestamp: bigint;
+}
+export interface ScheduledTransaction {
+	id: bigint;
+	status: ScheduledTransactionStatus;
+	request: SignRequest;
+	release_timestamp: bigint;
+	created_timestamp: bigint;
+}
+export type ScheduledTransactionStatus =
+	| { Failed: { reason: string } }
+	| { Cancelled: null }
+	| { Signed: string }
+	| { Pending: null };
+export interface SendTransactionResponse {
+	transaction_hash: string;
+	raw_transaction: string;
+	provider_results: Array<ProviderSendResult>;
+}
+export type SendTransactionStatus =
+	| { Ok: null }
+	| { Error: string }
+	| { NonceTooLow: null }
+	| { NonceTooHigh: null }
+	| { InsufficientFunds: null };
+export interface SharedAccount {
+	id: bigint;
+	members: Array<Principal>;
+	threshold: number;
+	name: string;
+	created_by: Principal;
+	created_timestamp: bigint;
 }
 export interface SignRequest {
 	to: string;
@@ -56,7 +352,61 @@ export interface SignRequest {
 	chain_id: bigint;
 	nonce: bigint;
 }
+export interface SnapshotBalance {
+	decimals: number;
+	token: PortfolioToken;
+	balance: bigint;
+}
+export interface TaprootSignature {
+	signature: string;
+	input_index: number;
+}
 export type Token = { Icrc: IcrcToken };
+export interface TokenBalance {
+	balance: Result;
+	contract_address: [] | [string];
+}
+export interface TrackedConversion {
+	created_timestamp: bigint;
+	conversion: CkEthConversion;
+}
+export type TransactionDirection = { SelfTransfer: null } | { Outgoing: null } | { Incoming: null };
+export interface TransactionHistoryCursor {
+	ledgers: Array<LedgerCursor>;
+}
+export interface TransactionHistoryError {
+	ledger_id: Principal;
+	error: string;
+}
+export interface TransactionHistoryPage {
+	errors: Array<TransactionHistoryError>;
+	next_cursor: [] | [TransactionHistoryCursor];
+	transactions: Array<HistoryTransaction>;
+}
+export type TransactionType = { Eip1559: null } | { Legacy: null };
+export interface UpdateUserSettingsRequest {
+	settings: UserSettings;
+	current_user_version: [] | [bigint];
+}
+export interface UserCatalogueToken {
+	id: CatalogueTokenId;
+	name: [] | [string];
+	version: [] | [bigint];
+	enabled: boolean;
+	symbol: [] | [string];
+}
+export interface UserProfile {
+	version: [] | [bigint];
+	settings: UserSettings;
+	created_timestamp: bigint;
+	updated_timestamp: bigint;
+}
+export interface UserSettings {
+	hidden_tokens: [] | [Array<string>];
+	fiat_currency: [] | [string];
+	preferred_networks: [] | [Array<string>];
+	show_testnets: [] | [boolean];
+}
 export interface UserToken {
 	decimals: [] | [number];
 	version: [] | [bigint];
@@ -69,19 +419,102 @@ export interface UserTokenId {
 	contract_address: string;
 }
 export interface _SERVICE {
+	add_allowlist_address: ActorMethod<[string], undefined>;
 	add_user_token: ActorMethod<[UserToken], undefined>;
+	approve_proposal: ActorMethod<[ProposalRef], Proposal>;
+	approve_recovery: ActorMethod<[ApproveRecoveryRequest], undefined>;
+	caller_btc_taproot_address: ActorMethod<[BitcoinNetwork], string>;
 	caller_eth_address: ActorMethod<[], string>;
+	caller_sol_address: ActorMethod<[], string>;
+	cancel_proposal: ActorMethod<[ProposalRef], undefined>;
+	cancel_recovery: ActorMethod<[], undefined>;
+	cancel_scheduled_transaction: ActorMethod<[bigint], undefined>;
+	cketh_deposit_request: ActorMethod<[CkEthDepositArgs], SignRequest>;
+	complete_recovery: ActorMethod<[Principal], undefined>;
+	conversion_status: ActorMethod<[], Array<ConversionStatus>>;
+	create_shared_account: ActorMethod<[CreateSharedAccountRequest], SharedAccount>;
+	decode_icrc_account: ActorMethod<[string], IcrcAccount>;
+	disable_allowlist_mode: ActorMethod<[], undefined>;
+	disable_notifications: ActorMethod<[], undefined>;
+	disable_portfolio_history: ActorMethod<[], undefined>;
+	enable_allowlist_mode: ActorMethod<[], undefined>;
+	enable_notifications: ActorMethod<[], undefined>;
+	enable_portfolio_history: ActorMethod<[], undefined>;
+	encode_icrc_account: ActorMethod<[IcrcAccount], string>;
 	eth_address_of: ActorMethod<[Principal], string>;
+	eth_balances: ActorMethod<[bigint], EthBalances>;
+	get_allowlist: ActorMethod<[], Allowlist>;
 	get_canister_status: ActorMethod<[], CanisterStatusResultV2>;
+	get_catalogue_logo: ActorMethod<[string], [] | [string]>;
+	get_guardians: ActorMethod<[], [] | [GuardianConfig]>;
+	get_icrc_token_metadata: ActorMethod<[Principal], [] | [IcrcTokenMetadata]>;
+	get_portfolio: ActorMethod<[], Portfolio>;
+	get_portfolio_history: ActorMethod<[bigint, bigint, bigint], Array<PortfolioSnapshot>>;
+	get_recovery_request: ActorMethod<[Principal], [] | [RecoveryRequest]>;
+	get_signed_transaction: ActorMethod<[bigint], [] | [string]>;
+	get_transaction_history: ActorMethod<
+		[[] | [TransactionHistoryCursor], bigint],
+		TransactionHistoryPage
+	>;
+	get_user_profile: ActorMethod<[], [] | [UserProfile]>;
 	http_request: ActorMethod<[HttpRequest], HttpResponse>;
-	list_custom_tokens: ActorMethod<[], Array<CustomToken>>;
-	list_user_tokens: ActorMethod<[], Array<UserToken>>;
+	icp_account_identifier: ActorMethod<[IcrcAccount], string>;
+	is_notifications_enabled: ActorMethod<[], boolean>;
+	is_portfolio_history_enabled: ActorMethod<[], boolean>;
+	is_valid_icp_account_identifier: ActorMethod<[string], boolean>;
+	is_valid_icrc_account: ActorMethod<[string], boolean>;
+	leave_shared_account: ActorMethod<[bigint], undefined>;
+	list_catalogue_tokens: ActorMethod<[Network, [] | [string]], CatalogueTokenPage>;
+	list_contacts: ActorMethod<[], Array<Contact>>;
+	list_custom_tokens: ActorMethod<[], Array<ListedCustomToken>>;
+	list_denylist: ActorMethod<[], Array<DenylistEntry>>;
+	list_notifications: ActorMethod<[[] | [bigint]], NotificationPage>;
+	list_proposals: ActorMethod<[bigint, [] | [bigint]], ProposalPage>;
+	list_scheduled_transactions: ActorMethod<[], Array<ScheduledTransaction>>;
+	list_shared_accounts: ActorMethod<[], Array<SharedAccount>>;
+	list_supported_chains: ActorMethod<[], Array<ChainConfig>>;
+	list_user_catalogue_tokens: ActorMethod<[], Array<ListedUserCatalogueToken>>;
+	list_user_tokens: ActorMethod<[], Array<ListedUserToken>>;
+	mark_notifications_read: ActorMethod<[BigUint64Array | bigint[]], undefined>;
+	next_nonce_hint: ActorMethod<[bigint], [] | [bigint]>;
 	personal_sign: ActorMethod<[string], string>;
+	poll_notifications_of: ActorMethod<[Principal], undefined>;
+	portfolio_snapshot_of: ActorMethod<[Principal], Portfolio>;
+	propose_transaction: ActorMethod<[ProposeTransactionRequest], bigint>;
+	remove_allowlist_address: ActorMethod<[string], undefined>;
+	remove_catalogue_token: ActorMethod<[CatalogueTokenId], undefined>;
+	remove_contact: ActorMethod<[string], undefined>;
+	remove_denylist_entry: ActorMethod<[DenylistedId], undefined>;
+	remove_user_catalogue_token: ActorMethod<[CatalogueTokenId], undefined>;
 	remove_user_token: ActorMethod<[UserTokenId], undefined>;
+	resolve_ens: ActorMethod<[string], [] | [string]>;
+	reverse_ens: ActorMethod<[string], [] | [string]>;
+	schedule_transaction: ActorMethod<[ScheduleTransactionRequest], bigint>;
+	set_catalogue_logo: ActorMethod<[string], string>;
+	set_catalogue_token: ActorMethod<[CatalogueToken], undefined>;
+	set_chain: ActorMethod<[ChainConfig], undefined>;
+	set_cketh_minter: ActorMethod<[[] | [CkEthMinter]], undefined>;
+	set_contact: ActorMethod<[Contact], undefined>;
 	set_custom_token: ActorMethod<[CustomToken], undefined>;
+	set_custom_token_validation: ActorMethod<[boolean], undefined>;
+	set_denylist_entry: ActorMethod<[DenylistEntry], undefined>;
+	set_evm_rpc_canister: ActorMethod<[[] | [Principal]], undefined>;
+	set_external_call_rate_limit: ActorMethod<[RateLimitConfig], undefined>;
+	set_guardians: ActorMethod<[GuardianConfig], undefined>;
 	set_many_custom_tokens: ActorMethod<[Array<CustomToken>], undefined>;
+	set_signing_rate_limit: ActorMethod<[RateLimitConfig], undefined>;
+	set_user_catalogue_token: ActorMethod<[UserCatalogueToken], undefined>;
+	shared_account_eth_address: ActorMethod<[bigint], string>;
+	sign_and_send_transaction: ActorMethod<[SignRequest], SendTransactionResponse>;
+	sign_btc_taproot_psbt: ActorMethod<[string], Array<TaprootSignature>>;
 	sign_prehash: ActorMethod<[string], string>;
+	sign_scheduled_transaction: ActorMethod<[Principal, SignRequest], string>;
+	sign_solana_message: ActorMethod<[string], string>;
 	sign_transaction: ActorMethod<[SignRequest], string>;
+	suggest_fees: ActorMethod<[bigint], FeeSuggestion>;
+	top_cycle_consumers: ActorMethod<[number], Array<CyclesConsumer>>;
+	track_cketh_conversion: ActorMethod<[CkEthConversion], undefined>;
+	update_user_settings: ActorMethod<[UpdateUserSettingsRequest], UserProfile>;
 }
 export declare const idlFactory: IDL.InterfaceFactory;
 export declare const init: (args: { IDL: typeof IDL }) => IDL.Type[];
@@ -2,7 +2,8 @@
 export const idlFactory = ({ IDL }) => {
 	const InitArg = IDL.Record({
 		ecdsa_key_name: IDL.Text,
-		allowed_callers: IDL.Vec(IDL.Principal)
+		allowed_callers: IDL.Vec(IDL.Principal),
+		schnorr_key_name: IDL.Opt(IDL.Text)
 	});
 	const Arg = IDL.Variant({ Upgrade: IDL.Null, Init: InitArg });
 	const UserToken = IDL.Record({
@@ -12,6 +13,114 @@ export const idlFactory = ({ IDL }) => {
 		contract_address: IDL.Text,
 		symbol: IDL.Opt(IDL.Text)
 	});
+	const ProposalRef = IDL.Record({
+		account_id: IDL.Nat64,
+		proposal_id: IDL.Nat64
+	});
+	const ProposalStatus = IDL.Variant({
+		Open: IDL.Null,
+		Approved: IDL.Null,
+		Cancelled: IDL.Null,
+		Signed: IDL.Text
+	});
+	const SignRequest = IDL.Record({
+		to: IDL.Text,
+		gas: IDL.Nat,
+		value: IDL.Nat,
+		max_priority_fee_per_gas: IDL.Nat,
+		data: IDL.Opt(IDL.Text),
+		max_fee_per_gas: IDL.Nat,
+		chain_id: IDL.Nat,
+		nonce: IDL.Nat
+	});
+	const Approval = IDL.Record({
+		member: IDL.Principal,
+		timestamp: IDL.Nat64
+	});
+	const Proposal = IDL.Record({
+		id: IDL.Nat64,
+		status: ProposalStatus,
+		request: SignRequest,
+		proposer: IDL.Principal,
+		created_timestamp: IDL.Nat64,
+		approvals: IDL.Vec(Approval)
+	});
+	const ApproveRecoveryRequest = IDL.Record({
+		new_principal: IDL.Principal,
+		user: IDL.Principal
+	});
+	const BitcoinNetwork = IDL.Variant({
+		mainnet: IDL.Null,
+		regtest: IDL.Null,
+		testnet: IDL.Null
+	});
+	const CkEthDepositArgs = IDL.Record({
+		max_priority_fee_per_gas: IDL.Nat,
+		max_fee_per_gas: IDL.Nat,
+		chain_id: IDL.Nat64,
+		nonce: IDL.Nat,
+		amount: IDL.Nat
+	});
+	const ConversionState = IDL.Variant({
+		Error: IDL.Text,
+		Failed: IDL.Record({ reason: IDL.Text }),
+		Sent: IDL.Record({ transaction_hash: IDL.Text }),
+		NotFound: IDL.Null,
+		Completed: IDL.Record({ transaction_hash: IDL.Text }),
+		Pending: IDL.Null
+	});
+	const CkEthConversion = IDL.Variant({
+		Deposit: IDL.Record({
+			transaction_hash: IDL.Text,
+			chain_id: IDL.Nat64
+		}),
+		Withdrawal: IDL.Record({ ledger_block_index: IDL.Nat64 })
+	});
+	const TrackedConversion = IDL.Record({
+		created_timestamp: IDL.Nat64,
+		conversion: CkEthConversion
+	});
+	const ConversionStatus = IDL.Record({
+		state: ConversionState,
+		conversion: TrackedConversion
+	});
+	const CreateSharedAccountRequest = IDL.Record({
+		members: IDL.Vec(IDL.Principal),
+		threshold: IDL.Nat8,
+		name: IDL.Text
+	});
+	const SharedAccount = IDL.Record({
+		id: IDL.Nat64,
+		members: IDL.Vec(IDL.Principal),
+		threshold: IDL.Nat8,
+		name: IDL.Text,
+		created_by: IDL.Principal,
+		created_timestamp: IDL.Nat64
+	});
+	const IcrcAccount = IDL.Record({
+		owner: IDL.Principal,
+		subaccount: IDL.Opt(IDL.Vec(IDL.Nat8))
+	});
+	const Result = IDL.Variant({ Ok: IDL.Nat, Err: IDL.Text });
+	const TokenBalance = IDL.Record({
+		balance: Result,
+		contract_address: IDL.Opt(IDL.Text)
+	});
+	const EthBalances = IDL.Record({
+		chain_id: IDL.Nat64,
+		address: IDL.Text,
+		balances: IDL.Vec(TokenBalance)
+	});
+	const PendingAllowlistAddress = IDL.Record({
+		address: IDL.Text,
+		confirmation_timestamp: IDL.Nat64
+	});
+	const Allowlist = IDL.Record({
+		enabled: IDL.Bool,
+		addresses: IDL.Vec(IDL.Text),
+		pending_addresses: IDL.Vec(PendingAllowlistAddress),
+		pending_disable_timestamp: IDL.Opt(IDL.Nat64)
+	});
 	const CanisterStatusType = IDL.Variant({
 		stopped: IDL.Null,
 		stopping: IDL.Null,
@@ -35,6 +144,102 @@ export const idlFactory = ({ IDL }) => {
 		idle_cycles_burned_per_day: IDL.Nat,
 		module_hash: IDL.Opt(IDL.Vec(IDL.Nat8))
 	});
+	const GuardianConfig = IDL.Record({
+		guardians: IDL.Vec(IDL.Principal),
+		threshold: IDL.Nat8
+	});
+	const IcrcTokenMetadata = IDL.Record({
+		fee: IDL.Nat,
+		decimals: IDL.Nat8,
+		validated_timestamp: IDL.Nat64,
+		index_id: IDL.Opt(IDL.Principal),
+		logo_hash: IDL.Opt(IDL.Text),
+		symbol: IDL.Text
+	});
+	const PortfolioToken = IDL.Variant({
+		Evm: IDL.Record({
+			chain_id: IDL.Nat64,
+			contract_address: IDL.Opt(IDL.Text)
+		}),
+		Icrc: IDL.Record({ ledger_id: IDL.Principal })
+	});
+	const PortfolioError = IDL.Record({
+		token: PortfolioToken,
+		error: IDL.Text
+	});
+	const PortfolioBalance = IDL.Record({
+		decimals: IDL.Nat8,
+		token: PortfolioToken,
+		balance: IDL.Nat,
+		amount: IDL.Text,
+		symbol: IDL.Opt(IDL.Text)
+	});
+	const Portfolio = IDL.Record({
+		errors: IDL.Vec(PortfolioError),
+		timestamp: IDL.Nat64,
+		balances: IDL.Vec(PortfolioBalance)
+	});
+	const SnapshotBalance = IDL.Record({
+		decimals: IDL.Nat8,
+		token: PortfolioToken,
+		balance: IDL.Nat
+	});
+	const PortfolioSnapshot = IDL.Record({
+		failed_tokens: IDL.Vec(PortfolioToken),
+		timestamp: IDL.Nat64,
+		balances: IDL.Vec(SnapshotBalance)
+	});
+	const RecoveryRequest = IDL.Record({
+		new_principal: IDL.Principal,
+		unlock_timestamp: IDL.Opt(IDL.Nat64),
+		created_timestamp: IDL.Nat64,
+		approvals: IDL.Vec(IDL.Principal)
+	});
+	const LedgerCursor = IDL.Record({
+		ledger_id: IDL.Principal,
+		start: IDL.Opt(IDL.Nat)
+	});
+	const TransactionHistoryCursor = IDL.Record({
+		ledgers: IDL.Vec(LedgerCursor)
+	});
+	const TransactionHistoryError = IDL.Record({
+		ledger_id: IDL.Principal,
+		error: IDL.Text
+	});
+	const TransactionDirection = IDL.Variant({
+		SelfTransfer: IDL.Null,
+		Outgoing: IDL.Null,
+		Incoming: IDL.Null
+	});
+	const HistoryTransaction = IDL.Record({
+		id: IDL.Nat,
+		direction: TransactionDirection,
+		decimals: IDL.Nat8,
+		value: IDL.Nat,
+		kind: IDL.Text,
+		ledger_id: IDL.Principal,
+		counterparty: IDL.Opt(IcrcAccount),
+		timestamp: IDL.Nat64,
+		amount: IDL.Text,
+		symbol: IDL.Text
+	});
+	const TransactionHistoryPage = IDL.Record({
+		errors: IDL.Vec(TransactionHistoryError),
+		next_cursor: IDL.Opt(TransactionHistoryCursor),
+		transactions: IDL.Vec(HistoryTransaction)
+	});
+	const UserSettings = IDL.Record({
+		hidden_tokens: IDL.Opt(IDL.Vec(IDL.Text)),
+		fiat_currency: IDL.Opt(IDL.Text),
+		preferred_networks: IDL.Opt(IDL.Vec(IDL.Text)),
+		show_testnets: IDL.Opt(IDL.Bool)
+	});
+	const UserProfile = IDL.Record({
+		version: IDL.Opt(IDL.Nat64),
+		settings: UserSettings,
+		created_timestamp: IDL.Nat64,
+		updated_timestamp: IDL.Nat64
+	});
 	const HttpRequest = IDL.Record({
 		url: IDL.Text,
 		method: IDL.Text,
@@ -46,51 +251,304 @@ export const idlFactory = ({ IDL }) => {
 		headers: IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
 		status_code: IDL.Nat16
 	});
+	const Network = IDL.Variant({
+		Evm: IDL.Nat64,
+		InternetComputer: IDL.Null
+	});
+	const UserTokenId = IDL.Record({
+		chain_id: IDL.Nat64,
+		contract_address: IDL.Text
+	});
+	const CatalogueTokenId = IDL.Variant({
+		Erc20: UserTokenId,
+		Icrc: IDL.Principal
+	});
+	const RiskFlag = IDL.Variant({
+		Bridged: IDL.Null,
+		Deprecated: IDL.Null,
+		Unverified: IDL.Null,
+		LowLiquidity: IDL.Null
+	});
+	const CatalogueToken = IDL.Record({
+		id: CatalogueTokenId,
+		decimals: IDL.Nat8,
+		enabled_networks: IDL.Vec(Network),
+		risk_flags: IDL.Vec(RiskFlag),
+		name: IDL.Text,
+		version: IDL.Opt(IDL.Nat64),
+		index_id: IDL.Opt(IDL.Principal),
+		logo_hash: IDL.Opt(IDL.Text),
+		symbol: IDL.Text
+	});
+	const CatalogueTokenPage = IDL.Record({
+		tokens: IDL.Vec(CatalogueToken),
+		next_cursor: IDL.Opt(IDL.Text)
+	});
+	const ContactAddress = IDL.Variant({
+		Icrc: IcrcAccount,
+		Ethereum: IDL.Text,
+		Bitcoin: IDL.Text
+	});
+	const Contact = IDL.Record({
+		label: IDL.Text,
+		version: IDL.Opt(IDL.Nat64),
+		addresses: IDL.Vec(ContactAddress)
+	});
 	const IcrcToken = IDL.Record({
 		ledger_id: IDL.Principal,
 		index_id: IDL.Opt(IDL.Principal)
 	});
 	const Token = IDL.Variant({ Icrc: IcrcToken });
+	const ListedCustomToken = IDL.Record({
+		token: Token,
+		version: IDL.Opt(IDL.Nat64),
+		enabled: IDL.Bool,
+		denylisted: IDL.Opt(IDL.Bool)
+	});
+	const DenylistedId = IDL.Variant({
+		Evm: IDL.Record({ chain_id: IDL.Nat64, address: IDL.Text }),
+		Icrc: IDL.Principal
+	});
+	const DenylistEntry = IDL.Record({
+		id: DenylistedId,
+		reason: IDL.Text
+	});
+	const Notification = IDL.Record({
+		id: IDL.Nat64,
+		transaction_id: IDL.Nat,
+		from: IDL.Opt(IcrcAccount),
+		read: IDL.Bool,
+		ledger_id: IDL.Principal,
+		timestamp: IDL.Nat64,
+		amount: IDL.Nat
+	});
+	const NotificationPage = IDL.Record({
+		notifications: IDL.Vec(Notification),
+		next_cursor: IDL.Opt(IDL.Nat64)
+	});
+	const ProposalPage = IDL.Record({
+		next_cursor: IDL.Opt(IDL.Nat64),
+		proposals: IDL.Vec(Proposal)
+	});
+	const ScheduledTransactionStatus = IDL.Variant({
+		Failed: IDL.Record({ reason: IDL.Text }),
+		Cancelled: IDL.Null,
+		Signed: IDL.Text,
+		Pending: IDL.Null
+	});
+	const ScheduledTransaction = IDL.Record({
+		id: IDL.Nat64,
+		status: ScheduledTransactionStatus,
+		request: SignRequest,
+		release_timestamp: IDL.Nat64,
+		created_timestamp: IDL.Nat64
+	});
+	const TransactionType = IDL.Variant({
+		Eip1559: IDL.Null,
+		Legacy: IDL.Null
+	});
+	const ChainConfig = IDL.Record({
+		max_gas_limit: IDL.Nat64,
+		name: IDL.Text,
+		native_symbol: IDL.Text,
+		supported_tx_types: IDL.Vec(TransactionType),
+		enabled: IDL.Bool,
+		chain_id: IDL.Nat64
+	});
+	const UserCatalogueToken = IDL.Record({
+		id: CatalogueTokenId,
+		name: IDL.Opt(IDL.Text),
+		version: IDL.Opt(IDL.Nat64),
+		enabled: IDL.Bool,
+		symbol: IDL.Opt(IDL.Text)
+	});
+	const ListedUserCatalogueToken = IDL.Record({
+		token: IDL.Opt(CatalogueToken),
+		denylisted: IDL.Opt(IDL.Bool),
+		user_token: UserCatalogueToken
+	});
+	const ListedUserToken = IDL.Record({
+		decimals: IDL.Opt(IDL.Nat8),
+		version: IDL.Opt(IDL.Nat64),
+		denylisted: IDL.Opt(IDL.Bool),
+		chain_id: IDL.Nat64,
+		contract_address: IDL.Text,
+		symbol: IDL.Opt(IDL.Text)
+	});
+	const ProposeTransactionRequest = IDL.Record({
+		account_id: IDL.Nat64,
+		request: SignRequest
+	});
+	const ScheduleTransactionRequest = IDL.Record({
+		request: SignRequest,
+		release_timestamp: IDL.Nat64
+	});
+	const CkEthMinter = IDL.Record({
+		canister_id: IDL.Principal,
+		chain_id: IDL.Nat64
+	});
 	const CustomToken = IDL.Record({
 		token: Token,
 		version: IDL.Opt(IDL.Nat64),
 		enabled: IDL.Bool
 	});
-	const UserTokenId = IDL.Record({
-		chain_id: IDL.Nat64,
-		contract_address: IDL.Text
+	const RateLimitConfig = IDL.Record({
+		refill_interval_secs: IDL.Nat64,
+		capacity: IDL.Nat32
 	});
-	const SignRequest = IDL.Record({
-		to: IDL.Text,
-		gas: IDL.Nat,
-		value: IDL.Nat,
+	const SendTransactionStatus = IDL.Variant({
+		Ok: IDL.Null,
+		Error: IDL.Text,
+		NonceTooLow: IDL.Null,
+		NonceTooHigh: IDL.Null,
+		InsufficientFunds: IDL.Null
+	});
+	const ProviderSendResult = IDL.Record({
+		status: SendTransactionStatus,
+		provider: IDL.Text
+	});
+	const SendTransactionResponse = IDL.Record({
+		transaction_hash: IDL.Text,
+		raw_transaction: IDL.Text,
+		provider_results: IDL.Vec(ProviderSendResult)
+	});
+	const TaprootSignature = IDL.Record({
+		signature: IDL.Text,
+		input_index: IDL.Nat32
+	});
+	const FeeEstimate = IDL.Record({
 		max_priority_fee_per_gas: IDL.Nat,
-		data: IDL.Opt(IDL.Text),
-		max_fee_per_gas: IDL.Nat,
-		chain_id: IDL.Nat,
-		nonce: IDL.Nat
+		max_fee_per_gas: IDL.Nat
+	});
+	const FeeSuggestion = IDL.Record({
+		low: FeeEstimate,
+		base_fee_per_gas: IDL.Nat,
+		high: FeeEstimate,
+		chain_id: IDL.Nat64,
+		timestamp: IDL.Nat64,
+		medium: FeeEstimate
+	});
+	const CyclesUsage = IDL.Record({
+		cycles: IDL.Nat,
+		signatures: IDL.Nat64
+	});
+	const CyclesConsumer = IDL.Record({
+		principal: IDL.Principal,
+		usage: CyclesUsage
+	});
+	const UpdateUserSettingsRequest = IDL.Record({
+		settings: UserSettings,
+		current_user_version: IDL.Opt(IDL.Nat64)
 	});
 	return IDL.Service({
+		add_allowlist_address: IDL.Func([IDL.Text], [], []),
 		add_user_token: IDL.Func([UserToken], [], []),
+		approve_proposal: IDL.Func([ProposalRef], [Proposal], []),
+		approve_recovery: IDL.Func([ApproveRecoveryRequest], [], []),
+		caller_btc_taproot_address: IDL.Func([BitcoinNetwork], [IDL.Text], []),
 		caller_eth_address: IDL.Func([], [IDL.Text], []),
+		caller_sol_address: IDL.Func([], [IDL.Text], []),
+		cancel_proposal: IDL.Func([ProposalRef], [], []),
+		cancel_recovery: IDL.Func([], [], []),
+		cancel_scheduled_transaction: IDL.Func([IDL.Nat64], [], []),
+		cketh_deposit_request: IDL.Func([CkEthDepositArgs], [SignRequest], []),
+		complete_recovery: IDL.Func([IDL.Principal], [], []),
+		conversion_status: IDL.Func([], [IDL.Vec(ConversionStatus)], []),
+		create_shared_account: IDL.Func([CreateSharedAccountRequest], [SharedAccount], []),
+		decode_icrc_account: IDL.Func([IDL.Text], [IcrcAccount]),
+		disable_allowlist_mode: IDL.Func([], [], []),
+		disable_notifications: IDL.Func([], [], []),
+		disable_portfolio_history: IDL.Func([], [], []),
+		enable_allowlist_mode: IDL.Func([], [], []),
+		enable_notifications: IDL.Func([], [], []),
+		enable_portfolio_history: IDL.Func([], [], []),
+		encode_icrc_account: IDL.Func([IcrcAccount], [IDL.Text]),
 		eth_address_of: IDL.Func([IDL.Principal], [IDL.Text], []),
+		eth_balances: IDL.Func([IDL.Nat64], [EthBalances], []),
+		get_allowlist: IDL.Func([], [Allowlist]),
 		get_canister_status: IDL.Func([], [CanisterStatusResultV2], []),
+		get_catalogue_logo: IDL.Func([IDL.Text], [IDL.Opt(IDL.Text)]),
+		get_guardians: IDL.Func([], [IDL.Opt(GuardianConfig)]),
+		get_icrc_token_metadata: IDL.Func([IDL.Principal], [IDL.Opt(IcrcTokenMetadata)]),
+		get_portfolio: IDL.Func([], [Portfolio], []),
+		get_portfolio_history: IDL.Func(
+			[IDL.Nat64, IDL.Nat64, IDL.Nat64],
+			[IDL.Vec(PortfolioSnapshot)]
+		),
+		get_recovery_request: IDL.Func([IDL.Principal], [IDL.Opt(RecoveryRequest)]),
+		get_signed_transaction: IDL.Func([IDL.Nat64], [IDL.Opt(IDL.Text)]),
+		get_transaction_history: IDL.Func(
+			[IDL.Opt(TransactionHistoryCursor), IDL.Nat64],
+			[TransactionHistoryPage],
+			[]
+		),
+		get_user_profile: IDL.Func([], [IDL.Opt(UserProfile)]),
 		http_request: IDL.Func([HttpRequest], [HttpResponse]),
-		list_custom_tokens: IDL.Func([], [IDL.Vec(CustomToken)]),
-		list_user_tokens: IDL.Func([], [IDL.Vec(UserToken)]),
+		icp_account_identifier: IDL.Func([IcrcAccount], [IDL.Text]),
+		is_notifications_enabled: IDL.Func([], [IDL.Bool]),
+		is_portfolio_history_enabled: IDL.Func([], [IDL.Bool]),
+		is_valid_icp_account_identifier: IDL.Func([IDL.Text], [IDL.Bool]),
+		is_valid_icrc_account: IDL.Func([IDL.Text], [IDL.Bool]),
+		leave_shared_account: IDL.Func([IDL.Nat64], [], []),
+		list_catalogue_tokens: IDL.Func([Network, IDL.Opt(IDL.Text)], [CatalogueTokenPage]),
+		list_contacts: IDL.Func([], [IDL.Vec(Contact)]),
+		list_custom_tokens: IDL.Func([], [IDL.Vec(ListedCustomToken)]),
+		list_denylist: IDL.Func([], [IDL.Vec(DenylistEntry)]),
+		list_notifications: IDL.Func([IDL.Opt(IDL.Nat64)], [NotificationPage]),
+		list_proposals: IDL.Func([IDL.Nat64, IDL.Opt(IDL.Nat64)], [ProposalPage]),
+		list_scheduled_transactions: IDL.Func([], [IDL.Vec(ScheduledTransaction)]),
+		list_shared_accounts: IDL.Func([], [IDL.Vec(SharedAccount)]),
+		list_supported_chains: IDL.Func([], [IDL.Vec(ChainConfig)]),
+		list_user_catalogue_tokens: IDL.Func([], [IDL.Vec(ListedUserCatalogueToken)]),
+		list_user_tokens: IDL.Func([], [IDL.Vec(ListedUserToken)]),
+		mark_notifications_read: IDL.Func([IDL.Vec(IDL.Nat64)], [], []),
+		next_nonce_hint: IDL.Func([IDL.Nat64], [IDL.Opt(IDL.Nat64)]),
 		personal_sign: IDL.Func([IDL.Text], [IDL.Text], []),
+		poll_notifications_of: IDL.Func([IDL.Principal], [], []),
+		portfolio_snapshot_of: IDL.Func([IDL.Principal], [Portfolio], []),
+		propose_transaction: IDL.Func([ProposeTransactionRequest], [IDL.Nat64], []),
+		remove_allowlist_address: IDL.Func([IDL.Text], [], []),
+		remove_catalogue_token: IDL.Func([CatalogueTokenId], [], []),
+		remove_contact: IDL.Func([IDL.Text], [], []),
+		remove_denylist_entry: IDL.Func([DenylistedId], [], []),
+		remove_user_catalogue_token: IDL.Func([CatalogueTokenId], [], []),
 		remove_user_token: IDL.Func([UserTokenId], [], []),
+		resolve_ens: IDL.Func([IDL.Text], [IDL.Opt(IDL.Text)], []),
+		reverse_ens: IDL.Func([IDL.Text], [IDL.Opt(IDL.Text)], []),
+		schedule_transaction: IDL.Func([ScheduleTransactionRequest], [IDL.Nat64], []),
+		set_catalogue_logo: IDL.Func([IDL.Text], [IDL.Text], []),
+		set_catalogue_token: IDL.Func([CatalogueToken], [], []),
+		set_chain: IDL.Func([ChainConfig], [], []),
+		set_cketh_minter: IDL.Func([IDL.Opt(CkEthMinter)], [], []),
+		set_contact: IDL.Func([Contact], [], []),
 		set_custom_token: IDL.Func([CustomToken], [], []),
+		set_custom_token_validation: IDL.Func([IDL.Bool], [], []),
+		set_denylist_entry: IDL.Func([DenylistEntry], [], []),
+		set_evm_rpc_canister: IDL.Func([IDL.Opt(IDL.Principal)], [], []),
+		set_external_call_rate_limit: IDL.Func([RateLimitConfig], [], []),
+		set_guardians: IDL.Func([GuardianConfig], [], []),
 		set_many_custom_tokens: IDL.Func([IDL.Vec(CustomToken)], [], []),
+		set_signing_rate_limit: IDL.Func([RateLimitConfig], [], []),
+		set_user_catalogue_token: IDL.Func([UserCatalogueToken], [], []),
+		shared_account_eth_address: IDL.Func([IDL.Nat64], [IDL.Text], []),
+		sign_and_send_transaction: IDL.Func([SignRequest], [SendTransactionResponse], []),
+		sign_btc_taproot_psbt: IDL.Func([IDL.Text], [IDL.Vec(TaprootSignature)], []),
 		sign_prehash: IDL.Func([IDL.Text], [IDL.Text], []),
-		sign_transaction: IDL.Func([SignRequest], [IDL.Text], [])
+		sign_scheduled_transaction: IDL.Func([IDL.Principal, SignRequest], [IDL.Text], []),
+		sign_solana_message: IDL.Func([IDL.Text], [IDL.Text], []),
+		sign_transaction: IDL.Func([SignRequest], [IDL.Text], []),
+		suggest_fees: IDL.Func([IDL.Nat64], [FeeSuggestion], []),
+		top_cycle_consumers: IDL.Func([IDL.Nat32], [IDL.Vec(CyclesConsumer)]),
+		track_cketh_conversion: IDL.Func([CkEthConversion], [], []),
+		update_user_settings: IDL.Func([UpdateUserSettingsRequest], [UserProfile], [])
 	});
 };
 // @ts-ignore
 export const init = ({ IDL }) => {
 	const InitArg = IDL.Record({
 		ecdsa_key_name: IDL.Text,
-		allowed_callers: IDL.Vec(IDL.Principal)
+		allowed_callers: IDL.Vec(IDL.Principal),
+		schnorr_key_name: IDL.Opt(IDL.Text)
 	});
 	const Arg = IDL.Variant({ Upgrade: IDL.Null, Init: InitArg });
 	return [Arg];
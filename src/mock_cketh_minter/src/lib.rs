@@ -0,0 +1,71 @@
+//! A mock of the [ckETH minter](https://github.com/dfinity/ic/tree/master/rs/ethereum/cketh/minter) for the integration tests.
+//!
+//! The status of the withdrawals is set by the tests instead of being tracked on Ethereum.
+use candid::{CandidType, Deserialize, Nat};
+use ic_cdk_macros::{query, update};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+/// The helper contract of the minter on Sepolia.
+const ETH_HELPER_CONTRACT_ADDRESS: &str = "0x2D39863d30716aaf2B7fFFd85Dd03Dda2BFC2E38";
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct MinterInfo {
+    pub eth_helper_contract_address: Option<String>,
+    pub smart_contract_address: Option<String>,
+    pub minimum_withdrawal_amount: Option<Nat>,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct EthTransaction {
+    pub transaction_hash: String,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub enum TxFinalizedStatus {
+    Success {
+        transaction_hash: String,
+        effective_transaction_fee: Option<Nat>,
+    },
+    Reimbursed {
+        transaction_hash: String,
+        reimbursed_amount: Nat,
+        reimbursed_in_block: Nat,
+    },
+    PendingReimbursement(EthTransaction),
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub enum RetrieveEthStatus {
+    NotFound,
+    Pending,
+    TxCreated,
+    TxSent(EthTransaction),
+    TxFinalized(TxFinalizedStatus),
+}
+
+thread_local! {
+    static RETRIEVE_ETH_STATUSES: RefCell<BTreeMap<u64, RetrieveEthStatus>> = RefCell::default();
+}
+
+#[query]
+fn get_minter_info() -> MinterInfo {
+    MinterInfo {
+        eth_helper_contract_address: Some(ETH_HELPER_CONTRACT_ADDRESS.to_string()),
+        smart_contract_address: Some(ETH_HELPER_CONTRACT_ADDRESS.to_string()),
+        minimum_withdrawal_amount: Some(Nat::from(30_000_000_000_000_000u64)),
+    }
+}
+
+#[query]
+fn retrieve_eth_status(block_index: u64) -> RetrieveEthStatus {
+    RETRIEVE_ETH_STATUSES
+        .with(|s| s.borrow().get(&block_index).cloned())
+        .unwrap_or(RetrieveEthStatus::NotFound)
+}
+
+/// Sets the status of the withdrawal burnt in the ledger block.
+#[update]
+fn mock_retrieve_eth_status(block_index: u64, status: RetrieveEthStatus) {
+    RETRIEVE_ETH_STATUSES.with(|s| s.borrow_mut().insert(block_index, status));
+}
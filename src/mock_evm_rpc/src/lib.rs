@@ -0,0 +1,245 @@
+//! A mock of the [EVM RPC canister](https://github.com/internet-computer-protocol/evm-rpc-canister) for the integration tests.
+//!
+//! The responses are set by the tests instead of being fetched from RPC providers.
+use candid::{CandidType, Deserialize};
+use ic_cdk_macros::{query, update};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug)]
+pub enum EthMainnetService {
+    Alchemy,
+    Ankr,
+    BlockPi,
+    PublicNode,
+    Cloudflare,
+    Llama,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug)]
+pub enum EthSepoliaService {
+    Alchemy,
+    Ankr,
+    BlockPi,
+    PublicNode,
+    Sepolia,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum RpcService {
+    EthMainnet(EthMainnetService),
+    EthSepolia(EthSepoliaService),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum RpcServices {
+    EthMainnet(Option<Vec<EthMainnetService>>),
+    EthSepolia(Option<Vec<EthSepoliaService>>),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RpcConfig {
+    #[serde(rename = "responseSizeEstimate")]
+    pub response_size_estimate: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum RpcError {
+    JsonRpcError(JsonRpcError),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum SendRawTransactionStatus {
+    Ok(Option<String>),
+    NonceTooLow,
+    NonceTooHigh,
+    InsufficientFunds,
+}
+
+pub type SendRawTransactionResult = Result<SendRawTransactionStatus, RpcError>;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum MultiRpcResult<T> {
+    Consistent(T),
+    Inconsistent(Vec<(RpcService, T)>),
+}
+
+thread_local! {
+    /// The results of the providers, in the order of the services of the request. All providers succeed if empty.
+    static SEND_RAW_TRANSACTION_RESULTS: RefCell<Vec<SendRawTransactionResult>> = RefCell::default();
+    static RAW_TRANSACTIONS: RefCell<Vec<String>> = RefCell::default();
+    /// The hex-encoded balances keyed by the lowercase address of the ERC-20 contract, or by `native`.
+    static BALANCES: RefCell<BTreeMap<String, String>> = RefCell::default();
+    /// The providers that answer `0x0` to every call.
+    static FAULTY_PROVIDERS: RefCell<Vec<String>> = RefCell::default();
+    /// The results of `eth_call` keyed by the lowercase contract address and data.
+    static ETH_CALLS: RefCell<BTreeMap<(String, String), String>> = RefCell::default();
+    /// The status of the receipts keyed by the lowercase transaction hash, the others are pending.
+    static RECEIPTS: RefCell<BTreeMap<String, String>> = RefCell::default();
+    /// The result of `eth_feeHistory`.
+    static FEE_HISTORY: RefCell<Value> = RefCell::default();
+}
+
+fn services(services: RpcServices) -> Vec<RpcService> {
+    match services {
+        RpcServices::EthMainnet(services) => services
+            .unwrap_or(vec![EthMainnetService::Ankr, EthMainnetService::Cloudflare])
+            .into_iter()
+            .map(RpcService::EthMainnet)
+            .collect(),
+        RpcServices::EthSepolia(services) => services
+            .unwrap_or(vec![EthSepoliaService::Ankr, EthSepoliaService::Sepolia])
+            .into_iter()
+            .map(RpcService::EthSepolia)
+            .collect(),
+    }
+}
+
+#[update(name = "eth_sendRawTransaction")]
+fn eth_send_raw_transaction(
+    services: RpcServices,
+    _config: Option<RpcConfig>,
+    raw_transaction: String,
+) -> MultiRpcResult<SendRawTransactionResult> {
+    RAW_TRANSACTIONS.with(|t| t.borrow_mut().push(raw_transaction));
+
+    let results = SEND_RAW_TRANSACTION_RESULTS.with(|r| r.borrow().clone());
+    if results.is_empty() {
+        return MultiRpcResult::Consistent(Ok(SendRawTransactionStatus::Ok(None)));
+    }
+
+    MultiRpcResult::Inconsistent(self::services(services).into_iter().zip(results).collect())
+}
+
+/// Sets the results of the providers of the next `eth_sendRawTransaction` calls.
+#[update]
+fn mock_send_raw_transaction_results(results: Vec<SendRawTransactionResult>) {
+    SEND_RAW_TRANSACTION_RESULTS.with(|r| *r.borrow_mut() = results);
+}
+
+/// Returns the raw transactions sent so far.
+#[query]
+fn raw_transactions() -> Vec<String> {
+    RAW_TRANSACTIONS.with(|t| t.borrow().clone())
+}
+
+/// Answers a JSON-RPC call as a provider would, from the mocked state.
+fn json_rpc_result(call: &Value) -> Value {
+    let balance = |key: &str| {
+        BALANCES
+            .with(|b| b.borrow().get(key).cloned())
+            .unwrap_or("0x0".to_string())
+    };
+
+    match call["method"].as_str() {
+        Some("eth_getBalance") => json!(balance("native")),
+        Some("eth_call") => {
+            let to = call["params"][0]["to"]
+                .as_str()
+                .unwrap_or_default()
+                .to_lowercase();
+            let data = call["params"][0]["data"]
+                .as_str()
+                .unwrap_or_default()
+                .to_lowercase();
+            if let Some(result) = ETH_CALLS.with(|c| c.borrow().get(&(to.clone(), data)).cloned()) {
+                return json!(result);
+            }
+            // The other calls are ERC-20 `balanceOf` calls.
+            let balance = balance(&to);
+            json!(format!("0x{:0>64}", balance.trim_start_matches("0x")))
+        }
+        Some("eth_feeHistory") => FEE_HISTORY.with(|h| h.borrow().clone()),
+        Some("eth_getTransactionReceipt") => {
+            let hash = call["params"][0]
+                .as_str()
+                .unwrap_or_default()
+                .to_lowercase();
+            RECEIPTS.with(|r| {
+                r.borrow().get(&hash).map_or(
+                    Value::Null,
+                    |status| json!({ "transactionHash": hash, "status": status }),
+                )
+            })
+        }
+        _ => Value::Null,
+    }
+}
+
+/// Answers a single or a batch JSON-RPC request.
+#[update]
+fn request(
+    service: RpcService,
+    json: String,
+    _max_response_bytes: u64,
+) -> Result<String, RpcError> {
+    let request: Value = serde_json::from_str(&json).map_err(|err| {
+        RpcError::JsonRpcError(JsonRpcError {
+            code: -32700,
+            message: err.to_string(),
+        })
+    })?;
+
+    let faulty = FAULTY_PROVIDERS.with(|f| f.borrow().contains(&format!("{service:?}")));
+    let respond = |call: &Value| {
+        let result = if faulty {
+            json!("0x0")
+        } else {
+            json_rpc_result(call)
+        };
+        json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })
+    };
+
+    let response = match request {
+        Value::Array(calls) => Value::Array(calls.iter().map(respond).collect()),
+        call => respond(&call),
+    };
+    Ok(response.to_string())
+}
+
+/// Sets the hex-encoded balance of an ERC-20 contract, or the native balance if the contract is not set.
+#[update]
+fn mock_balance(contract_address: Option<String>, balance: String) {
+    let key = contract_address.map_or("native".to_string(), |a| a.to_lowercase());
+    BALANCES.with(|b| b.borrow_mut().insert(key, balance));
+}
+
+/// Sets the providers that answer `0x0` to every call, e.g. `EthSepolia(Ankr)`.
+#[update]
+fn mock_faulty_providers(providers: Vec<String>) {
+    FAULTY_PROVIDERS.with(|f| *f.borrow_mut() = providers);
+}
+
+/// Sets the JSON result of `eth_feeHistory`.
+#[update]
+fn mock_fee_history(result: String) {
+    let result = serde_json::from_str(&result).expect("the fee history should be valid JSON");
+    FEE_HISTORY.with(|h| *h.borrow_mut() = result);
+}
+
+/// Sets the hex-encoded result of an `eth_call` of a contract with the given data.
+#[update]
+fn mock_eth_call(contract_address: String, data: String, result: String) {
+    ETH_CALLS.with(|c| {
+        c.borrow_mut().insert(
+            (contract_address.to_lowercase(), data.to_lowercase()),
+            result,
+        )
+    });
+}
+
+/// Sets the hex-encoded status of the receipt of a transaction, `0x1` if it succeeded.
+#[update]
+fn mock_transaction_receipt(transaction_hash: String, status: String) {
+    RECEIPTS.with(|r| {
+        r.borrow_mut()
+            .insert(transaction_hash.to_lowercase(), status)
+    });
+}
@@ -0,0 +1,243 @@
+//! A mock of an [ICRC-1](https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-1) ledger for the integration tests.
+//!
+//! The balances are set by the tests instead of being the result of transfers. The ledger is also its own index,
+//! serving the transactions the tests record.
+use candid::{CandidType, Deserialize, Int, Nat, Principal};
+use ic_cdk_macros::{query, update};
+use serde_bytes::ByteBuf;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+pub const SYMBOL: &str = "MCK";
+pub const DECIMALS: u8 = 8;
+pub const FEE: u64 = 10_000;
+
+#[derive(CandidType, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Account {
+    pub owner: Principal,
+    pub subaccount: Option<ByteBuf>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub enum MetadataValue {
+    Nat(Nat),
+    Int(Int),
+    Text(String),
+    Blob(ByteBuf),
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct Transfer {
+    pub from: Account,
+    pub to: Account,
+    pub amount: Nat,
+    pub fee: Option<Nat>,
+    pub memo: Option<ByteBuf>,
+    pub created_at_time: Option<u64>,
+    pub spender: Option<Account>,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct Mint {
+    pub to: Account,
+    pub amount: Nat,
+    pub memo: Option<ByteBuf>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct Burn {
+    pub from: Account,
+    pub amount: Nat,
+    pub memo: Option<ByteBuf>,
+    pub created_at_time: Option<u64>,
+    pub spender: Option<Account>,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct Transaction {
+    pub kind: String,
+    pub mint: Option<Mint>,
+    pub burn: Option<Burn>,
+    pub transfer: Option<Transfer>,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct TransactionWithId {
+    pub id: Nat,
+    pub transaction: Transaction,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct GetAccountTransactionsArgs {
+    pub account: Account,
+    pub start: Option<Nat>,
+    pub max_results: Nat,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct GetTransactions {
+    pub balance: Nat,
+    pub transactions: Vec<TransactionWithId>,
+    pub oldest_tx_id: Option<Nat>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub enum GetIndexPrincipalError {
+    IndexPrincipalNotSet,
+    GenericError {
+        error_code: Nat,
+        description: String,
+    },
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct GetTransactionsErr {
+    pub message: String,
+}
+
+thread_local! {
+    static BALANCES: RefCell<BTreeMap<Account, Nat>> = RefCell::default();
+    static TRANSACTIONS: RefCell<Vec<TransactionWithId>> = RefCell::default();
+    static INDEX_LEDGER_ID: RefCell<Option<Principal>> = RefCell::default();
+}
+
+impl Transaction {
+    fn involves(&self, account: &Account) -> bool {
+        self.transfer
+            .as_ref()
+            .map_or(false, |t| &t.from == account || &t.to == account)
+            || self.mint.as_ref().map_or(false, |m| &m.to == account)
+            || self.burn.as_ref().map_or(false, |b| &b.from == account)
+    }
+}
+
+#[query]
+fn icrc1_metadata() -> Vec<(String, MetadataValue)> {
+    vec![
+        (
+            "icrc1:symbol".to_string(),
+            MetadataValue::Text(SYMBOL.to_string()),
+        ),
+        (
+            "icrc1:decimals".to_string(),
+            MetadataValue::Nat(Nat::from(DECIMALS)),
+        ),
+        ("icrc1:fee".to_string(), MetadataValue::Nat(Nat::from(FEE))),
+    ]
+}
+
+#[query]
+fn icrc1_balance_of(account: Account) -> Nat {
+    BALANCES
+        .with(|b| b.borrow().get(&account).cloned())
+        .unwrap_or_default()
+}
+
+/// Sets the balance of the account.
+#[update]
+fn mock_balance(account: Account, balance: Nat) {
+    BALANCES.with(|b| b.borrow_mut().insert(account, balance));
+}
+
+/// The ledger designates itself as its index.
+#[query]
+fn icrc106_get_index_principal() -> Result<Principal, GetIndexPrincipalError> {
+    Ok(ic_cdk::id())
+}
+
+/// The ledger the index claims to belong to, itself unless mocked otherwise.
+#[query]
+fn ledger_id() -> Principal {
+    INDEX_LEDGER_ID.with(|l| l.borrow().unwrap_or_else(ic_cdk::id))
+}
+
+/// Sets the ledger the index claims to belong to.
+#[update]
+fn mock_ledger_id(ledger_id: Principal) {
+    INDEX_LEDGER_ID.with(|l| *l.borrow_mut() = Some(ledger_id));
+}
+
+/// Returns the transactions of the account, most recent first, starting after the `start` transaction.
+#[query]
+fn get_account_transactions(
+    args: GetAccountTransactionsArgs,
+) -> Result<GetTransactions, GetTransactionsErr> {
+    let transactions: Vec<TransactionWithId> = TRANSACTIONS.with(|t| {
+        t.borrow()
+            .iter()
+            .filter(|t| t.transaction.involves(&args.account))
+            .cloned()
+            .collect()
+    });
+
+    Ok(GetTransactions {
+        balance: icrc1_balance_of(args.account),
+        oldest_tx_id: transactions.first().map(|t| t.id.clone()),
+        transactions: transactions
+            .into_iter()
+            .rev()
+            .filter(|t| args.start.as_ref().map_or(true, |start| &t.id < start))
+            .take(usize::try_from(&args.max_results.0).unwrap_or(usize::MAX))
+            .collect(),
+    })
+}
+
+fn record(kind: &str, mint: Option<Mint>, burn: Option<Burn>, transfer: Option<Transfer>) -> Nat {
+    TRANSACTIONS.with(|t| {
+        let mut transactions = t.borrow_mut();
+        let id = Nat::from(transactions.len());
+        transactions.push(TransactionWithId {
+            id: id.clone(),
+            transaction: Transaction {
+                kind: kind.to_string(),
+                mint,
+                burn,
+                transfer,
+                timestamp: ic_cdk::api::time(),
+            },
+        });
+        id
+    })
+}
+
+/// Records a transfer at the current time, without changing the balances, returns its id.
+#[update]
+fn mock_transfer(from: Account, to: Account, amount: Nat) -> Nat {
+    let transfer = Transfer {
+        from,
+        to,
+        amount,
+        fee: Some(Nat::from(FEE)),
+        memo: None,
+        created_at_time: None,
+        spender: None,
+    };
+    record("transfer", None, None, Some(transfer))
+}
+
+/// Records a mint at the current time, without changing the balances, returns its id.
+#[update]
+fn mock_mint(to: Account, amount: Nat) -> Nat {
+    let mint = Mint {
+        to,
+        amount,
+        memo: None,
+        created_at_time: None,
+    };
+    record("mint", Some(mint), None, None)
+}
+
+/// Records a burn at the current time, without changing the balances, returns its id.
+#[update]
+fn mock_burn(from: Account, amount: Nat) -> Nat {
+    let burn = Burn {
+        from,
+        amount,
+        memo: None,
+        created_at_time: None,
+        spender: None,
+    };
+    record("burn", None, Some(burn), None)
+}
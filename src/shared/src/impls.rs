@@ -1,5 +1,8 @@
+use crate::types::catalogue::{CatalogueToken, UserCatalogueToken};
+use crate::types::contact::Contact;
 use crate::types::custom_token::{CustomToken, CustomTokenId, Token};
 use crate::types::token::UserToken;
+use crate::types::user_profile::UserProfile;
 use crate::types::{TokenVersion, Version};
 
 impl From<&Token> for CustomTokenId {
@@ -33,3 +36,51 @@ impl TokenVersion for CustomToken {
         cloned
     }
 }
+
+impl TokenVersion for Contact {
+    fn get_version(&self) -> Option<Version> {
+        self.version
+    }
+
+    fn clone_with_incremented_version(&self) -> Self {
+        let mut cloned = self.clone();
+        cloned.version = Some(cloned.version.unwrap_or_default() + 1);
+        cloned
+    }
+}
+
+impl TokenVersion for UserProfile {
+    fn get_version(&self) -> Option<Version> {
+        self.version
+    }
+
+    fn clone_with_incremented_version(&self) -> Self {
+        let mut cloned = self.clone();
+        cloned.version = Some(cloned.version.unwrap_or_default() + 1);
+        cloned
+    }
+}
+
+impl TokenVersion for CatalogueToken {
+    fn get_version(&self) -> Option<Version> {
+        self.version
+    }
+
+    fn clone_with_incremented_version(&self) -> Self {
+        let mut cloned = self.clone();
+        cloned.version = Some(cloned.version.unwrap_or_default() + 1);
+        cloned
+    }
+}
+
+impl TokenVersion for UserCatalogueToken {
+    fn get_version(&self) -> Option<Version> {
+        self.version
+    }
+
+    fn clone_with_incremented_version(&self) -> Self {
+        let mut cloned = self.clone();
+        cloned.version = Some(cloned.version.unwrap_or_default() + 1);
+        cloned
+    }
+}
@@ -5,18 +5,20 @@ use crate::http::HttpResponse;
 use core::arch::wasm32::memory_size as wasm_memory_size;
 #[cfg(target_arch = "wasm32")]
 use ic_cdk::api::stable::stable64_size;
-use ic_metrics_encoder::MetricsEncoder;
+pub use ic_metrics_encoder::MetricsEncoder;
 use serde_bytes::ByteBuf;
 /// The Wasm page size as defined in [the Wasm Spec](https://webassembly.github.io/spec/core/exec/runtime.html#memory-instances).
 #[cfg(target_arch = "wasm32")]
 const WASM_PAGE_SIZE: u64 = 65536;
 const GIBIBYTE: u64 = 1 << 30;
 
-/// Returns the metrics in the Prometheus format.
-pub fn get_metrics() -> HttpResponse {
+/// Returns the metrics in the Prometheus format, followed by the ones specific to the canister.
+pub fn get_metrics(
+    encode_canister_metrics: impl FnOnce(&mut MetricsEncoder<Vec<u8>>) -> std::io::Result<()>,
+) -> HttpResponse {
     let now = ic_cdk::api::time();
     let mut writer = MetricsEncoder::new(vec![], (now / 1_000_000) as i64);
-    match encode_metrics(&mut writer) {
+    match encode_metrics(&mut writer).and_then(|()| encode_canister_metrics(&mut writer)) {
         Ok(()) => {
             let body = writer.into_inner();
             HttpResponse {
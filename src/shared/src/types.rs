@@ -5,6 +5,8 @@ use std::fmt::Debug;
 pub struct InitArg {
     pub ecdsa_key_name: String,
     pub allowed_callers: Vec<Principal>,
+    /// The name of the threshold Schnorr key, the ECDSA key name is used when not set.
+    pub schnorr_key_name: Option<String>,
 }
 
 #[derive(CandidType, Deserialize)]
@@ -16,7 +18,7 @@ pub enum Arg {
 pub mod transaction {
     use candid::{CandidType, Deserialize, Nat};
 
-    #[derive(CandidType, Deserialize)]
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
     pub struct SignRequest {
         pub chain_id: Nat,
         pub to: String,
@@ -27,6 +29,31 @@ pub mod transaction {
         pub nonce: Nat,
         pub data: Option<String>,
     }
+
+    /// The outcome of the broadcast of a transaction by an RPC provider.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub enum SendTransactionStatus {
+        Ok,
+        NonceTooLow,
+        NonceTooHigh,
+        InsufficientFunds,
+        /// The provider could not be reached or returned an error.
+        Error(String),
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ProviderSendResult {
+        pub provider: String,
+        pub status: SendTransactionStatus,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct SendTransactionResponse {
+        pub transaction_hash: String,
+        /// The hex-encoded signed raw transaction, it can be broadcast again if no provider accepted it.
+        pub raw_transaction: String,
+        pub provider_results: Vec<ProviderSendResult>,
+    }
 }
 
 pub type Version = u64;
@@ -54,6 +81,17 @@ pub mod token {
         pub version: Option<Version>,
     }
 
+    /// A `UserToken` as listed, flagged when the controllers denylisted it after it was added.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ListedUserToken {
+        pub contract_address: String,
+        pub chain_id: ChainId,
+        pub symbol: Option<String>,
+        pub decimals: Option<u8>,
+        pub version: Option<Version>,
+        pub denylisted: Option<bool>,
+    }
+
     #[derive(CandidType, Deserialize, Clone)]
     pub struct UserTokenId {
         pub contract_address: String,
@@ -61,10 +99,32 @@ pub mod token {
     }
 }
 
+/// EVM chains the backend is willing to sign for
+pub mod chain {
+    use crate::types::token::ChainId;
+    use candid::{CandidType, Deserialize};
+
+    #[derive(CandidType, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+    pub enum TransactionType {
+        Legacy,
+        Eip1559,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ChainConfig {
+        pub chain_id: ChainId,
+        pub name: String,
+        pub native_symbol: String,
+        pub supported_tx_types: Vec<TransactionType>,
+        pub max_gas_limit: u64,
+        pub enabled: bool,
+    }
+}
+
 /// Extendable custom user defined tokens
 pub mod custom_token {
     use crate::types::Version;
-    use candid::{CandidType, Deserialize, Principal};
+    use candid::{CandidType, Deserialize, Nat, Principal};
 
     pub type LedgerId = Principal;
     pub type IndexId = Principal;
@@ -87,8 +147,784 @@ pub mod custom_token {
         pub version: Option<Version>,
     }
 
+    /// A `CustomToken` as listed, flagged when the controllers denylisted it after it was added.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ListedCustomToken {
+        pub token: Token,
+        pub enabled: bool,
+        pub version: Option<Version>,
+        pub denylisted: Option<bool>,
+    }
+
     #[derive(CandidType, Deserialize, Clone, Eq, PartialEq)]
     pub enum CustomTokenId {
         Icrc(LedgerId),
     }
+
+    /// The metadata of an ICRC ledger, cached once the ledger and its index have been validated.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct IcrcTokenMetadata {
+        pub symbol: String,
+        pub decimals: u8,
+        pub fee: Nat,
+        /// The hex-encoded SHA-256 hash of the logo, if the ledger has one.
+        pub logo_hash: Option<String>,
+        /// The index canister confirmed to belong to the ledger, if any.
+        pub index_id: Option<IndexId>,
+        pub validated_timestamp: u64,
+    }
+}
+
+/// Labelled recipients of the user's address book
+pub mod contact {
+    pub use crate::types::account::IcrcAccount;
+    use crate::types::Version;
+    use candid::{CandidType, Deserialize};
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub enum ContactAddress {
+        Ethereum(String),
+        Icrc(IcrcAccount),
+        Bitcoin(String),
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct Contact {
+        pub label: String,
+        pub addresses: Vec<ContactAddress>,
+        pub version: Option<Version>,
+    }
+}
+
+/// Settings and preferences of the user, kept across devices
+pub mod user_profile {
+    use crate::types::Version;
+    use candid::{CandidType, Deserialize};
+
+    /// All the settings are optional so that new ones can be added without breaking existing clients.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+    pub struct UserSettings {
+        pub hidden_tokens: Option<Vec<String>>,
+        pub preferred_networks: Option<Vec<String>>,
+        pub fiat_currency: Option<String>,
+        pub show_testnets: Option<bool>,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct UserProfile {
+        pub settings: UserSettings,
+        pub created_timestamp: u64,
+        pub updated_timestamp: u64,
+        pub version: Option<Version>,
+    }
+
+    #[derive(CandidType, Deserialize, Clone)]
+    pub struct UpdateUserSettingsRequest {
+        pub settings: UserSettings,
+        pub current_user_version: Option<Version>,
+    }
+}
+
+/// Limits of the signing calls a principal can make
+pub mod rate_limit {
+    use candid::{CandidType, Deserialize};
+
+    /// A token bucket holding up to `capacity` calls, refilled by one call every `refill_interval_secs`.
+    #[derive(CandidType, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+    pub struct RateLimitConfig {
+        pub capacity: u32,
+        pub refill_interval_secs: u64,
+    }
+}
+
+/// Cycles spent by the canister on behalf of the users
+pub mod cycles {
+    use candid::{CandidType, Deserialize, Principal};
+
+    #[derive(CandidType, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+    pub struct CyclesUsage {
+        pub cycles: u128,
+        pub signatures: u64,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct CyclesConsumer {
+        pub principal: Principal,
+        pub usage: CyclesUsage,
+    }
+}
+
+/// Bitcoin Taproot key path spends
+pub mod taproot {
+    use candid::{CandidType, Deserialize};
+
+    /// The hex-encoded BIP-340 signature of a PSBT input, using the default sighash type.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct TaprootSignature {
+        pub input_index: u32,
+        pub signature: String,
+    }
+}
+
+/// Tokens curated by the controllers and shared by all the users
+pub mod catalogue {
+    use crate::types::custom_token::{IndexId, LedgerId};
+    use crate::types::token::ChainId;
+    use crate::types::Version;
+    use candid::{CandidType, Deserialize};
+
+    #[derive(CandidType, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+    pub enum Network {
+        InternetComputer,
+        Evm(ChainId),
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub enum CatalogueTokenId {
+        Erc20 {
+            chain_id: ChainId,
+            contract_address: String,
+        },
+        Icrc(LedgerId),
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+    pub enum RiskFlag {
+        Unverified,
+        Deprecated,
+        LowLiquidity,
+        Bridged,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct CatalogueToken {
+        pub id: CatalogueTokenId,
+        pub symbol: String,
+        pub name: String,
+        pub decimals: u8,
+        /// The hash of a logo stored with `set_catalogue_logo`.
+        pub logo_hash: Option<String>,
+        pub index_id: Option<IndexId>,
+        pub risk_flags: Vec<RiskFlag>,
+        /// The networks the token is listed on.
+        pub enabled_networks: Vec<Network>,
+        pub version: Option<Version>,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct CatalogueTokenPage {
+        pub tokens: Vec<CatalogueToken>,
+        /// Passed to the next call to continue the listing, `None` once all the tokens have been listed.
+        pub next_cursor: Option<String>,
+    }
+
+    /// A reference of a user to a catalogue token, the symbol and name set by the user override the catalogue ones.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct UserCatalogueToken {
+        pub id: CatalogueTokenId,
+        pub enabled: bool,
+        pub symbol: Option<String>,
+        pub name: Option<String>,
+        pub version: Option<Version>,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ListedUserCatalogueToken {
+        pub user_token: UserCatalogueToken,
+        /// The current catalogue entry with the overrides of the user, `None` once it was removed from the catalogue.
+        pub token: Option<CatalogueToken>,
+        /// Set when the controllers denylisted the token after it was added.
+        pub denylisted: Option<bool>,
+    }
+}
+
+/// Scam and phishing contracts and ledgers blocked by the controllers
+pub mod denylist {
+    use crate::types::custom_token::LedgerId;
+    use crate::types::token::ChainId;
+    use candid::{CandidType, Deserialize};
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub enum DenylistedId {
+        /// A token contract or an approval spender.
+        Evm {
+            chain_id: ChainId,
+            address: String,
+        },
+        Icrc(LedgerId),
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct DenylistEntry {
+        pub id: DenylistedId,
+        pub reason: String,
+    }
+}
+
+/// Recipients a user restricted their Ethereum transactions to
+pub mod allowlist {
+    use candid::{CandidType, Deserialize};
+
+    /// An address waiting for the end of its confirmation period.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct PendingAllowlistAddress {
+        pub address: String,
+        pub confirmation_timestamp: u64,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug, Default)]
+    pub struct Allowlist {
+        /// Whether `sign_transaction` only signs for the allowlisted recipients.
+        pub enabled: bool,
+        pub addresses: Vec<String>,
+        pub pending_addresses: Vec<PendingAllowlistAddress>,
+        /// When the allowlist mode turns off, set while the disabling is time-locked.
+        pub pending_disable_timestamp: Option<u64>,
+    }
+}
+
+/// Transactions signed once their release time has come
+pub mod scheduled_transaction {
+    use crate::types::transaction::SignRequest;
+    use candid::{CandidType, Deserialize};
+
+    pub type ScheduledTransactionId = u64;
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ScheduleTransactionRequest {
+        pub request: SignRequest,
+        /// The time, in nanoseconds since the epoch, after which the transaction is signed.
+        pub release_timestamp: u64,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub enum ScheduledTransactionStatus {
+        Pending,
+        /// The hex-encoded signed raw transaction.
+        Signed(String),
+        Cancelled,
+        /// The transaction was no longer allowed at its release time, or could not be signed.
+        Failed {
+            reason: String,
+        },
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ScheduledTransaction {
+        pub id: ScheduledTransactionId,
+        pub request: SignRequest,
+        pub created_timestamp: u64,
+        pub release_timestamp: u64,
+        pub status: ScheduledTransactionStatus,
+    }
+}
+
+/// Recovery of the keys of a user by their guardians
+pub mod recovery {
+    use candid::{CandidType, Deserialize, Principal};
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct GuardianConfig {
+        pub guardians: Vec<Principal>,
+        /// The number of guardians that should approve a recovery.
+        pub threshold: u8,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ApproveRecoveryRequest {
+        /// The user whose keys are recovered.
+        pub user: Principal,
+        /// The principal taking control of the keys.
+        pub new_principal: Principal,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct RecoveryRequest {
+        pub new_principal: Principal,
+        pub approvals: Vec<Principal>,
+        pub created_timestamp: u64,
+        /// When the new principal can complete the recovery, set once the threshold is reached.
+        pub unlock_timestamp: Option<u64>,
+    }
+}
+
+/// Accounts controlled by several principals, signing once enough members approved
+pub mod shared_account {
+    use crate::types::transaction::SignRequest;
+    use candid::{CandidType, Deserialize, Principal};
+
+    pub type SharedAccountId = u64;
+    pub type ProposalId = u64;
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct CreateSharedAccountRequest {
+        pub name: String,
+        /// The members, including the caller.
+        pub members: Vec<Principal>,
+        /// The number of members that should approve a transaction.
+        pub threshold: u8,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct SharedAccount {
+        pub id: SharedAccountId,
+        pub name: String,
+        pub members: Vec<Principal>,
+        pub threshold: u8,
+        pub created_by: Principal,
+        pub created_timestamp: u64,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ProposeTransactionRequest {
+        pub account_id: SharedAccountId,
+        pub request: SignRequest,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+    pub struct ProposalRef {
+        pub account_id: SharedAccountId,
+        pub proposal_id: ProposalId,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct Approval {
+        pub member: Principal,
+        pub timestamp: u64,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub enum ProposalStatus {
+        Open,
+        /// The threshold is reached, the transaction is being signed.
+        Approved,
+        /// The hex-encoded signed raw transaction.
+        Signed(String),
+        Cancelled,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct Proposal {
+        pub id: ProposalId,
+        pub request: SignRequest,
+        pub proposer: Principal,
+        pub created_timestamp: u64,
+        pub approvals: Vec<Approval>,
+        pub status: ProposalStatus,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ProposalPage {
+        /// The proposals, oldest first.
+        pub proposals: Vec<Proposal>,
+        /// Passed to the next call to continue the listing, `None` once all the proposals have been listed.
+        pub next_cursor: Option<ProposalId>,
+    }
+}
+
+pub mod balance {
+    use crate::types::token::ChainId;
+    use candid::{CandidType, Deserialize, Nat};
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct TokenBalance {
+        /// The address of the ERC-20 contract, `None` for the native token of the chain.
+        pub contract_address: Option<String>,
+        /// The balance a majority of the RPC providers agree on, in the smallest unit of the token.
+        pub balance: Result<Nat, String>,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct EthBalances {
+        pub chain_id: ChainId,
+        pub address: String,
+        pub balances: Vec<TokenBalance>,
+    }
+}
+
+pub mod fee {
+    use crate::types::token::ChainId;
+    use candid::{CandidType, Deserialize, Nat};
+
+    /// The fees of an [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) transaction, as set in a `SignRequest`.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct FeeEstimate {
+        pub max_fee_per_gas: Nat,
+        pub max_priority_fee_per_gas: Nat,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct FeeSuggestion {
+        pub chain_id: ChainId,
+        /// The base fee of the next block.
+        pub base_fee_per_gas: Nat,
+        pub low: FeeEstimate,
+        pub medium: FeeEstimate,
+        pub high: FeeEstimate,
+        /// The time the fee history was fetched, in nanoseconds since the epoch.
+        pub timestamp: u64,
+    }
+}
+
+pub mod cketh {
+    use crate::types::token::ChainId;
+    use candid::{CandidType, Deserialize, Nat, Principal};
+
+    /// The ckETH minter and the chain of the ETH it converts.
+    #[derive(CandidType, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+    pub struct CkEthMinter {
+        pub canister_id: Principal,
+        pub chain_id: ChainId,
+    }
+
+    /// The amount and fees of a deposit of ETH to the ckETH helper contract.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct CkEthDepositArgs {
+        pub chain_id: ChainId,
+        /// The amount of ETH to convert, in wei.
+        pub amount: Nat,
+        pub nonce: Nat,
+        pub max_fee_per_gas: Nat,
+        pub max_priority_fee_per_gas: Nat,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub enum CkEthConversion {
+        /// A deposit of ETH to the helper contract, minting ckETH.
+        Deposit {
+            chain_id: ChainId,
+            transaction_hash: String,
+        },
+        /// A withdrawal of ckETH to ETH, identified by the index of the burn block on the ckETH ledger.
+        Withdrawal { ledger_block_index: u64 },
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct TrackedConversion {
+        pub conversion: CkEthConversion,
+        pub created_timestamp: u64,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub enum ConversionState {
+        /// The deposit is not mined yet, or the minter has not sent the withdrawal transaction yet.
+        Pending,
+        /// The minter sent the withdrawal transaction, it is not finalized yet.
+        Sent { transaction_hash: String },
+        /// The deposit is mined, the minter mints the ckETH once it sees it, or the withdrawal is finalized.
+        Completed { transaction_hash: String },
+        /// The deposit reverted, or the withdrawal failed and the ckETH is reimbursed.
+        Failed { reason: String },
+        /// The minter does not know the withdrawal.
+        NotFound,
+        /// The status could not be fetched.
+        Error(String),
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct ConversionStatus {
+        pub conversion: TrackedConversion,
+        pub state: ConversionState,
+    }
+}
+
+/// The encoding of [ICRC-1 accounts](https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-1/TextualEncoding.md)
+/// and of the [account identifiers](https://internetcomputer.org/docs/current/references/ledger#_accounts) of the ICP ledger.
+pub mod account {
+    use candid::{CandidType, Deserialize, Principal};
+    use serde_bytes::ByteBuf;
+    use sha2::{Digest, Sha224};
+
+    pub type Subaccount = [u8; 32];
+
+    const DEFAULT_SUBACCOUNT: Subaccount = [0; 32];
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct IcrcAccount {
+        pub owner: Principal,
+        /// `None` and the all-zero subaccount are the same, default, subaccount.
+        pub subaccount: Option<ByteBuf>,
+    }
+
+    impl IcrcAccount {
+        /// Returns the subaccount, the default one if not set, or an error if it is not 32 bytes long.
+        pub fn effective_subaccount(&self) -> Result<Subaccount, String> {
+            match &self.subaccount {
+                None => Ok(DEFAULT_SUBACCOUNT),
+                Some(subaccount) => subaccount
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| "The subaccount must be 32 bytes long".to_string()),
+            }
+        }
+    }
+
+    /// The CRC-32 (ISO-HDLC) of the data, as used by both encodings.
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for byte in data {
+            crc ^= u32::from(*byte);
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    /// Lowercase RFC 4648 base32, without padding, as in the textual encoding of principals.
+    fn base32(data: &[u8]) -> String {
+        const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+        let mut encoded = String::new();
+        let (mut buffer, mut bits) = (0u32, 0u32);
+        for byte in data {
+            buffer = (buffer << 8) | u32::from(*byte);
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                encoded.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+            }
+        }
+        if bits > 0 {
+            encoded.push(ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+        }
+        encoded
+    }
+
+    fn checksum(owner: &Principal, subaccount: &Subaccount) -> String {
+        let mut data = owner.as_slice().to_vec();
+        data.extend_from_slice(subaccount);
+        base32(&crc32(&data).to_be_bytes())
+    }
+
+    /// Encodes the account as `owner` for the default subaccount, else as `owner-checksum.subaccount`.
+    pub fn encode_icrc_account(account: &IcrcAccount) -> Result<String, String> {
+        let subaccount = account.effective_subaccount()?;
+        if subaccount == DEFAULT_SUBACCOUNT {
+            return Ok(account.owner.to_text());
+        }
+        let hex = hex::encode(subaccount);
+        Ok(format!(
+            "{}-{}.{}",
+            account.owner.to_text(),
+            checksum(&account.owner, &subaccount),
+            hex.trim_start_matches('0')
+        ))
+    }
+
+    /// Decodes the textual encoding of an account, rejecting non-canonical encodings.
+    pub fn decode_icrc_account(text: &str) -> Result<IcrcAccount, String> {
+        let Some((owner_and_checksum, hex)) = text.rsplit_once('.') else {
+            let owner = Principal::from_text(text).map_err(|e| format!("Invalid owner: {e}"))?;
+            return Ok(IcrcAccount {
+                owner,
+                subaccount: None,
+            });
+        };
+        let (owner, checksum_text) = owner_and_checksum
+            .rsplit_once('-')
+            .ok_or_else(|| "Missing checksum".to_string())?;
+        let owner = Principal::from_text(owner).map_err(|e| format!("Invalid owner: {e}"))?;
+
+        if hex.is_empty()
+            || hex.starts_with('0')
+            || hex.len() > 64
+            || hex.bytes().any(|b| b.is_ascii_uppercase())
+        {
+            return Err("The subaccount is not in its canonical form".to_string());
+        }
+        let mut subaccount = DEFAULT_SUBACCOUNT;
+        hex::decode_to_slice(format!("{hex:0>64}"), &mut subaccount)
+            .map_err(|_| "Invalid subaccount".to_string())?;
+        if checksum(&owner, &subaccount) != checksum_text {
+            return Err("Invalid checksum".to_string());
+        }
+        Ok(IcrcAccount {
+            owner,
+            subaccount: Some(ByteBuf::from(subaccount)),
+        })
+    }
+
+    /// Returns the hex account identifier of the account on the ICP ledger: the CRC-32 of the hash, then the hash.
+    pub fn icp_account_identifier(account: &IcrcAccount) -> Result<String, String> {
+        let hash = Sha224::new()
+            .chain_update(b"\x0Aaccount-id")
+            .chain_update(account.owner.as_slice())
+            .chain_update(account.effective_subaccount()?)
+            .finalize();
+        let mut identifier = crc32(&hash).to_be_bytes().to_vec();
+        identifier.extend_from_slice(&hash);
+        Ok(hex::encode(identifier))
+    }
+
+    /// Whether the text is a 64 hex characters account identifier with a valid checksum.
+    pub fn is_valid_icp_account_identifier(text: &str) -> bool {
+        let mut bytes = [0u8; 32];
+        if hex::decode_to_slice(text, &mut bytes).is_err() {
+            return false;
+        }
+        bytes[..4] == crc32(&bytes[4..]).to_be_bytes()
+    }
+}
+
+pub mod portfolio {
+    use crate::types::custom_token::LedgerId;
+    use crate::types::token::ChainId;
+    use candid::{CandidType, Deserialize, Nat};
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub enum PortfolioToken {
+        Icrc {
+            ledger_id: LedgerId,
+        },
+        /// The native token of the chain if there is no contract address, an ERC-20 token otherwise.
+        Evm {
+            chain_id: ChainId,
+            contract_address: Option<String>,
+        },
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct PortfolioBalance {
+        pub token: PortfolioToken,
+        pub symbol: Option<String>,
+        pub decimals: u8,
+        /// The balance in the smallest unit of the token.
+        pub balance: Nat,
+        /// The balance with the decimals applied, e.g. `1.5`.
+        pub amount: String,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct PortfolioError {
+        pub token: PortfolioToken,
+        pub error: String,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct Portfolio {
+        pub balances: Vec<PortfolioBalance>,
+        /// The tokens whose balance could not be fetched, they are not part of the balances.
+        pub errors: Vec<PortfolioError>,
+        /// The time the balances were fetched, in nanoseconds since the epoch.
+        pub timestamp: u64,
+    }
+
+    /// A balance of a snapshot, without the symbol and the formatted amount to keep the history compact.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct SnapshotBalance {
+        pub token: PortfolioToken,
+        pub decimals: u8,
+        pub balance: Nat,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct PortfolioSnapshot {
+        pub timestamp: u64,
+        pub balances: Vec<SnapshotBalance>,
+        /// The tokens whose balance could not be fetched, so that they are not mistaken for empty ones.
+        pub failed_tokens: Vec<PortfolioToken>,
+    }
+}
+
+/// The incoming transfers of the ICRC tokens of the users, found by polling the index canisters.
+pub mod notification {
+    use crate::types::account::IcrcAccount;
+    use crate::types::custom_token::LedgerId;
+    use candid::{CandidType, Deserialize, Nat};
+
+    pub type NotificationId = u64;
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct Notification {
+        pub id: NotificationId,
+        pub ledger_id: LedgerId,
+        /// The id of the transaction in the ledger.
+        pub transaction_id: Nat,
+        /// The sender of the transfer, `None` for a mint.
+        pub from: Option<IcrcAccount>,
+        /// The amount received, in the smallest unit of the token.
+        pub amount: Nat,
+        /// The time of the transaction, in nanoseconds since the epoch.
+        pub timestamp: u64,
+        pub read: bool,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct NotificationPage {
+        /// The notifications, most recent first.
+        pub notifications: Vec<Notification>,
+        /// Passed to the next call to continue the listing, `None` once all the notifications have been listed.
+        pub next_cursor: Option<NotificationId>,
+    }
+}
+
+/// The transactions of the ICRC tokens of the users, merged from the index canisters.
+pub mod transaction_history {
+    use crate::types::account::IcrcAccount;
+    use crate::types::custom_token::LedgerId;
+    use candid::{CandidType, Deserialize, Nat};
+
+    #[derive(CandidType, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+    pub enum TransactionDirection {
+        /// A transfer or a mint to the user.
+        Incoming,
+        /// A transfer or a burn from the user.
+        Outgoing,
+        /// A transfer from the user to themselves.
+        SelfTransfer,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct HistoryTransaction {
+        pub ledger_id: LedgerId,
+        /// The id of the transaction in the ledger.
+        pub id: Nat,
+        /// The kind of the transaction in the index, e.g. `transfer`, `mint` or `burn`.
+        pub kind: String,
+        pub direction: TransactionDirection,
+        /// The other account of a transfer, `None` for a mint or a burn.
+        pub counterparty: Option<IcrcAccount>,
+        pub symbol: String,
+        pub decimals: u8,
+        /// The value in the smallest unit of the token.
+        pub value: Nat,
+        /// The value with the decimals applied, e.g. `1.5`.
+        pub amount: String,
+        /// The time of the transaction, in nanoseconds since the epoch.
+        pub timestamp: u64,
+    }
+
+    /// The position of the listing in the transactions of a ledger.
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct LedgerCursor {
+        pub ledger_id: LedgerId,
+        /// The id of the last transaction listed, the listing continues with the older ones.
+        pub start: Option<Nat>,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct TransactionHistoryCursor {
+        /// The ledgers with transactions left to list, the others are not listed anymore.
+        pub ledgers: Vec<LedgerCursor>,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct TransactionHistoryError {
+        pub ledger_id: LedgerId,
+        pub error: String,
+    }
+
+    #[derive(CandidType, Deserialize, Clone, Eq, PartialEq, Debug)]
+    pub struct TransactionHistoryPage {
+        /// The transactions, most recent first.
+        pub transactions: Vec<HistoryTransaction>,
+        /// The ledgers whose transactions could not be fetched, they are not listed in the next pages.
+        pub errors: Vec<TransactionHistoryError>,
+        /// Passed to the next call to continue the listing, `None` once all the transactions have been listed.
+        pub next_cursor: Option<TransactionHistoryCursor>,
+    }
 }